@@ -0,0 +1,127 @@
+use crate::{note::load_notes, open::best_match};
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Whether the garden keeps its history in git, i.e. it was `git init`-ed
+pub fn is_repo(garden_path: &Path) -> bool {
+    garden_path.join(".git").exists()
+}
+
+/// Runs git in the garden and returns what it printed
+fn git(garden_path: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(garden_path)
+        .args(args)
+        .output()
+        .wrap_err("Failed to run git")?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Commits the changes to `note`, if there are any and the garden is a repository.
+/// Only that file is committed, so notes being written elsewhere stay out.
+pub fn commit_note(garden_path: &Path, note: &Path, message: &str) -> Result<()> {
+    if !is_repo(garden_path) {
+        return Ok(());
+    }
+    let note = relative(garden_path, note);
+    if git(garden_path, &["status", "--porcelain", "--", &note])?.is_empty() {
+        return Ok(());
+    }
+    git(garden_path, &["add", "--", &note])?;
+    git(
+        garden_path,
+        &["commit", "--quiet", "-m", message, "--", &note],
+    )?;
+    Ok(())
+}
+
+fn relative(garden_path: &Path, note: &Path) -> String {
+    note.strip_prefix(garden_path)
+        .unwrap_or(note)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn require_repo(garden_path: &Path) -> Result<()> {
+    match is_repo(garden_path) {
+        true => Ok(()),
+        false => Err(eyre!(
+            "The garden at {} is not a git repository, run `git init` in it first",
+            garden_path.display()
+        )),
+    }
+}
+
+/// Prints the commits that changed the note matching `title`, newest first
+pub fn history(garden_path: PathBuf, title: String) -> Result<()> {
+    require_repo(&garden_path)?;
+    let notes = load_notes(&garden_path)?;
+    let note = best_match(&notes, &title).ok_or_else(|| eyre!("No note matches \"{}\"", title))?;
+    let log = git(
+        &garden_path,
+        &[
+            "log",
+            "--follow",
+            "--date=short",
+            "--format=%h %ad %s",
+            "--",
+            &relative(&garden_path, &note.path),
+        ],
+    )?;
+    println!("{} ({}.md)", note.title, note.file_stem());
+    if log.is_empty() {
+        println!("  no revisions yet");
+    }
+    for line in log.lines() {
+        println!("  {}", line);
+    }
+    Ok(())
+}
+
+/// Commits what is left, then pulls from and pushes to `remote`
+pub fn sync(garden_path: PathBuf, remote: String) -> Result<()> {
+    require_repo(&garden_path)?;
+    if !git(&garden_path, &["remote"])?.lines().any(|r| r == remote) {
+        return Err(eyre!(
+            "The garden has no remote \"{}\", add one with `git -C {} remote add {} URL`",
+            remote,
+            garden_path.display(),
+            remote
+        ));
+    }
+    // notes changed outside of garden, but not the ones being written
+    let changes = git(
+        &garden_path,
+        &["status", "--porcelain", "--", "*.md", ":!.*"],
+    )?;
+    if !changes.is_empty() {
+        git(&garden_path, &["add", "--", "*.md", ":!.*"])?;
+        git(&garden_path, &["commit", "--quiet", "-m", "Sync garden"])?;
+    }
+    let branch = git(&garden_path, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+    let branch = branch.trim();
+    // a new remote has nothing to pull yet
+    if !git(&garden_path, &["ls-remote", "--heads", &remote, branch])?.is_empty() {
+        git(
+            &garden_path,
+            &["pull", "--quiet", "--rebase", &remote, branch],
+        )?;
+    }
+    git(&garden_path, &["push", "--quiet", "-u", &remote, branch])?;
+    println!("Synced with {}/{}", remote, branch);
+    Ok(())
+}
@@ -1,3 +1,16 @@
+mod git;
+mod links;
+mod list;
+mod note;
+mod open;
+mod publish;
+mod search;
 mod write;
 
+pub use git::{history, sync};
+pub use links::{graph, links};
+pub use list::list;
+pub use open::open;
+pub use publish::publish;
+pub use search::search;
 pub use write::write;
@@ -0,0 +1,74 @@
+use crate::{
+    note::{load_notes, Note},
+    open::best_match,
+};
+use color_eyre::{eyre::eyre, Result};
+use std::path::PathBuf;
+
+/// Prints the notes `title` links to and the notes linking to it
+pub fn links(garden_path: PathBuf, title: String) -> Result<()> {
+    let notes = load_notes(&garden_path)?;
+    let note = best_match(&notes, &title).ok_or_else(|| eyre!("No note matches \"{}\"", title))?;
+    println!("{} ({}.md)", note.title, note.file_stem());
+    println!("links to:");
+    for target in &note.links {
+        match resolve(&notes, target) {
+            Some(linked) => println!("  {} ({}.md)", linked.title, linked.file_stem()),
+            None => println!("  {} (missing)", target),
+        }
+    }
+    println!("linked from:");
+    for backlink in backlinks(&notes, note) {
+        println!("  {} ({}.md)", backlink.title, backlink.file_stem());
+    }
+    Ok(())
+}
+
+/// Prints the links between the notes as a DOT graph, e.g. for `dot -Tsvg`
+pub fn graph(garden_path: PathBuf) -> Result<()> {
+    print!("{}", to_dot(&load_notes(&garden_path)?));
+    Ok(())
+}
+
+/// The note a `[[target]]` link points to
+pub(crate) fn resolve<'a>(notes: &'a [Note], target: &str) -> Option<&'a Note> {
+    notes.iter().find(|note| note.is_target_of(target))
+}
+
+pub(crate) fn backlinks<'a>(notes: &'a [Note], note: &'a Note) -> impl Iterator<Item = &'a Note> {
+    notes.iter().filter(move |other| {
+        other.path != note.path && other.links.iter().any(|target| note.is_target_of(target))
+    })
+}
+
+fn to_dot(notes: &[Note]) -> String {
+    let mut dot = String::from("digraph garden {\n");
+    for note in notes {
+        dot.push_str(&format!(
+            "    {} [label={}];\n",
+            quote(&note.file_stem()),
+            quote(&note.title)
+        ));
+    }
+    for note in notes {
+        for target in &note.links {
+            // links to notes not written yet are dashed
+            let (id, style) = match resolve(notes, target) {
+                Some(linked) => (linked.file_stem(), ""),
+                None => (slug::slugify(target), " [style=dashed]"),
+            };
+            dot.push_str(&format!(
+                "    {} -> {}{};\n",
+                quote(&note.file_stem()),
+                quote(&id),
+                style
+            ));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,18 @@
+use crate::note::load_notes;
+use color_eyre::Result;
+use std::path::PathBuf;
+
+pub fn list(garden_path: PathBuf) -> Result<()> {
+    let mut notes = load_notes(&garden_path)?;
+    if notes.is_empty() {
+        eprintln!("Your garden at {} is empty", garden_path.display());
+        return Ok(());
+    }
+    // the most recent notes come first
+    notes.sort_by(|a, b| b.date.cmp(&a.date));
+    for note in notes {
+        let tags: Vec<String> = note.tags.iter().map(|tag| format!("#{}", tag)).collect();
+        println!("{}  {}  {}", note.date, note.title, tags.join(" "))
+    }
+    Ok(())
+}
@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 use color_eyre::eyre::{eyre, Context, Result};
-use digital_garden::write;
+use digital_garden::{graph, history, links, list, open, publish, search, sync, write};
 use directories::UserDirs;
 /// A CLI for the growing and curation of a digital garden
 #[derive(Parser)]
@@ -24,6 +24,65 @@ enum Command {
         /// Optionally set a title for what you are going to write about
         #[arg(short, long)]
         title: Option<String>,
+        /// Tags for the note, separated by commas
+        #[arg(short = 'g', long, value_delimiter = ',')]
+        tags: Vec<String>,
+    },
+    /// list the notes in your garden
+    ///
+    /// Shows the date, title and tags of every note, most recent first.
+    List,
+    /// search the text of your notes
+    ///
+    /// Prints the notes containing every word of QUERY, with the
+    /// matching lines.
+    Search {
+        /// The words to look for, case is ignored
+        query: String,
+    },
+    /// open a note in your $EDITOR
+    ///
+    /// TITLE does not have to be exact, the closest title wins.
+    Open {
+        /// Part of the title or filename of the note
+        title: String,
+    },
+    /// show the links of a note
+    ///
+    /// Prints the notes TITLE links to with [[wiki-links]], and the
+    /// notes that link to it.
+    Links {
+        /// Part of the title or filename of the note
+        title: String,
+    },
+    /// export the links between your notes as a DOT graph
+    ///
+    /// Render it with graphviz, e.g. `garden graph | dot -Tsvg > garden.svg`.
+    Graph,
+    /// publish your garden as a static HTML site
+    ///
+    /// Renders every note to OUT_DIR, with the [[wiki-links]] turned
+    /// into links, an index page and a page per tag.
+    Publish {
+        /// The directory to write the site to
+        out_dir: PathBuf,
+    },
+    /// show the revisions of a note
+    ///
+    /// Needs the garden to be a git repository, where `write` and
+    /// `open` commit every change.
+    History {
+        /// Part of the title or filename of the note
+        title: String,
+    },
+    /// pull and push your garden
+    ///
+    /// Commits the changes made outside of garden, then pulls from and
+    /// pushes to the remote of the garden repository.
+    Sync {
+        /// The git remote to sync with
+        #[arg(short, long, default_value = "origin")]
+        remote: String,
     },
 }
 
@@ -40,6 +99,14 @@ fn main() -> Result<()> {
         None => get_default_garden_dir().wrap_err("`garden_path` was not supplied"),
     }?;
     match opt.cmd {
-        Command::Write { title } => write(garden_path, title),
+        Command::Write { title, tags } => write(garden_path, title, tags),
+        Command::List => list(garden_path),
+        Command::Search { query } => search(garden_path, query),
+        Command::Open { title } => open(garden_path, title),
+        Command::Links { title } => links(garden_path, title),
+        Command::Graph => graph(garden_path),
+        Command::Publish { out_dir } => publish(garden_path, out_dir),
+        Command::History { title } => history(garden_path, title),
+        Command::Sync { remote } => sync(garden_path, remote),
     }
 }
@@ -0,0 +1,206 @@
+use chrono::{DateTime, Local};
+use color_eyre::{eyre::Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A markdown file in the garden
+#[derive(Debug)]
+pub struct Note {
+    pub path: PathBuf,
+    pub title: String,
+    pub date: String,
+    pub tags: Vec<String>,
+    pub links: Vec<String>, // targets of the [[wiki-links]], in order
+    pub body: String,
+}
+
+/// The optional YAML block between `---` lines at the top of a note
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct FrontMatter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // keys we do not know about are kept as they are
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl FrontMatter {
+    /// Splits `contents` into its front-matter and the markdown after it
+    pub fn parse(contents: &str) -> (Option<FrontMatter>, &str) {
+        let (yaml, body) = split_front_matter(contents);
+        (yaml.and_then(|yaml| serde_yaml::from_str(yaml).ok()), body)
+    }
+
+    /// The front-matter block, `---` lines included
+    pub fn render(&self) -> Result<String> {
+        let yaml = serde_yaml::to_string(self).wrap_err("Failed to write front-matter")?;
+        Ok(format!("---\n{}---\n", yaml))
+    }
+}
+
+/// The time in the format used by the front-matter
+pub fn timestamp() -> String {
+    Local::now().format("%Y-%m-%dT%H:%M:%S%:z").to_string()
+}
+
+impl Note {
+    pub fn load(path: &Path) -> Result<Note> {
+        let contents = fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+        // a broken front-matter should not hide the note
+        let (front_matter, body) = FrontMatter::parse(&contents);
+        let front_matter = front_matter.unwrap_or_default();
+        // use the front-matter title, then the first heading, then the filename
+        let title = front_matter
+            .title
+            .or_else(|| {
+                body.lines()
+                    .find(|line| line.starts_with("# "))
+                    .map(|line| line.trim_start_matches("# ").trim().to_string())
+            })
+            .unwrap_or_else(|| file_stem(path));
+        // an explicit date, then the day the note was created
+        let created = front_matter
+            .created
+            .map(|created| created.chars().take(10).collect());
+        let date = match front_matter.date.or(created) {
+            Some(date) => date,
+            None => {
+                let modified = fs::metadata(path)?.modified()?;
+                DateTime::<Local>::from(modified)
+                    .format("%Y-%m-%d")
+                    .to_string()
+            }
+        };
+        Ok(Note {
+            path: path.to_path_buf(),
+            title,
+            date,
+            tags: front_matter.tags,
+            links: wiki_links(body),
+            body: body.to_string(),
+        })
+    }
+
+    pub fn file_stem(&self) -> String {
+        file_stem(&self.path)
+    }
+
+    /// Whether a `[[target]]` link points to this note, by title or filename
+    pub fn is_target_of(&self, target: &str) -> bool {
+        self.title.eq_ignore_ascii_case(target)
+            || self.file_stem() == target
+            || self.file_stem() == slug::slugify(target)
+    }
+}
+
+/// The targets of the `[[target]]` and `[[target|label]]` links in `body`
+pub fn wiki_links(body: &str) -> Vec<String> {
+    let mut links: Vec<String> = vec![];
+    let mut rest = body;
+    while let Some(start) = rest.find("[[") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("]]") else {
+            break;
+        };
+        let inner = &rest[..end];
+        let target = inner.split('|').next().unwrap_or_default().trim();
+        // a link does not span lines
+        if !target.is_empty() && !inner.contains('\n') && !links.iter().any(|l| l == target) {
+            links.push(target.to_string());
+        }
+        rest = &rest[end + 2..];
+    }
+    links
+}
+
+/// All the notes of the garden and its subdirectories, sorted by title.
+/// Hidden files and directories, like the ones being written or `.git`, are skipped.
+pub fn load_notes(garden_path: &Path) -> Result<Vec<Note>> {
+    let mut notes = vec![];
+    load_dir(garden_path, &mut notes)?;
+    notes.sort_by_key(|note| note.title.to_lowercase());
+    Ok(notes)
+}
+
+fn load_dir(dir: &Path, notes: &mut Vec<Note>) -> Result<()> {
+    let entries = fs::read_dir(dir)
+        .wrap_err_with(|| format!("Failed to read garden at {}", dir.display()))?;
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        // symlinked directories are not followed, they could loop
+        if entry.file_type()?.is_dir() {
+            load_dir(&path, notes)?;
+        } else if path.is_file() && path.extension().is_some_and(|ext| ext == "md") {
+            notes.push(Note::load(&path)?);
+        }
+    }
+    Ok(())
+}
+
+fn split_front_matter(contents: &str) -> (Option<&str>, &str) {
+    let Some(rest) = contents.strip_prefix("---\n") else {
+        return (None, contents);
+    };
+    match rest.find("\n---\n") {
+        Some(end) => (Some(&rest[..end]), &rest[end + 5..]),
+        None => match rest.strip_suffix("\n---") {
+            Some(yaml) => (Some(yaml), ""),
+            None => (None, contents),
+        },
+    }
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{wiki_links, FrontMatter};
+
+    #[test]
+    fn test_wiki_links() {
+        assert_eq!(
+            wiki_links("See [[Borrowing]] and [[rust-lifetimes|lifetimes]], again [[Borrowing]]."),
+            vec!["Borrowing", "rust-lifetimes"]
+        );
+        assert!(wiki_links("[[]] [not a link] [[open\n]]").is_empty());
+    }
+
+    #[test]
+    fn test_front_matter() {
+        let contents =
+            "---\ncreated: 2023-05-01T10:00:00+02:00\ntags: [rust]\nauthor: me\n---\n# Title\n";
+        let (front_matter, body) = FrontMatter::parse(contents);
+        let front_matter = front_matter.unwrap();
+        assert_eq!(front_matter.tags, vec!["rust"]);
+        assert_eq!(body, "# Title\n");
+        assert_eq!(
+            front_matter.render().unwrap(),
+            "---\ncreated: 2023-05-01T10:00:00+02:00\ntags:\n- rust\nauthor: me\n---\n"
+        );
+        assert!(FrontMatter::parse("# No front-matter\n").0.is_none());
+    }
+}
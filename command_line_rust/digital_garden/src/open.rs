@@ -0,0 +1,68 @@
+use crate::{
+    git::commit_note,
+    note::{load_notes, Note},
+};
+use color_eyre::{
+    eyre::{eyre, Context},
+    Result,
+};
+use edit::edit_file;
+use std::path::PathBuf;
+
+pub fn open(garden_path: PathBuf, title: String) -> Result<()> {
+    let notes = load_notes(&garden_path)?;
+    let note = best_match(&notes, &title).ok_or_else(|| eyre!("No note matches \"{}\"", title))?;
+    edit_file(&note.path).wrap_err_with(|| format!("Failed to open {}", note.path.display()))?;
+    commit_note(&garden_path, &note.path, &format!("Update {}", note.title))
+}
+
+/// The note whose title (or filename) matches `query` best
+pub(crate) fn best_match<'a>(notes: &'a [Note], query: &str) -> Option<&'a Note> {
+    notes
+        .iter()
+        .filter_map(|note| {
+            let score =
+                fuzzy_score(&note.title, query).max(fuzzy_score(&note.file_stem(), query))?;
+            Some((score, note))
+        })
+        // on a tie, the first note in title order wins
+        .max_by_key(|(score, note)| (*score, std::cmp::Reverse(note.title.to_lowercase())))
+        .map(|(_, note)| note)
+}
+
+/// How well `query` matches `candidate`, if all its characters appear in order.
+/// Consecutive characters and characters starting a word count more,
+/// and a shorter candidate wins over a longer one.
+fn fuzzy_score(candidate: &str, query: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    let mut pos = 0;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let i = pos + candidate[pos..].iter().position(|&c| c == q)?;
+        score += 1;
+        if last_match == Some(i.wrapping_sub(1)) {
+            score += 5;
+        }
+        if i == 0 || !candidate[i - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last_match = Some(i);
+        pos = i + 1;
+    }
+    Some(score * 100 - candidate.len() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_score;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("Rust ownership", "rown").is_some());
+        assert!(fuzzy_score("Rust ownership", "nwo").is_none());
+        assert!(fuzzy_score("Rust ownership", "own") > fuzzy_score("Rust unknown", "own"));
+        assert!(fuzzy_score("garden", "garden") > fuzzy_score("gardening", "garden"));
+        assert!(fuzzy_score("Rust", "").is_some());
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{
+    links::{backlinks, resolve},
+    note::{load_notes, Note},
+};
+use color_eyre::{eyre::Context, Result};
+use pulldown_cmark::{html, Event, Options, Parser, Tag};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+// embedded, so publishing needs nothing but the binary
+const TEMPLATE: &str = include_str!("template.html");
+
+/// Renders the garden to a static site in `out_dir`: a page per note,
+/// an index and a page per tag
+pub fn publish(garden_path: PathBuf, out_dir: PathBuf) -> Result<()> {
+    let mut notes = load_notes(&garden_path)?;
+    let pages = Pages::new(&notes);
+    fs::create_dir_all(out_dir.join("tags"))
+        .wrap_err_with(|| format!("Failed to create {}", out_dir.display()))?;
+    for note in &notes {
+        let html = page(&note.title, "", &note_content(note, &notes, &pages));
+        write_page(&out_dir, &format!("{}.html", pages.note(note)), &html)?;
+    }
+    // the most recent notes come first
+    notes.sort_by(|a, b| b.date.cmp(&a.date));
+    let mut tags: BTreeMap<&str, Vec<&Note>> = BTreeMap::new();
+    for note in &notes {
+        for tag in &note.tags {
+            tags.entry(tag).or_default().push(note);
+        }
+    }
+    for (tag, tagged) in &tags {
+        let content = format!(
+            "<h1>#{}</h1>\n{}",
+            escape(tag),
+            note_list(tagged, "../", &pages)
+        );
+        let html = page(&format!("#{}", tag), "../", &content);
+        write_page(&out_dir, &format!("tags/{}.html", pages.tag(tag)), &html)?;
+    }
+    write_page(
+        &out_dir,
+        "index.html",
+        &page("Index", "", &index_content(&notes, &tags, &pages)),
+    )?;
+    println!("Published {} notes to {}", notes.len(), out_dir.display());
+    Ok(())
+}
+
+/// The names of the pages, without `.html`. Notes keep their filename and tags
+/// their slug, unless that is taken: by the index, by a note of the same name
+/// in another directory or by a tag with the same slug.
+struct Pages {
+    notes: HashMap<PathBuf, String>,
+    tags: HashMap<String, String>,
+}
+
+impl Pages {
+    fn new(notes: &[Note]) -> Pages {
+        // the notes closest to the top of the garden keep their names
+        let mut by_depth: Vec<&Note> = notes.iter().collect();
+        by_depth.sort_by_key(|note| (note.path.components().count(), &note.path));
+        let mut taken = HashSet::from(["index".to_string()]);
+        let note_pages = by_depth
+            .into_iter()
+            .map(|note| {
+                (
+                    note.path.clone(),
+                    unique(note.file_stem(), "note", &mut taken),
+                )
+            })
+            .collect();
+        let tags: BTreeSet<&String> = notes.iter().flat_map(|note| &note.tags).collect();
+        let mut taken = HashSet::new();
+        let tag_pages = tags
+            .into_iter()
+            .map(|tag| (tag.clone(), unique(slug::slugify(tag), "tag", &mut taken)))
+            .collect();
+        Pages {
+            notes: note_pages,
+            tags: tag_pages,
+        }
+    }
+
+    fn note(&self, note: &Note) -> &str {
+        &self.notes[&note.path]
+    }
+
+    fn tag(&self, tag: &str) -> &str {
+        &self.tags[tag]
+    }
+}
+
+/// `name`, or `fallback` if it is empty, numbered until it is not in `taken`.
+/// Case is ignored, as it is by some filesystems.
+fn unique(name: String, fallback: &str, taken: &mut HashSet<String>) -> String {
+    let base = if name.is_empty() {
+        fallback.to_string()
+    } else {
+        name
+    };
+    let mut name = base.clone();
+    let mut n = 2;
+    while !taken.insert(name.to_lowercase()) {
+        name = format!("{}-{}", base, n);
+        n += 1;
+    }
+    name
+}
+
+fn write_page(out_dir: &Path, name: &str, html: &str) -> Result<()> {
+    let path = out_dir.join(name);
+    fs::write(&path, html).wrap_err_with(|| format!("Failed to write {}", path.display()))
+}
+
+/// Fills the template, `root` leads from the page back to the top of the site
+fn page(title: &str, root: &str, content: &str) -> String {
+    TEMPLATE
+        .replace("{{title}}", &escape(title))
+        .replace("{{root}}", root)
+        .replace("{{content}}", content)
+}
+
+fn note_content(note: &Note, notes: &[Note], pages: &Pages) -> String {
+    let mut content = format!("<p><span class=\"date\">{}</span>", escape(&note.date));
+    for tag in &note.tags {
+        content.push_str(&tag_link(tag, "", pages));
+    }
+    content.push_str("</p>\n");
+    html::push_html(
+        &mut content,
+        with_wiki_links(&note.body, notes, pages).into_iter(),
+    );
+    let linked_from: Vec<&Note> = backlinks(notes, note).collect();
+    if !linked_from.is_empty() {
+        content.push_str("<h2>Linked from</h2>\n");
+        content.push_str(&note_list(&linked_from, "", pages));
+    }
+    content
+}
+
+fn index_content(notes: &[Note], tags: &BTreeMap<&str, Vec<&Note>>, pages: &Pages) -> String {
+    let all: Vec<&Note> = notes.iter().collect();
+    let mut content = format!("<h1>Notes</h1>\n{}", note_list(&all, "", pages));
+    if !tags.is_empty() {
+        content.push_str("<h2>Tags</h2>\n<p>");
+        for (tag, tagged) in tags {
+            content.push_str(&format!("{}({}) ", tag_link(tag, "", pages), tagged.len()));
+        }
+        content.push_str("</p>\n");
+    }
+    content
+}
+
+fn note_list(notes: &[&Note], root: &str, pages: &Pages) -> String {
+    let mut list = String::from("<ul class=\"notes\">\n");
+    for note in notes {
+        list.push_str(&format!(
+            "<li><span class=\"date\">{}</span><a href=\"{}{}.html\">{}</a></li>\n",
+            escape(&note.date),
+            root,
+            encode_href(pages.note(note)),
+            escape(&note.title)
+        ));
+    }
+    list.push_str("</ul>\n");
+    list
+}
+
+fn tag_link(tag: &str, root: &str, pages: &Pages) -> String {
+    format!(
+        "<a class=\"tag\" href=\"{}tags/{}.html\">#{}</a>",
+        root,
+        encode_href(pages.tag(tag)),
+        escape(tag)
+    )
+}
+
+/// The markdown events of `body`, with the `[[wiki-links]]` in its text
+/// turned into links to the pages of the notes. Code is left alone.
+fn with_wiki_links<'a>(body: &'a str, notes: &[Note], pages: &Pages) -> Vec<Event<'a>> {
+    let mut events = vec![];
+    // the parser splits text at the brackets, so it is put back together first
+    let mut text = String::new();
+    let mut in_code_block = false;
+    for event in Parser::new_ext(body, Options::all()) {
+        match event {
+            Event::Text(t) if !in_code_block => {
+                text.push_str(&t);
+                continue;
+            }
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            _ => {}
+        }
+        replace_wiki_links(&text, notes, pages, &mut events);
+        text.clear();
+        events.push(event);
+    }
+    replace_wiki_links(&text, notes, pages, &mut events);
+    events
+}
+
+/// Pushes the events of `text`, links to missing notes are marked instead
+fn replace_wiki_links(text: &str, notes: &[Note], pages: &Pages, events: &mut Vec<Event>) {
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        let Some(end) = rest[start..].find("]]").map(|end| start + end) else {
+            break;
+        };
+        let inner = &rest[start + 2..end];
+        let (target, label) = match inner.split_once('|') {
+            Some((target, label)) => (target.trim(), label.trim()),
+            None => (inner.trim(), inner.trim()),
+        };
+        if !rest[..start].is_empty() {
+            events.push(Event::Text(rest[..start].to_string().into()));
+        }
+        let html = match resolve(notes, target) {
+            Some(note) => format!(
+                "<a href=\"{}.html\">{}</a>",
+                encode_href(pages.note(note)),
+                escape(label)
+            ),
+            None => format!("<span class=\"missing\">{}</span>", escape(label)),
+        };
+        events.push(Event::Html(html.into()));
+        rest = &rest[end + 2..];
+    }
+    if !rest.is_empty() {
+        events.push(Event::Text(rest.to_string().into()));
+    }
+}
+
+/// Percent-encodes a filename for an href, the pages keep the name of the note
+fn encode_href(name: &str) -> String {
+    let mut href = String::new();
+    for b in name.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                href.push(b as char)
+            }
+            _ => href.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    href
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
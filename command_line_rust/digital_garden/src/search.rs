@@ -0,0 +1,155 @@
+use crate::note::load_notes;
+use color_eyre::{eyre::eyre, owo_colors::OwoColorize, Result};
+use std::{
+    io::{self, IsTerminal},
+    ops::Range,
+    path::PathBuf,
+};
+
+// characters shown on each side of the first match of a line
+const CONTEXT: usize = 30;
+// lines shown for each note
+const MAX_LINES: usize = 3;
+
+pub fn search(garden_path: PathBuf, query: String) -> Result<()> {
+    let words: Vec<&str> = query.split_whitespace().collect();
+    // no words would match every note
+    if words.is_empty() {
+        return Err(eyre!("The search query is empty"));
+    }
+    let highlight = io::stdout().is_terminal();
+    let mut found = false;
+    for note in load_notes(&garden_path)? {
+        // every word has to be somewhere in the note
+        let text = format!("{}\n{}", note.title, note.body);
+        if words
+            .iter()
+            .any(|word| find_ignore_case(&text, word).is_empty())
+        {
+            continue;
+        }
+        found = true;
+        let title = match highlight {
+            true => note.title.green().bold().to_string(),
+            false => note.title.clone(),
+        };
+        println!("{} ({}.md)", title, note.file_stem());
+        let lines = note
+            .body
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let mut matches: Vec<Range<usize>> = words
+                    .iter()
+                    .flat_map(|word| find_ignore_case(line, word))
+                    .collect();
+                matches.sort_by_key(|m| m.start);
+                (!matches.is_empty()).then(|| (i, snippet(line, &matches, highlight)))
+            })
+            .take(MAX_LINES);
+        for (i, snippet) in lines {
+            println!("  {}: {}", i + 1, snippet);
+        }
+    }
+    if !found {
+        eprintln!("No notes match \"{}\"", query);
+    }
+    Ok(())
+}
+
+/// The byte ranges where `needle` appears in `haystack`, ignoring case
+fn find_ignore_case(haystack: &str, needle: &str) -> Vec<Range<usize>> {
+    let needle: Vec<char> = needle.chars().flat_map(char::to_lowercase).collect();
+    let mut ranges = vec![];
+    if needle.is_empty() {
+        return ranges;
+    }
+    let mut next = 0;
+    for (start, _) in haystack.char_indices() {
+        if start < next {
+            continue;
+        }
+        let mut chars = haystack[start..].char_indices();
+        let mut wanted = needle.iter();
+        let mut end = start;
+        loop {
+            let Some(&want) = wanted.next() else {
+                ranges.push(start..end);
+                next = end;
+                break;
+            };
+            match chars.next() {
+                // compare one lowercase character at a time, e.g. no 'ß' to "ss"
+                Some((i, c)) if c.to_lowercase().eq(std::iter::once(want)) => {
+                    end = start + i + c.len_utf8()
+                }
+                _ => break,
+            }
+        }
+    }
+    ranges
+}
+
+/// The part of `line` around the first match, with the matches highlighted
+fn snippet(line: &str, matches: &[Range<usize>], highlight: bool) -> String {
+    let first = &matches[0];
+    let start = floor_char_boundary(line, first.start.saturating_sub(CONTEXT));
+    let end = floor_char_boundary(line, (first.end + CONTEXT).min(line.len()));
+    let mut out = String::new();
+    if start > 0 {
+        out.push_str("...");
+    }
+    let mut pos = start;
+    for m in matches {
+        // skip overlapping matches and the ones cut off by the snippet
+        if m.start < pos || m.end > end {
+            continue;
+        }
+        out.push_str(&line[pos..m.start]);
+        let text = &line[m.clone()];
+        if highlight {
+            out.push_str(&text.yellow().bold().to_string());
+        } else {
+            out.push_str(text);
+        }
+        pos = m.end;
+    }
+    out.push_str(&line[pos..end]);
+    if end < line.len() {
+        out.push_str("...");
+    }
+    out
+}
+
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_ignore_case, snippet};
+
+    #[test]
+    fn test_find_ignore_case() {
+        assert_eq!(find_ignore_case("Rust is rusty", "rust"), vec![0..4, 8..12]);
+        assert_eq!(find_ignore_case("Größe GRÖSSE", "größe"), vec![0..7]);
+        assert_eq!(find_ignore_case("aaa", "aa"), vec![0..2]);
+        assert!(find_ignore_case("garden", "").is_empty());
+    }
+
+    #[test]
+    fn test_snippet() {
+        let line =
+            "a garden is a place where ideas grow slowly, one note at a time, year after year";
+        let matches = find_ignore_case(line, "note");
+        assert_eq!(
+            snippet(line, &matches, false),
+            "... where ideas grow slowly, one note at a time, year after year"
+        );
+        let matches = find_ignore_case("one note", "note");
+        assert_eq!(snippet("one note", &matches, false), "one note");
+    }
+}
@@ -1,14 +1,18 @@
+use crate::{
+    git::commit_note,
+    note::{timestamp, FrontMatter},
+};
 use color_eyre::{eyre::Context, owo_colors::OwoColorize, Result};
 use edit::{edit_file, Builder};
 use std::{
     fs,
-    io::{Read, Seek, SeekFrom, Write},
+    io::{self, IsTerminal, Read, Seek, SeekFrom, Write},
     path::PathBuf,
 };
 
 const TEMPLATE: &[u8; 2] = b"# ";
 
-pub fn write(garden_path: PathBuf, title: Option<String>) -> Result<()> {
+pub fn write(garden_path: PathBuf, title: Option<String>, tags: Vec<String>) -> Result<()> {
     let (mut file, filepath) = Builder::new()
         .suffix(".md")
         .rand_bytes(5)
@@ -16,6 +20,15 @@ pub fn write(garden_path: PathBuf, title: Option<String>) -> Result<()> {
         .wrap_err("Failed to create wip file")?
         .keep()
         .wrap_err("Failed to keep tempfile")?;
+    let now = timestamp();
+    let front_matter = FrontMatter {
+        title: title.clone(),
+        created: Some(now.clone()),
+        updated: Some(now),
+        tags,
+        ..Default::default()
+    };
+    file.write_all(front_matter.render()?.as_bytes())?;
     file.write_all(TEMPLATE)?;
     // let the user write whatever they want in their favourite editor
     // before returning to the clid and finishing up
@@ -24,7 +37,6 @@ pub fn write(garden_path: PathBuf, title: Option<String>) -> Result<()> {
     let mut contents = String::new();
     file.seek(SeekFrom::Start(0))?;
     file.read_to_string(&mut contents)?;
-
     // use `title` if the user passed it in,
     // otherwise try to find a heading in the markdown
     let document_title = title.or_else(|| {
@@ -33,10 +45,19 @@ pub fn write(garden_path: PathBuf, title: Option<String>) -> Result<()> {
             .find(|line| line.starts_with("# "))
             .map(|maybe_line| maybe_line.trim_start_matches("# ").to_string())
     });
-    let filename = match document_title {
-        Some(raw_title) => confirm_filename(&raw_title),
-        None => ask_for_filename(),
+    let title = match &document_title {
+        Some(raw_title) => confirm_title(raw_title),
+        None => ask_for_title(),
     }?;
+    let filename = slug::slugify(&title);
+    // the note was updated by the editing, unless the front-matter was removed,
+    // and it gets the title chosen in the end
+    if let (Some(mut front_matter), body) = FrontMatter::parse(&contents) {
+        front_matter.title = Some(title.clone());
+        front_matter.updated = Some(timestamp());
+        contents = format!("{}{}", front_matter.render()?, body);
+        fs::write(&filepath, &contents).wrap_err("Failed to update front-matter")?;
+    }
     let mut i: usize = 0;
     loop {
         let dest_filename = format!(
@@ -54,14 +75,14 @@ pub fn write(garden_path: PathBuf, title: Option<String>) -> Result<()> {
             i += 1;
         } else {
             fs::rename(filepath, &dest)?;
-            break;
+            // the title is the commit message
+            return commit_note(&garden_path, &dest, &title);
         }
     }
-    Ok(())
 }
 
-fn ask_for_filename() -> Result<String> {
-    rprompt::prompt_reply(&format!(
+fn ask_for_title() -> Result<String> {
+    prompt_reply(format!(
         "{}",
         "\
 Enter filename
@@ -70,13 +91,12 @@ Enter filename
         .bold()
     ))
     .wrap_err("Failed to get filename")
-    .map(|title| slug::slugify(title))
 }
 
-fn confirm_filename(raw_title: &str) -> Result<String> {
+fn confirm_title(raw_title: &str) -> Result<String> {
     loop {
         // prompt defaults to uppercase character in question
-        let res = rprompt::prompt_reply(&format!(
+        let res = prompt_reply(format!(
             "\
 {} {}
 Do you want a different title? (y/N): ",
@@ -86,10 +106,10 @@ Do you want a different title? (y/N): ",
         .wrap_err("Failed to get input for y/n question")?;
 
         match res.as_str() {
-            "y" | "Y" => break ask_for_filename(),
+            "y" | "Y" => break ask_for_title(),
             "n" | "N" | "" => {
                 // the capital N in the prompt means default
-                break Ok(slug::slugify(raw_title));
+                break Ok(raw_title.to_string());
             }
             _ => {
                 // ask again because something went wrong
@@ -98,4 +118,14 @@ Do you want a different title? (y/N): ",
     }
 }
 
+/// Asks on the terminal. Without one on either side, as in scripts and
+/// tests, the answers are read from stdin.
+fn prompt_reply(prompt: String) -> io::Result<String> {
+    if io::stdin().is_terminal() || io::stdout().is_terminal() {
+        rprompt::prompt_reply(prompt)
+    } else {
+        rprompt::prompt_reply_from_bufread(&mut io::stdin().lock(), &mut io::stdout(), prompt)
+    }
+}
+
 // EDITOR="code -w" cargo run -- write
@@ -51,3 +51,434 @@ fn test_write_with_written_title() {
         .child("testing.md")
         .assert(predicate::path::exists());
 }
+
+/// a garden with a few notes already in it
+fn setup_garden() -> (Command, assert_fs::TempDir) {
+    let (cmd, temp_dir) = setup_command();
+    temp_dir
+        .child("rust-ownership.md")
+        .write_str("---\ntitle: Rust Ownership\ndate: 2023-05-01\ntags: [rust, memory]\n---\n# Ownership\n\nEvery value has a single owner.\nSee [[Borrowing]] and [[Lifetimes]].\nBorrowing lends it out.\n")
+        .unwrap();
+    temp_dir
+        .child("compost.md")
+        .write_str("---\ndate: 2023-06-12\ntags: [gardening]\n---\n# Compost\n\nGreens and browns, turned every week.\n")
+        .unwrap();
+    temp_dir
+        .child("borrowing.md")
+        .write_str("---\ncreated: 2023-05-02T09:00:00+02:00\ntags: [rust]\n---\n# Borrowing\n\nBack to [[rust-ownership]].\n")
+        .unwrap();
+    temp_dir
+        .child(".tmpabcde.md")
+        .write_str("# Work in progress\n")
+        .unwrap();
+    (cmd, temp_dir)
+}
+
+#[test]
+/// choosing another title at the prompt renames the note and its front-matter title
+fn test_write_other_title() {
+    let (mut cmd, temp_dir) = setup_command();
+    cmd.arg("write")
+        .arg("-t")
+        .arg("atitle")
+        .write_stdin("y\nA Better Title\n".as_bytes())
+        .assert()
+        .success();
+    temp_dir
+        .child("a-better-title.md")
+        .assert(predicate::str::starts_with("---\ntitle: A Better Title\n"));
+    temp_dir.child("atitle.md").assert(predicate::path::missing());
+}
+
+#[test]
+/// list the notes, most recent first, skipping the unfinished ones
+fn test_list() {
+    let (mut cmd, _temp_dir) = setup_garden();
+    cmd.arg("list")
+        .assert()
+        .success()
+        .stdout(
+        "2023-06-12  Compost  #gardening\n2023-05-02  Borrowing  #rust\n2023-05-01  Rust Ownership  #rust #memory\n",
+    );
+}
+
+#[test]
+/// search the notes, ignoring case
+fn test_search() {
+    let (mut cmd, _temp_dir) = setup_garden();
+    cmd.arg("search")
+        .arg("SINGLE owner")
+        .assert()
+        .success()
+        .stdout("Rust Ownership (rust-ownership.md)\n  1: # Ownership\n  3: Every value has a single owner.\n");
+}
+
+#[test]
+/// search for something that is not in the garden
+fn test_search_not_found() {
+    let (mut cmd, _temp_dir) = setup_garden();
+    cmd.arg("search")
+        .arg("owner weeks")
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("No notes match \"owner weeks\""));
+}
+
+#[test]
+/// an empty query is refused instead of matching every note
+fn test_search_empty() {
+    let (mut cmd, _temp_dir) = setup_garden();
+    cmd.arg("search")
+        .arg(" ")
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains("The search query is empty"));
+}
+
+#[test]
+/// open the closest note in the editor
+fn test_open() {
+    let (mut cmd, temp_dir) = setup_garden();
+    cmd.arg("open").arg("rust own").assert().success();
+    temp_dir
+        .child("rust-ownership.md")
+        .assert(predicate::str::ends_with(
+            "Borrowing lends it out.\ntesting\n",
+        ));
+    temp_dir
+        .child("compost.md")
+        .assert(predicate::str::contains("testing").not());
+}
+
+#[test]
+/// open a note that does not exist
+fn test_open_not_found() {
+    let (mut cmd, _temp_dir) = setup_garden();
+    cmd.arg("open")
+        .arg("xyz")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No note matches \"xyz\""));
+}
+
+#[test]
+/// new notes get a front-matter with their tags
+fn test_write_front_matter() {
+    let (mut cmd, temp_dir) = setup_command();
+    cmd.arg("write")
+        .arg("-t")
+        .arg("atitle")
+        .arg("--tags")
+        .arg("rust,notes")
+        .write_stdin("N\n".as_bytes())
+        .assert()
+        .success();
+    let note = temp_dir.child("atitle.md");
+    note.assert(predicate::str::starts_with("---\ntitle: atitle\ncreated: "));
+    note.assert(predicate::str::contains("\nupdated: "));
+    note.assert(predicate::str::contains(
+        "\ntags:\n- rust\n- notes\n---\n# testing\n",
+    ));
+}
+
+#[test]
+/// show the outgoing links and the backlinks of a note
+fn test_links() {
+    let (mut cmd, _temp_dir) = setup_garden();
+    cmd.arg("links").arg("rust own").assert().success().stdout(
+        "\
+Rust Ownership (rust-ownership.md)
+links to:
+  Borrowing (borrowing.md)
+  Lifetimes (missing)
+linked from:
+  Borrowing (borrowing.md)
+",
+    );
+}
+
+#[test]
+/// export the links as a DOT graph
+fn test_graph() {
+    let (mut cmd, _temp_dir) = setup_garden();
+    cmd.arg("graph").assert().success().stdout(
+        "\
+digraph garden {
+    \"borrowing\" [label=\"Borrowing\"];
+    \"compost\" [label=\"Compost\"];
+    \"rust-ownership\" [label=\"Rust Ownership\"];
+    \"borrowing\" -> \"rust-ownership\";
+    \"rust-ownership\" -> \"borrowing\";
+    \"rust-ownership\" -> \"lifetimes\" [style=dashed];
+}
+",
+    );
+}
+
+#[test]
+/// publish the garden as a static site
+fn test_publish() {
+    let (mut cmd, temp_dir) = setup_garden();
+    let site = temp_dir.child("site");
+    cmd.arg("publish")
+        .arg(site.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Published 3 notes to "));
+    let note = site.child("rust-ownership.html");
+    note.assert(predicate::str::contains("<title>Rust Ownership</title>"));
+    note.assert(predicate::str::contains(
+        "See <a href=\"borrowing.html\">Borrowing</a> and <span class=\"missing\">Lifetimes</span>.",
+    ));
+    note.assert(predicate::str::contains(
+        "<a class=\"tag\" href=\"tags/memory.html\">#memory</a>",
+    ));
+    // the backlinks
+    note.assert(predicate::str::contains(
+        "<h2>Linked from</h2>\n<ul class=\"notes\">\n<li><span class=\"date\">2023-05-02</span><a href=\"borrowing.html\">Borrowing</a></li>",
+    ));
+    site.child("compost.html")
+        .assert(predicate::str::contains("<h1>Compost</h1>"));
+    site.child("index.html")
+        .assert(predicate::str::contains(
+            "<li><span class=\"date\">2023-06-12</span><a href=\"compost.html\">Compost</a></li>\n<li><span class=\"date\">2023-05-02</span>",
+        ));
+    site.child("tags/rust.html")
+        .assert(predicate::str::contains(
+            "<a href=\"../rust-ownership.html\">Rust Ownership</a>",
+        ));
+    site.child("tags/rust.html")
+        .assert(predicate::str::contains(
+            "<a href=\"../index.html\">Index</a>",
+        ));
+}
+
+#[test]
+/// wiki-links in code are left alone, and odd filenames are encoded in the links
+fn test_publish_code_and_filenames() {
+    let (mut cmd, temp_dir) = setup_garden();
+    temp_dir
+        .child("my notes #1.md")
+        .write_str("# Hash\n\nA note with an odd name.\n")
+        .unwrap();
+    temp_dir
+        .child("code.md")
+        .write_str("# Code\n\nSee [[my notes #1]] and `[[Compost]]`.\n\n```rust\nlet x = grid[[0]];\n```\n")
+        .unwrap();
+    let site = temp_dir.child("site");
+    cmd.arg("publish").arg(site.path()).assert().success();
+    let note = site.child("code.html");
+    note.assert(predicate::str::contains(
+        "See <a href=\"my%20notes%20%231.html\">my notes #1</a> and <code>[[Compost]]</code>.",
+    ));
+    note.assert(predicate::str::contains("let x = grid[[0]];\n</code></pre>"));
+    site.child("my notes #1.html")
+        .assert(predicate::str::contains("<h1>Hash</h1>"));
+    site.child("index.html")
+        .assert(predicate::str::contains("<a href=\"my%20notes%20%231.html\">Hash</a>"));
+}
+
+#[test]
+/// a note named index does not replace the index
+fn test_publish_index_note() {
+    let (mut cmd, temp_dir) = setup_garden();
+    temp_dir
+        .child("index.md")
+        .write_str("# My Index\n\nStart with [[Compost]].\n")
+        .unwrap();
+    let site = temp_dir.child("site");
+    cmd.arg("publish")
+        .arg(site.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Published 4 notes to "));
+    site.child("index.html")
+        .assert(predicate::str::contains("<a href=\"index-2.html\">My Index</a>"));
+    site.child("index-2.html")
+        .assert(predicate::str::contains("<title>My Index</title>"));
+}
+
+#[test]
+/// tags with the same slug, or none, get pages of their own
+fn test_publish_tag_pages() {
+    let (mut cmd, temp_dir) = setup_garden();
+    temp_dir
+        .child("languages.md")
+        .write_str("---\ntags: [C++, c, \"!!!\"]\n---\n# Languages\n")
+        .unwrap();
+    let site = temp_dir.child("site");
+    cmd.arg("publish").arg(site.path()).assert().success();
+    site.child("tags/c.html")
+        .assert(predicate::str::contains("<h1>#C++</h1>"));
+    site.child("tags/c-2.html")
+        .assert(predicate::str::contains("<h1>#c</h1>"));
+    site.child("tags/tag.html")
+        .assert(predicate::str::contains("<h1>#!!!</h1>"));
+    site.child("languages.html").assert(predicate::str::contains(
+        "<a class=\"tag\" href=\"tags/c.html\">#C++</a><a class=\"tag\" href=\"tags/c-2.html\">#c</a><a class=\"tag\" href=\"tags/tag.html\">#!!!</a>",
+    ));
+}
+
+#[test]
+/// the notes in subdirectories are published too, hidden directories are not
+fn test_publish_nested_notes() {
+    let (mut cmd, temp_dir) = setup_garden();
+    temp_dir
+        .child("rust/traits.md")
+        .write_str("# Traits\n\nShared behaviour, see [[Borrowing]].\n")
+        .unwrap();
+    temp_dir
+        .child("archive/borrowing.md")
+        .write_str("# Old Borrowing\n")
+        .unwrap();
+    temp_dir
+        .child(".trash/deleted.md")
+        .write_str("# Deleted\n")
+        .unwrap();
+    let site = temp_dir.child("site");
+    cmd.arg("publish")
+        .arg(site.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Published 5 notes to "));
+    site.child("traits.html").assert(predicate::str::contains(
+        "see <a href=\"borrowing.html\">Borrowing</a>.",
+    ));
+    // the note at the top keeps its name
+    site.child("borrowing.html")
+        .assert(predicate::str::contains("<h1>Borrowing</h1>"));
+    site.child("borrowing-2.html")
+        .assert(predicate::str::contains("<h1>Old Borrowing</h1>"));
+    site.child("deleted.html").assert(predicate::path::missing());
+}
+
+/// runs git in `dir`, panicking if it fails
+fn git(dir: &std::path::Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?}: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn git_init(dir: &std::path::Path) {
+    git(dir, &["init", "--quiet", "-b", "main"]);
+    git(dir, &["config", "user.name", "Gardener"]);
+    git(dir, &["config", "user.email", "gardener@example.com"]);
+}
+
+#[test]
+/// writing in a git garden commits the note
+fn test_write_commits() {
+    let (mut cmd, temp_dir) = setup_command();
+    git_init(temp_dir.path());
+    cmd.arg("write")
+        .arg("-t")
+        .arg("atitle")
+        .write_stdin("N\n".as_bytes())
+        .assert()
+        .success();
+    assert_eq!(git(temp_dir.path(), &["log", "--format=%s"]), "atitle\n");
+    assert_eq!(git(temp_dir.path(), &["ls-files"]), "atitle.md\n");
+}
+
+#[test]
+/// show the revisions of a note
+fn test_history() {
+    let (mut cmd, temp_dir) = setup_garden();
+    git_init(temp_dir.path());
+    git(temp_dir.path(), &["add", "rust-ownership.md"]);
+    git(
+        temp_dir.path(),
+        &["commit", "--quiet", "-m", "Rust Ownership"],
+    );
+    // opening the note commits the changes made in the editor
+    cmd.arg("open").arg("rust own").assert().success();
+    let mut cmd = Command::cargo_bin("garden").unwrap();
+    cmd.env("GARDEN_PATH", temp_dir.path())
+        .arg("history")
+        .arg("rust own")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(
+            "^Rust Ownership \\(rust-ownership.md\\)\n  [0-9a-f]+ \\d{4}-\\d\\d-\\d\\d Update Rust Ownership\n  [0-9a-f]+ \\d{4}-\\d\\d-\\d\\d Rust Ownership\n$",
+        ).unwrap());
+}
+
+#[test]
+/// history needs a git garden
+fn test_history_not_a_repo() {
+    let (mut cmd, _temp_dir) = setup_garden();
+    cmd.arg("history")
+        .arg("rust own")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not a git repository"));
+}
+
+#[test]
+/// sync two gardens through a bare repository
+fn test_sync() {
+    let (mut cmd, temp_dir) = setup_garden();
+    let remote = assert_fs::TempDir::new().unwrap();
+    git(remote.path(), &["init", "--quiet", "--bare", "-b", "main"]);
+    git_init(temp_dir.path());
+    git(
+        temp_dir.path(),
+        &["remote", "add", "origin", remote.path().to_str().unwrap()],
+    );
+    // the notes not committed yet are committed by the sync
+    cmd.arg("sync")
+        .assert()
+        .success()
+        .stdout("Synced with origin/main\n");
+    assert_eq!(
+        git(remote.path(), &["ls-tree", "--name-only", "main"]),
+        "borrowing.md\ncompost.md\nrust-ownership.md\n"
+    );
+
+    // a note written in another clone comes back with the next sync
+    let other = assert_fs::TempDir::new().unwrap();
+    git(
+        other.path(),
+        &["clone", "--quiet", remote.path().to_str().unwrap(), "."],
+    );
+    git(other.path(), &["config", "user.name", "Gardener"]);
+    git(
+        other.path(),
+        &["config", "user.email", "gardener@example.com"],
+    );
+    other.child("seeds.md").write_str("# Seeds\n").unwrap();
+    git(other.path(), &["add", "seeds.md"]);
+    git(other.path(), &["commit", "--quiet", "-m", "Seeds"]);
+    git(other.path(), &["push", "--quiet"]);
+    let mut cmd = Command::cargo_bin("garden").unwrap();
+    cmd.env("GARDEN_PATH", temp_dir.path())
+        .arg("sync")
+        .assert()
+        .success();
+    temp_dir.child("seeds.md").assert("# Seeds\n");
+}
+
+#[test]
+/// sync needs a remote
+fn test_sync_without_remote() {
+    let (mut cmd, temp_dir) = setup_garden();
+    git_init(temp_dir.path());
+    cmd.arg("sync")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The garden has no remote \"origin\"",
+        ));
+}
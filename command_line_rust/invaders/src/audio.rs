@@ -0,0 +1,73 @@
+/// The sounds of the game, named after their files in `audio/`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    Explode,
+    Lose,
+    Move,
+    Pew,
+    Startup,
+    Win
+}
+
+impl Sound {
+    pub const ALL: [Sound; 6] = [Sound::Explode, Sound::Lose, Sound::Move, Sound::Pew, Sound::Startup, Sound::Win];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sound::Explode => "explode",
+            Sound::Lose => "lose",
+            Sound::Move => "move",
+            Sound::Pew => "pew",
+            Sound::Startup => "startup",
+            Sound::Win => "win",
+        }
+    }
+}
+
+/// Where the game sends its sounds
+pub trait AudioBackend {
+    fn play(&mut self, sound: Sound);
+
+    /// Blocks until the sounds being played are done
+    fn wait(&mut self) {}
+}
+
+/// Plays nothing, for tests and machines without a sound card
+pub struct NullAudio;
+
+impl AudioBackend for NullAudio {
+    fn play(&mut self, _sound: Sound) {}
+}
+
+/// Plays the sounds in `audio/` on the sound card
+#[cfg(feature = "audio")]
+pub struct RustyAudio(rusty_audio::Audio);
+
+#[cfg(feature = "audio")]
+impl Default for RustyAudio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "audio")]
+impl RustyAudio {
+    pub fn new() -> Self {
+        let mut audio = rusty_audio::Audio::new();
+        for sound in Sound::ALL {
+            audio.add(sound.name(), format!("audio/{}.wav", sound.name()));
+        }
+        Self(audio)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl AudioBackend for RustyAudio {
+    fn play(&mut self, sound: Sound) {
+        self.0.play(sound.name());
+    }
+
+    fn wait(&mut self) {
+        self.0.wait();
+    }
+}
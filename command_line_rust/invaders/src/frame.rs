@@ -1,17 +1,9 @@
-use crate::{NUM_COLS, NUM_ROWS};
+use crate::Field;
 
 pub type Frame = Vec<Vec<&'static str>>;
 
-pub fn new_frame() -> Frame {
-    let mut cols = Vec::with_capacity(NUM_COLS);
-    for _ in 0..NUM_COLS {
-        let mut col = Vec::with_capacity(NUM_ROWS);
-        for _ in 0..NUM_ROWS {
-            col.push(" ");
-        }
-        cols.push(col);
-    }
-    cols
+pub fn new_frame(field: Field) -> Frame {
+    vec![vec![" "; field.rows]; field.cols]
 }
 
 pub trait Drawable {
@@ -0,0 +1,212 @@
+use std::{str::FromStr, time::Duration};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    audio::{AudioBackend, Sound},
+    frame::{Drawable, Frame},
+    invaders::Invaders,
+    mothership::Mothership,
+    player::{Player, LIVES},
+    score::Score,
+    shields::Shields,
+    Field,
+};
+
+/// The game advances by this much at every step, whatever the frame rate
+pub const TICK: Duration = Duration::from_millis(10);
+
+/// What the player can do during a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Left,
+    Right,
+    Shoot,
+    Quit
+}
+
+impl Input {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Input::Left => "left",
+            Input::Right => "right",
+            Input::Shoot => "shoot",
+            Input::Quit => "quit",
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Input::Left),
+            "right" => Ok(Input::Right),
+            "shoot" => Ok(Input::Shoot),
+            "quit" => Ok(Input::Quit),
+            _ => Err(format!("unknown input \"{}\"", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    // only when the game has a last level
+    Won,
+    Lost,
+    Quit
+}
+
+/// Gives the inputs of each step, e.g. the keyboard or a replay
+pub trait InputSource {
+    fn inputs(&mut self, game: &Game) -> Vec<Input>;
+}
+
+/// The whole state of a game. Given the same seed and the same inputs at the
+/// same ticks, it always plays out the same way.
+pub struct Game {
+    pub player: Player,
+    pub invaders: Invaders,
+    pub shields: Shields,
+    pub mothership: Mothership,
+    pub score: Score,
+    rng: StdRng,
+    seed: u64,
+    field: Field,
+    tick: u64,
+    status: Status,
+    max_level: Option<u32>
+}
+
+impl Game {
+    pub fn new(seed: u64) -> Self {
+        Self::for_field(seed, Field::DEFAULT)
+    }
+
+    /// A game on a field of another size, e.g. the size of the terminal
+    pub fn for_field(seed: u64, field: Field) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        Self {
+            player: Player::new(field),
+            invaders: Invaders::new(field),
+            shields: Shields::new(field),
+            mothership: Mothership::new(field, &mut rng),
+            score: Score::new(LIVES),
+            rng,
+            seed,
+            field,
+            tick: 0,
+            status: Status::Playing,
+            max_level: None
+        }
+    }
+
+    /// Makes clearing `level` win the game, instead of going on forever
+    pub fn with_max_level(mut self, level: u32) -> Self {
+        self.max_level = Some(level);
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// The number of steps played
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Plays one step of `TICK` with the given inputs
+    pub fn step(&mut self, inputs: &[Input], audio: &mut dyn AudioBackend) -> Status {
+        if self.status != Status::Playing {
+            return self.status;
+        }
+        for input in inputs {
+            match input {
+                Input::Left => self.player.move_left(),
+                Input::Right => self.player.move_right(),
+                Input::Shoot => {
+                    if self.player.shoot() {
+                        audio.play(Sound::Pew);
+                    }
+                }
+                Input::Quit => {
+                    audio.play(Sound::Lose);
+                    self.status = Status::Quit;
+                    return self.status;
+                }
+            }
+        }
+
+        // Updates
+        self.player.update(TICK);
+        if self.invaders.update(TICK, &mut self.rng) {
+            audio.play(Sound::Move);
+        }
+        if self.mothership.update(TICK, &mut self.rng) {
+            audio.play(Sound::Startup);
+        }
+        let (shields, mothership, rng) = (&mut self.shields, &mut self.mothership, &mut self.rng);
+        shields.crush(&self.invaders);
+        shields.block(&mut self.invaders.shots);
+        self.player.detect_hits_with(|x, y| shields.hit_at(x, y).then_some(0));
+        let points = self.player.detect_hits(&mut self.invaders)
+            + self.player.detect_hits_with(|x, y| mothership.hit_at(x, y, rng));
+        if points > 0 {
+            audio.play(Sound::Explode);
+            self.score.add(points);
+            self.invaders.speed_up(self.score.points);
+        }
+        if self.player.detect_hit_by(&mut self.invaders) {
+            audio.play(Sound::Explode);
+        }
+        self.score.lives = self.player.lives;
+        self.tick += 1;
+
+        // Next level, win or lose?
+        if self.invaders.all_killed() {
+            audio.play(Sound::Win);
+            if self.max_level == Some(self.score.level) {
+                self.status = Status::Won;
+                return self.status;
+            }
+            self.score.level += 1;
+            self.invaders = Invaders::for_level(self.score.level, self.field);
+            self.invaders.speed_up(self.score.points);
+        }
+        if self.invaders.reached_bottom() || self.player.dead() {
+            audio.play(Sound::Lose);
+            self.status = Status::Lost;
+        }
+        self.status
+    }
+
+    /// Plays steps until the game is over or `max_ticks` steps were played
+    pub fn run(&mut self, source: &mut dyn InputSource, audio: &mut dyn AudioBackend, max_ticks: u64) -> Status {
+        while self.status == Status::Playing && self.tick < max_ticks {
+            let inputs = source.inputs(self);
+            self.step(&inputs, audio);
+        }
+        self.status
+    }
+}
+
+impl Drawable for Game {
+    fn draw(&self, frame: &mut Frame) {
+        // the score is drawn last, over whatever reached the top row
+        let drawables: [&dyn Drawable; 5] = [&self.shields, &self.player, &self.invaders, &self.mothership, &self.score];
+        for drawable in drawables {
+            drawable.draw(frame);
+        }
+    }
+}
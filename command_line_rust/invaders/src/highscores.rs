@@ -0,0 +1,83 @@
+use std::{cmp::Reverse, fs, io, path::{Path, PathBuf}};
+
+use directories::ProjectDirs;
+
+// the table keeps this many scores
+const MAX_ENTRIES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub points: u32,
+    pub level: u32
+}
+
+/// The best scores, highest first, saved as `points<TAB>level<TAB>name` lines
+#[derive(Debug, Default)]
+pub struct HighScores {
+    pub entries: Vec<Entry>
+}
+
+impl HighScores {
+    /// Where the table is kept, under the user's data directory
+    pub fn default_path() -> Option<PathBuf> {
+        ProjectDirs::from("", "", "invaders").map(|dirs| dirs.data_dir().join("highscores.txt"))
+    }
+
+    /// Reads the table, a missing file being an empty table
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        let mut entries: Vec<Entry> = contents
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                Some(Entry {
+                    points: fields.next()?.parse().ok()?,
+                    level: fields.next()?.parse().ok()?,
+                    name: fields.next()?.to_string()
+                })
+            })
+            .collect();
+        entries.sort_by_key(|e| Reverse(e.points));
+        entries.truncate(MAX_ENTRIES);
+        Ok(Self { entries })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let lines: Vec<String> = self
+            .entries
+            .iter()
+            .map(|e| format!("{}\t{}\t{}\n", e.points, e.level, e.name))
+            .collect();
+        fs::write(path, lines.concat())
+    }
+
+    /// Whether `points` makes it into the table
+    pub fn qualifies(&self, points: u32) -> bool {
+        points > 0
+            && (self.entries.len() < MAX_ENTRIES
+                || self.entries.last().is_some_and(|last| points > last.points))
+    }
+
+    /// Adds a score and returns its rank, starting at 1, if it made it into the table.
+    /// A new score goes below older ones with the same points.
+    pub fn insert(&mut self, name: &str, points: u32, level: u32) -> Option<usize> {
+        if !self.qualifies(points) {
+            return None;
+        }
+        // tabs and newlines would break the file
+        let name: String = name.trim().chars().filter(|c| !c.is_control()).collect();
+        let name = if name.is_empty() { "anonymous".to_string() } else { name };
+        let idx = self.entries.iter().position(|e| e.points < points).unwrap_or(self.entries.len());
+        self.entries.insert(idx, Entry { name, points, level });
+        self.entries.truncate(MAX_ENTRIES);
+        Some(idx + 1)
+    }
+}
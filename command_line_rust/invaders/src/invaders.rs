@@ -1,38 +1,80 @@
 use std::{time::Duration, cmp::max};
 
+use rand::Rng;
 use rusty_time::Timer;
 
-use crate::{NUM_COLS, NUM_ROWS, frame::Drawable};
+use crate::{Field, frame::{Drawable, Frame}, shot::Shot};
+
+// how many shots of the invaders can be on the screen at once
+const MAX_SHOTS: usize = 3;
+// every this many points the invaders march 10% faster
+const SPEEDUP_POINTS: u32 = 100;
+// the rows of the formation, the first four make level 1 and every level adds the next one
+const ROWS: [usize; 9] = [2, 4, 6, 8, 3, 5, 7, 9, 10];
 
 pub struct Invader {
     pub x: usize,
     pub y: usize
 }
 
+impl Invader {
+    /// Invaders further from the player are worth more
+    pub fn points(&self) -> u32 {
+        match self.y {
+            0..=4 => 30,
+            5..=6 => 20,
+            _ => 10,
+        }
+    }
+}
+
 pub struct Invaders {
     pub army: Vec<Invader>,
+    pub shots: Vec<Shot>,
     move_timer: Timer,
-    direction: i32
+    // the marching pace before the score-based speedups
+    base_duration: Duration,
+    speedups: u32,
+    fire_timer: Timer,
+    direction: i32,
+    field: Field
 }
 
 impl Invaders {
-    pub fn new() -> Self {
+    pub fn new(field: Field) -> Self {
+        Self::for_level(1, field)
+    }
+
+    /// Each level has a denser formation that starts marching faster
+    pub fn for_level(level: u32, field: Field) -> Self {
+        let rows = &ROWS[..(3 + level as usize).min(ROWS.len())];
+        // as wide as on the default field at most, in the middle
+        let width = field.cols.min(Field::DEFAULT.cols);
+        let left = (field.cols - width) / 2;
         let mut army = Vec::new();
-        for x in 0..NUM_COLS {
-            for y in 0..NUM_ROWS {
-                if x > 1 && x < NUM_COLS - 2 && y > 0 && y < 9 && x % 2 == 0 && y % 2 == 0 {
-                    army.push(Invader {x, y})
-                } 
+        for x in 0..width {
+            for &y in rows {
+                if x > 1 && x < width - 2 && x % 2 == 0 {
+                    army.push(Invader {x: left + x, y})
+                }
             }
         }
+        let start = Duration::from_millis(2000u64.saturating_sub(250 * (level.max(1) as u64 - 1)).max(500));
         Self {
             army,
-            move_timer: Timer::new(Duration::from_millis(2000)),
-            direction: 1
+            shots: Vec::new(),
+            move_timer: Timer::new(start),
+            base_duration: start,
+            speedups: 0,
+            fire_timer: Timer::new(Duration::from_millis(1000)),
+            direction: 1,
+            field
         }
     }
 
-    pub fn update(&mut self, delta: Duration) -> bool {
+    /// Marches and fires, the shots being aimed with `rng`
+    pub fn update(&mut self, delta: Duration, rng: &mut impl Rng) -> bool {
+        self.update_shots(delta, rng);
         self.move_timer.tick(delta);
         if self.move_timer.finished() {
             self.move_timer.reset();
@@ -48,7 +90,7 @@ impl Invaders {
             } else {
                 let max_x = self.army.iter().map(|invader| invader.x).max().unwrap_or(0);
                 // invaders are all the way on the left side of the screen
-                if max_x == NUM_COLS - 1 {
+                if max_x == self.field.cols - 1 {
                     // next time invaders will move to the right
                     self.direction = -1;
                     downwards = true;
@@ -57,8 +99,9 @@ impl Invaders {
             if downwards {
                 // whenever invaders move downwards, they increase their speed, so the move_timer is
                 // going to go to a smaller value
-                let new_duration = max(self.move_timer.duration().as_millis() - 250, 250);
-                self.move_timer = Timer::new(Duration::from_millis(new_duration as u64));
+                let new_duration = max(self.base_duration.as_millis() - 250, 250);
+                self.base_duration = Duration::from_millis(new_duration as u64);
+                self.move_timer = Timer::new(self.paced(self.base_duration));
                 for invader in self.army.iter_mut() {
                     invader.y += 1;
                 }
@@ -72,27 +115,67 @@ impl Invaders {
         false
     }
 
+    /// Drops a shot now and then from the lowest invader of a random column
+    fn update_shots(&mut self, delta: Duration, rng: &mut impl Rng) {
+        for shot in self.shots.iter_mut() {
+            shot.update(delta);
+        }
+        self.shots.retain(|shot| !shot.dead());
+        self.fire_timer.tick(delta);
+        if self.fire_timer.finished() && !self.army.is_empty() {
+            self.fire_timer.reset();
+            if self.shots.len() < MAX_SHOTS && rng.gen_bool(0.7) {
+                let x = self.army[rng.gen_range(0..self.army.len())].x;
+                let y = self.army.iter().filter(|invader| invader.x == x).map(|invader| invader.y).max().unwrap_or(0);
+                if y + 1 < self.field.rows {
+                    self.shots.push(Shot::falling(x, y + 1, self.field.rows));
+                }
+            }
+        }
+    }
+
+    /// Marches faster as the score goes up, on top of the speedup of moving down
+    pub fn speed_up(&mut self, score: u32) {
+        let speedups = score / SPEEDUP_POINTS;
+        if speedups != self.speedups {
+            self.speedups = speedups;
+            let (move_duration, fire_duration) = (self.paced(self.base_duration), self.paced(Duration::from_millis(1000)));
+            retime(&mut self.move_timer, move_duration);
+            // they fire faster too
+            retime(&mut self.fire_timer, fire_duration);
+        }
+    }
+
+    fn paced(&self, duration: Duration) -> Duration {
+        let factor = 0.9_f64.powi(self.speedups as i32);
+        max(duration.mul_f64(factor), Duration::from_millis(100))
+    }
 
     pub fn all_killed(&self) -> bool {
         self.army.is_empty()
     }
 
     pub fn reached_bottom(&self) -> bool {
-        self.army.iter().map(|invader| invader.y).max().unwrap_or(0) >= NUM_ROWS - 1
+        self.army.iter().map(|invader| invader.y).max().unwrap_or(0) >= self.field.rows - 1
     }
 
-    pub fn kill_invader_at(&mut self, x: usize, y: usize) -> bool {
-        if let Some(idx) = self.army.iter().position(|invader| invader.x == x && invader.y == y) {
-            self.army.remove(idx);
-            true
-        } else {
-            false
-        }
+    /// Kills the invader at (x, y) and returns how many points it was worth
+    pub fn kill_invader_at(&mut self, x: usize, y: usize) -> Option<u32> {
+        let idx = self.army.iter().position(|invader| invader.x == x && invader.y == y)?;
+        Some(self.army.remove(idx).points())
     }
 }
 
+/// Changes the duration of a running timer, keeping what is left of it if it is shorter
+fn retime(timer: &mut Timer, duration: Duration) {
+    let remaining = timer.remaining();
+    timer.set_duration(duration);
+    // the remaining time is clamped to the new duration
+    timer.set_remaining(remaining);
+}
+
 impl Drawable for Invaders {
-    fn draw(&self, frame: &mut crate::frame::Frame) {
+    fn draw(&self, frame: &mut Frame) {
         for invader in self.army.iter() {
             frame[invader.x][invader.y] = if self.move_timer.percent_left() > 0.5 {
                 "x"
@@ -100,5 +183,8 @@ impl Drawable for Invaders {
                 "+"
             };
         }
+        for shot in self.shots.iter() {
+            shot.draw(frame);
+        }
     }
-}
\ No newline at end of file
+}
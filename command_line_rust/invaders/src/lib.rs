@@ -1,8 +1,58 @@
+pub mod audio;
 pub mod frame;
+pub mod game;
+pub mod replay;
 pub mod render;
 pub mod player;
 pub mod shot;
 pub mod invaders;
+pub mod score;
+pub mod shields;
+pub mod mothership;
+pub mod highscores;
+pub mod menu;
 
-pub const NUM_ROWS: usize = 20;
-pub const NUM_COLS: usize = 40;
\ No newline at end of file
+/// The size of the playfield, in terminal cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    pub cols: usize,
+    pub rows: usize
+}
+
+impl Field {
+    pub const DEFAULT: Field = Field { cols: 40, rows: 20 };
+    // the formation, the bunkers and the score still fit
+    pub const MIN: Field = Field { cols: 32, rows: 18 };
+    // the formation does not get any wider, so more room only makes the game slower
+    pub const MAX: Field = Field { cols: 64, rows: 24 };
+
+    /// The biggest field a terminal of `cols` x `rows` can show, if it is big enough
+    pub fn fit(cols: u16, rows: u16) -> Option<Field> {
+        let field = Field { cols: (cols as usize).min(Self::MAX.cols), rows: (rows as usize).min(Self::MAX.rows) };
+        (field.cols >= Self::MIN.cols && field.rows >= Self::MIN.rows).then_some(field)
+    }
+
+    /// Whether a terminal of `cols` x `rows` can show the whole field
+    pub fn fits_in(&self, cols: u16, rows: u16) -> bool {
+        self.cols <= cols as usize && self.rows <= rows as usize
+    }
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::Field;
+
+    #[test]
+    fn test_fit() {
+        assert_eq!(Field::fit(50, 22), Some(Field { cols: 50, rows: 22 }));
+        assert_eq!(Field::fit(200, 60), Some(Field::MAX));
+        assert_eq!(Field::fit(80, 10), None);
+        assert!(Field::DEFAULT.fits_in(40, 20));
+        assert!(!Field::DEFAULT.fits_in(39, 50));
+    }
+}
@@ -1,18 +1,173 @@
-use std::{error::Error, io, time::{Duration, Instant}, sync::mpsc, thread};
+use std::{error::Error, io::{self, Write}, mem, path::PathBuf, time::{Duration, Instant}, sync::mpsc, thread};
+use clap::{value_parser, Arg, ArgAction, Command};
 use crossterm::{terminal::{self, EnterAlternateScreen, LeaveAlternateScreen}, ExecutableCommand, cursor::{Hide, Show}, event::{self, KeyCode, Event}};
-use invaders::{frame::{self, new_frame, Drawable}, render, player::Player, invaders::Invaders};
-use rusty_audio::Audio;
+use invaders::{
+    audio::{AudioBackend, NullAudio, Sound},
+    frame::{new_frame, Drawable, Frame},
+    game::{Game, Input, InputSource, Status, TICK},
+    highscores::HighScores,
+    menu::{Choice, PauseMenu},
+    render,
+    replay::{Recorder, Replay, ReplayInput},
+    score::Score,
+    Field,
+};
+
+/// The keys pressed since the last step
+#[derive(Default)]
+struct Keyboard {
+    pressed: Vec<Input>,
+}
+
+impl InputSource for Keyboard {
+    fn inputs(&mut self, _game: &Game) -> Vec<Input> {
+        mem::take(&mut self.pressed)
+    }
+}
+
+/// Where the inputs of a game played in the terminal come from
+enum Controls {
+    Keyboard(Recorder<Keyboard>),
+    // a recorded game, that can still be stopped with the keyboard
+    Replay(ReplayInput, bool),
+}
+
+impl Controls {
+    fn press(&mut self, pressed: Vec<Input>) {
+        match self {
+            Controls::Keyboard(recorder) => recorder.source.pressed.extend(pressed),
+            Controls::Replay(_, quit) => *quit |= pressed.contains(&Input::Quit),
+        }
+    }
+}
+
+impl InputSource for Controls {
+    fn inputs(&mut self, game: &Game) -> Vec<Input> {
+        match self {
+            Controls::Keyboard(recorder) => recorder.inputs(game),
+            Controls::Replay(replay, quit) => {
+                let mut inputs = replay.inputs(game);
+                if *quit {
+                    inputs.push(Input::Quit);
+                }
+                inputs
+            }
+        }
+    }
+}
+
+/// What the render thread shows
+enum Screen {
+    Frame(Frame),
+    // the terminal was resized, so the next frame is drawn in full
+    Redraw,
+    TooSmall(u16, u16, Field),
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let mut audio = Audio::new();
-    audio.add("explode", "audio/explode.wav");
-    audio.add("lose", "audio/lose.wav");
-    audio.add("move", "audio/move.wav");
-    audio.add("pew", "audio/pew.wav");
-    audio.add("startup", "audio/startup.wav");
-    audio.add("win", "audio/win.wav");
-    audio.play("startup");
+    let matches = Command::new("invaders")
+        .version("0.1.0")
+        .author("Zhang Tianwei <zhangtianwei1015@gmail.com>")
+        .about("Terminal space invaders")
+        .arg(
+            Arg::new("seed")
+                .value_name("SEED")
+                .long("seed")
+                .help("Seed of the random numbers, to play the same game again")
+                .value_parser(value_parser!(u64))
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("record")
+                .value_name("FILE")
+                .long("record")
+                .help("Record the game to FILE")
+                .conflicts_with("replay")
+                .value_parser(value_parser!(PathBuf))
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("replay")
+                .value_name("FILE")
+                .long("replay")
+                .help("Play back a game recorded with --record")
+                .conflicts_with("seed")
+                .value_parser(value_parser!(PathBuf))
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("headless")
+                .long("headless")
+                .help("Simulate the replay without showing it, and print how it ends")
+                .requires("replay")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("mute")
+                .long("mute")
+                .help("Play without sound")
+                .action(ArgAction::SetTrue),
+        )
+        .get_matches();
+
+    let replay = match matches.get_one::<PathBuf>("replay") {
+        Some(path) => Some(Replay::load(path)?),
+        None => None,
+    };
+    if matches.get_flag("headless") {
+        let replay = replay.expect("--headless requires --replay");
+        let mut game = replay.game();
+        // a recorded game was played until it was over, so it ends the same way again
+        let status = game.run(&mut replay.player(), &mut NullAudio, u64::MAX);
+        println!("{:?} at tick {}: score {}, level {}", status, game.tick(), game.score.points, game.score.level);
+        return Ok(());
+    }
+
+    let mut audio = audio(matches.get_flag("mute"));
+    audio.play(Sound::Startup);
+    let mut game = match &replay {
+        Some(replay) => replay.game(),
+        None => {
+            let seed = matches.get_one::<u64>("seed").copied().unwrap_or_else(rand::random);
+            // as big as the terminal allows, a smaller terminal shows how big it has to be
+            let (cols, rows) = terminal::size()?;
+            Game::for_field(seed, Field::fit(cols, rows).unwrap_or(Field::MIN))
+        }
+    };
+    let mut controls = match &replay {
+        Some(replay) => Controls::Replay(replay.player(), false),
+        None => Controls::Keyboard(Recorder::new(Keyboard::default(), &game)),
+    };
+    play(&mut game, &mut controls, audio.as_mut())?;
+    audio.wait();
+    if let (Controls::Keyboard(recorder), Some(path)) = (&controls, matches.get_one::<PathBuf>("record")) {
+        recorder.replay.save(path)?;
+    }
+
+    println!("Score: {}, level {}", game.score.points, game.score.level);
+    // a game played back scores nothing
+    if replay.is_none() {
+        high_scores(&game.score)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "audio")]
+fn audio(mute: bool) -> Box<dyn AudioBackend> {
+    match mute {
+        true => Box::new(NullAudio),
+        false => Box::new(invaders::audio::RustyAudio::new()),
+    }
+}
 
+#[cfg(not(feature = "audio"))]
+fn audio(_mute: bool) -> Box<dyn AudioBackend> {
+    Box::new(NullAudio)
+}
+
+/// Plays the game in the terminal until it is over
+fn play(game: &mut Game, controls: &mut Controls, audio: &mut dyn AudioBackend) -> Result<(), Box<dyn Error>> {
     // Terminal
     let mut stdout = io::stdout();
     terminal::enable_raw_mode()?;
@@ -22,88 +177,133 @@ fn main() -> Result<(), Box<dyn Error>> {
     // Render loop in a separate thread
     let (render_tx, render_rx) = mpsc::channel();
     let render_handle = thread::spawn(move || {
-        let mut last_frame = frame::new_frame();
+        // nothing to compare the next frame with, so it is drawn in full
+        let mut last_frame: Option<Frame> = None;
         let mut stdout = io::stdout();
-        render::render(&mut stdout, &last_frame, &last_frame, true);
-        loop {
-            let curr_frame = match render_rx.recv() {
-                Ok(x) => x,
-                Err(_) => break
-            };
-            render::render(&mut stdout, &last_frame, &curr_frame, false);
-            last_frame = curr_frame;
+        while let Ok(screen) = render_rx.recv() {
+            match screen {
+                Screen::Frame(curr_frame) => {
+                    match &last_frame {
+                        Some(last_frame) => render::render(&mut stdout, last_frame, &curr_frame, false),
+                        None => render::render(&mut stdout, &curr_frame, &curr_frame, true),
+                    }
+                    last_frame = Some(curr_frame);
+                }
+                Screen::Redraw => last_frame = None,
+                Screen::TooSmall(cols, rows, field) => {
+                    render::too_small(&mut stdout, cols, rows, field);
+                    last_frame = None;
+                }
+            }
         }
     });
 
-    let mut player = Player::new();
-    let mut instant = Instant::now();
-    let mut invaders = Invaders::new();
-
     // Game loop
-    'gameloop: loop {
-        // Per-frame init
-        let delta = instant.elapsed();
-        instant = Instant::now();
-        let mut curr_frame = new_frame();
-
+    // the game moves in steps of TICK, as many as fit in the time since the last frame
+    let (mut cols, mut rows) = terminal::size()?;
+    let mut resized = true;
+    let mut menu: Option<PauseMenu> = None;
+    let mut instant = Instant::now();
+    let mut lag = Duration::ZERO;
+    while game.status() == Status::Playing {
+        let mut pressed = vec![];
         while event::poll(Duration::default())? {
-            if let Event::Key(key_event) = event::read()? {
-                match key_event.code {
-                    KeyCode::Left => player.move_left(),
-                    KeyCode::Right => player.move_right(),
-                    KeyCode::Char(' ') | KeyCode::Enter => {
-                        if player.shoot() {
-                            audio.play("pew");
-
-                        }
-                    }
-                    KeyCode::Esc | KeyCode::Char('q') => {
-                        audio.play("lose");
-                        break 'gameloop;
-                    }
-                    _  => {}
+            match event::read()? {
+                Event::Resize(new_cols, new_rows) => {
+                    (cols, rows) = (new_cols, new_rows);
+                    resized = true;
                 }
+                Event::Key(key_event) => match &mut menu {
+                    Some(paused) => match key_event.code {
+                        KeyCode::Up => paused.up(),
+                        KeyCode::Down => paused.down(),
+                        KeyCode::Char(' ') | KeyCode::Enter => match paused.choice() {
+                            Choice::Resume => menu = None,
+                            Choice::Quit => pressed.push(Input::Quit),
+                        },
+                        KeyCode::Esc | KeyCode::Char('p') => menu = None,
+                        KeyCode::Char('q') => pressed.push(Input::Quit),
+                        _ => {}
+                    },
+                    None => match key_event.code {
+                        KeyCode::Left => pressed.push(Input::Left),
+                        KeyCode::Right => pressed.push(Input::Right),
+                        KeyCode::Char(' ') | KeyCode::Enter => pressed.push(Input::Shoot),
+                        KeyCode::Esc | KeyCode::Char('p') => menu = Some(PauseMenu::default()),
+                        KeyCode::Char('q') => pressed.push(Input::Quit),
+                        _ => {}
+                    },
+                },
+                _ => {}
             }
         }
+        let quit = pressed.contains(&Input::Quit);
+        controls.press(pressed);
 
-        // Updates
-        player.update(delta);
-        if invaders.update(delta) {
-            audio.play("move");
+        let fits = game.field().fits_in(cols, rows);
+        if resized {
+            resized = false;
+            let _ = render_tx.send(if fits { Screen::Redraw } else { Screen::TooSmall(cols, rows, game.field()) });
         }
-        if player.detect_hits(&mut invaders) {
-            audio.play("explode");
+        if !fits {
+            // the game waits for the terminal to grow, and for the player after that
+            menu.get_or_insert_with(PauseMenu::default);
+        }
+        if menu.is_none() {
+            lag += instant.elapsed();
+            while lag >= TICK && game.status() == Status::Playing {
+                lag -= TICK;
+                let inputs = controls.inputs(game);
+                game.step(&inputs, audio);
+            }
+        } else if quit {
+            // quitting takes a step, but the game does not move
+            let inputs = controls.inputs(game);
+            game.step(&inputs, audio);
         }
+        instant = Instant::now();
 
         // Draw & render
         // We expect this will fail the first few times because this game loop is going to get going
         // before that child thread is set up and start receiving. So there's not going to be a receiving end
         // of the channel available for a little while.
-        let drawables: Vec<&dyn Drawable> = vec![&player, &invaders];
-        for drawable in drawables {
-            drawable.draw(&mut curr_frame);
+        if fits {
+            let mut curr_frame = new_frame(game.field());
+            game.draw(&mut curr_frame);
+            if let Some(paused) = &menu {
+                paused.draw(&mut curr_frame);
+            }
+            let _ = render_tx.send(Screen::Frame(curr_frame));
         }
-        let _ = render_tx.send(curr_frame);
         thread::sleep(Duration::from_millis(1));
-
-        // Win or lose?
-        if invaders.all_killed() {
-            audio.play("win");
-            break 'gameloop;
-        }
-        if invaders.reached_bottom() {
-            audio.play("lose");
-            break 'gameloop;
-        }
     }
 
     // Cleanup
     drop(render_tx);
     render_handle.join().unwrap();
-    audio.wait();
     stdout.execute(Show)?;
     stdout.execute(LeaveAlternateScreen)?;
     terminal::disable_raw_mode()?;
+    Ok(())
+}
 
+/// Asks for a name if the score made it into the high-score table, then shows the table
+fn high_scores(score: &Score) -> Result<(), Box<dyn Error>> {
+    let Some(path) = HighScores::default_path() else {
+        return Ok(());
+    };
+    let mut table = HighScores::load(&path)?;
+    if table.qualifies(score.points) {
+        print!("New high score! Enter your name: ");
+        io::stdout().flush()?;
+        let mut name = String::new();
+        io::stdin().read_line(&mut name)?;
+        table.insert(&name, score.points, score.level);
+        table.save(&path)?;
+    }
+    println!("\nHIGH SCORES");
+    for (i, entry) in table.entries.iter().enumerate() {
+        println!("{:>2}. {:>6}  level {:<2}  {}", i + 1, entry.points, entry.level, entry.name);
+    }
     Ok(())
 }
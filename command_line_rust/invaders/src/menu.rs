@@ -0,0 +1,62 @@
+use crate::{frame::{Drawable, Frame}, score::draw_text};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    Resume,
+    Quit
+}
+
+const CHOICES: [(Choice, &str); 2] = [(Choice::Resume, "RESUME"), (Choice::Quit, "QUIT")];
+
+/// The menu shown over the game while it is paused
+#[derive(Default)]
+pub struct PauseMenu {
+    selected: usize
+}
+
+impl PauseMenu {
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        self.selected = (self.selected + 1).min(CHOICES.len() - 1);
+    }
+
+    pub fn choice(&self) -> Choice {
+        CHOICES[self.selected].0
+    }
+}
+
+impl Drawable for PauseMenu {
+    fn draw(&self, frame: &mut Frame) {
+        let mut lines = vec!["+----------+".to_string(), "|  PAUSED  |".to_string()];
+        for (i, (_, label)) in CHOICES.iter().enumerate() {
+            let cursor = if i == self.selected { ">" } else { " " };
+            lines.push(format!("| {} {:<6} |", cursor, label));
+        }
+        lines.push("+----------+".to_string());
+        // in the middle of the field
+        let x = frame.len().saturating_sub(lines[0].len()) / 2;
+        let y = frame[0].len().saturating_sub(lines.len()) / 2;
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(frame, x, y + i, line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Choice, PauseMenu};
+
+    #[test]
+    fn test_choice() {
+        let mut menu = PauseMenu::default();
+        assert_eq!(menu.choice(), Choice::Resume);
+        menu.up();
+        assert_eq!(menu.choice(), Choice::Resume);
+        menu.down();
+        menu.down();
+        assert_eq!(menu.choice(), Choice::Quit);
+    }
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use rand::Rng;
+use rusty_time::Timer;
+
+use crate::{frame::{Drawable, Frame}, Field};
+
+// the mothership flies over the invaders, under the score
+const ROW: usize = 1;
+const SHAPE: [&str; 3] = ["<", "M", ">"];
+
+/// The bonus invader crossing the top of the screen now and then
+pub struct Mothership {
+    // column of the left end while flying across
+    x: Option<i32>,
+    direction: i32,
+    cols: usize,
+    move_timer: Timer,
+    // time until the next appearance
+    spawn_timer: Timer
+}
+
+impl Mothership {
+    pub fn new(field: Field, rng: &mut impl Rng) -> Self {
+        Self {
+            x: None,
+            direction: 1,
+            cols: field.cols,
+            move_timer: Timer::new(Duration::from_millis(120)),
+            spawn_timer: Timer::new(next_appearance(rng))
+        }
+    }
+
+    /// Returns true when the mothership appears
+    pub fn update(&mut self, delta: Duration, rng: &mut impl Rng) -> bool {
+        match self.x {
+            None => {
+                self.spawn_timer.tick(delta);
+                if self.spawn_timer.finished() {
+                    // from either side
+                    self.direction = if rng.gen_bool(0.5) { 1 } else { -1 };
+                    self.x = Some(if self.direction == 1 { 0 } else { (self.cols - SHAPE.len()) as i32 });
+                    self.move_timer.reset();
+                    return true;
+                }
+            }
+            Some(x) => {
+                self.move_timer.tick(delta);
+                if self.move_timer.finished() {
+                    self.move_timer.reset();
+                    let x = x + self.direction;
+                    if x < 0 || x as usize + SHAPE.len() > self.cols {
+                        self.leave(rng);
+                    } else {
+                        self.x = Some(x);
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    pub fn flying(&self) -> bool {
+        self.x.is_some()
+    }
+
+    fn leave(&mut self, rng: &mut impl Rng) {
+        self.x = None;
+        self.spawn_timer = Timer::new(next_appearance(rng));
+    }
+
+    /// Shoots the mothership down if it is at (x, y), for 50 to 300 points
+    pub fn hit_at(&mut self, x: usize, y: usize, rng: &mut impl Rng) -> Option<u32> {
+        let left = self.x? as usize;
+        if y != ROW || x < left || x >= left + SHAPE.len() {
+            return None;
+        }
+        self.leave(rng);
+        Some(50 * rng.gen_range(1..=6))
+    }
+}
+
+fn next_appearance(rng: &mut impl Rng) -> Duration {
+    Duration::from_secs(rng.gen_range(15..30))
+}
+
+impl Drawable for Mothership {
+    fn draw(&self, frame: &mut Frame) {
+        if let Some(x) = self.x {
+            for (i, s) in SHAPE.iter().enumerate() {
+                frame[x as usize + i][ROW] = s;
+            }
+        }
+    }
+}
@@ -1,16 +1,37 @@
 use std::time::Duration;
 
-use crate::{NUM_COLS, NUM_ROWS, frame::{Drawable, Frame}, shot::Shot, invaders::Invaders};
+use rusty_time::Timer;
+
+use crate::{Field, frame::{Drawable, Frame}, shot::Shot, invaders::Invaders};
+
+pub const LIVES: u32 = 3;
 
 pub struct Player {
     x: usize,
     y: usize,
-    shots: Vec<Shot>
+    max_x: usize,
+    shots: Vec<Shot>,
+    pub lives: u32,
+    // after a hit the player can not be hit again for a little while
+    invulnerable: Timer
 }
 
 impl Player {
-    pub fn new() -> Self {
-        Self { x: NUM_COLS / 2, y: NUM_ROWS - 1, shots: Vec::new() }
+    pub fn new(field: Field) -> Self {
+        let mut invulnerable = Timer::new(Duration::from_millis(2000));
+        invulnerable.set_elapsed(invulnerable.duration());
+        Self {
+            x: field.cols / 2,
+            y: field.rows - 1,
+            max_x: field.cols - 1,
+            shots: Vec::new(),
+            lives: LIVES,
+            invulnerable
+        }
+    }
+
+    pub fn x(&self) -> usize {
+        self.x
     }
 
     pub fn move_left(&mut self) {
@@ -20,7 +41,7 @@ impl Player {
     }
 
     pub fn move_right(&mut self) {
-        if self.x < NUM_COLS - 1 {
+        if self.x < self.max_x {
             self.x += 1;
         }
     }
@@ -35,31 +56,61 @@ impl Player {
     }
 
     pub fn update(&mut self, delta: Duration) {
+        self.invulnerable.tick(delta);
         for shot in self.shots.iter_mut() {
             shot.update(delta);
         }
         self.shots.retain(|shot| !shot.dead());
     }
 
-    pub fn detect_hits(&mut self, invaders: &mut Invaders) -> bool {
-        let mut hit_sth = false;
+    /// Returns the points of the invaders shot down
+    pub fn detect_hits(&mut self, invaders: &mut Invaders) -> u32 {
+        self.detect_hits_with(|x, y| invaders.kill_invader_at(x, y))
+    }
+
+    /// Explodes the shots for which `hit` says something was hit at their position,
+    /// and returns the sum of the points it was worth
+    pub fn detect_hits_with(&mut self, mut hit: impl FnMut(usize, usize) -> Option<u32>) -> u32 {
+        let mut points = 0;
         for shot in self.shots.iter_mut() {
             if !shot.exploding {
-                if invaders.kill_invader_at(shot.x, shot.y) {
-                    hit_sth = true;
+                if let Some(worth) = hit(shot.x, shot.y) {
+                    points += worth;
                     shot.explode();
                 }
             }
         }
-        hit_sth
+        points
+    }
+
+    /// Whether a shot of the invaders hit the player, who then loses a life
+    pub fn detect_hit_by(&mut self, invaders: &mut Invaders) -> bool {
+        let Some(idx) = invaders.shots.iter().position(|shot| !shot.exploding && shot.x == self.x && shot.y == self.y) else {
+            return false;
+        };
+        invaders.shots.remove(idx);
+        if !self.invulnerable.finished() {
+            return false;
+        }
+        self.lives = self.lives.saturating_sub(1);
+        self.invulnerable.reset();
+        true
+    }
+
+    pub fn dead(&self) -> bool {
+        self.lives == 0
     }
 }
 
 impl Drawable for Player {
     fn draw(&self, frame: &mut Frame) {
-        frame[self.x][self.y] = "A";
+        // blink while invulnerable
+        let hidden = !self.invulnerable.finished() && (self.invulnerable.percent() * 10.0) as u32 % 2 == 1;
+        if !hidden {
+            frame[self.x][self.y] = "A";
+        }
         for shot in self.shots.iter() {
             shot.draw(frame);
         }
     }
-}
\ No newline at end of file
+}
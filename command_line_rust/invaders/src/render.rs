@@ -1,7 +1,7 @@
 use std::io::{Stdout, Write};
 use crossterm::{style::{SetBackgroundColor, Color}, QueueableCommand, terminal::{Clear, ClearType}, cursor::MoveTo};
 
-use crate::frame::Frame;
+use crate::{frame::Frame, Field};
 
 pub fn render(stdout: &mut Stdout, last_frame: &Frame, cur_frame: &Frame, force: bool) {
     if force {
@@ -18,4 +18,20 @@ pub fn render(stdout: &mut Stdout, last_frame: &Frame, cur_frame: &Frame, force:
         }
     }
     stdout.flush().unwrap();
-}
\ No newline at end of file
+}
+/// Replaces the game with a note on how big the terminal of `cols` x `rows` has to be
+pub fn too_small(stdout: &mut Stdout, cols: u16, rows: u16, field: Field) {
+    stdout.queue(SetBackgroundColor(Color::Reset)).unwrap();
+    stdout.queue(Clear(ClearType::All)).unwrap();
+    let lines = [
+        "Terminal too small".to_string(),
+        format!("{}x{}, needs {}x{}", cols, rows, field.cols, field.rows),
+        "q to quit".to_string(),
+    ];
+    for (y, line) in lines.iter().enumerate() {
+        stdout.queue(MoveTo(0, y as u16)).unwrap();
+        // as much as fits, the terminal could be tiny
+        print!("{}", line.chars().take(cols as usize).collect::<String>());
+    }
+    stdout.flush().unwrap();
+}
@@ -0,0 +1,138 @@
+use std::{fs, io, path::Path};
+
+use crate::{game::{Game, Input, InputSource}, Field};
+
+const HEADER: &str = "invaders replay 1";
+
+/// The seed and field of a game and the inputs given at each tick, enough to play it again.
+///
+/// Saved as text: the header line, `seed N`, `field COLSxROWS`, then a `TICK INPUT` line per input.
+/// Replays without a field line were played on the default field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub field: Field,
+    pub inputs: Vec<(u64, Input)>
+}
+
+impl Replay {
+    /// An empty replay of `game`
+    pub fn new(game: &Game) -> Self {
+        Self { seed: game.seed(), field: game.field(), inputs: Vec::new() }
+    }
+
+    /// The game to play the inputs on
+    pub fn game(&self) -> Game {
+        Game::for_field(self.seed, self.field)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().peekable();
+        if lines.next() != Some(HEADER) {
+            return Err("not an invaders replay".to_string());
+        }
+        let seed = lines
+            .next()
+            .and_then(|line| line.strip_prefix("seed "))
+            .and_then(|seed| seed.parse().ok())
+            .ok_or("missing seed")?;
+        let mut field = Field::DEFAULT;
+        if let Some(size) = lines.peek().and_then(|line| line.strip_prefix("field ")) {
+            field = parse_field(size).ok_or_else(|| format!("invalid field \"{}\"", size))?;
+            lines.next();
+        }
+        let mut inputs = Vec::new();
+        for line in lines.filter(|line| !line.is_empty()) {
+            let (tick, input) = line.split_once(' ').ok_or_else(|| format!("invalid line \"{}\"", line))?;
+            let tick = tick.parse().map_err(|_| format!("invalid tick \"{}\"", tick))?;
+            inputs.push((tick, input.parse()?));
+        }
+        Ok(Self { seed, field, inputs })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\nseed {}\nfield {}x{}\n", HEADER, self.seed, self.field.cols, self.field.rows);
+        for (tick, input) in &self.inputs {
+            text.push_str(&format!("{} {}\n", tick, input.name()));
+        }
+        text
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    /// Gives the recorded inputs again, at their ticks
+    pub fn player(&self) -> ReplayInput {
+        ReplayInput { inputs: self.inputs.clone(), next: 0 }
+    }
+}
+
+fn parse_field(size: &str) -> Option<Field> {
+    let (cols, rows) = size.split_once('x')?;
+    let field = Field { cols: cols.parse().ok()?, rows: rows.parse().ok()? };
+    // the game does not fit in a smaller one
+    (field.cols >= Field::MIN.cols && field.rows >= Field::MIN.rows).then_some(field)
+}
+
+pub struct ReplayInput {
+    inputs: Vec<(u64, Input)>,
+    next: usize
+}
+
+impl InputSource for ReplayInput {
+    fn inputs(&mut self, game: &Game) -> Vec<Input> {
+        let mut inputs = Vec::new();
+        while let Some(&(tick, input)) = self.inputs.get(self.next) {
+            if tick > game.tick() {
+                break;
+            }
+            inputs.push(input);
+            self.next += 1;
+        }
+        inputs
+    }
+}
+
+/// Records the inputs of another source while passing them on
+pub struct Recorder<S: InputSource> {
+    pub source: S,
+    pub replay: Replay
+}
+
+impl<S: InputSource> Recorder<S> {
+    pub fn new(source: S, game: &Game) -> Self {
+        Self { source, replay: Replay::new(game) }
+    }
+}
+
+impl<S: InputSource> InputSource for Recorder<S> {
+    fn inputs(&mut self, game: &Game) -> Vec<Input> {
+        let inputs = self.source.inputs(game);
+        self.replay.inputs.extend(inputs.iter().map(|&input| (game.tick(), input)));
+        inputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Replay;
+    use crate::{game::Input, Field};
+
+    #[test]
+    fn test_text() {
+        let field = Field { cols: 60, rows: 24 };
+        let replay = Replay { seed: 7, field, inputs: vec![(0, Input::Left), (0, Input::Shoot), (42, Input::Quit)] };
+        let text = replay.to_text();
+        assert_eq!(text, "invaders replay 1\nseed 7\nfield 60x24\n0 left\n0 shoot\n42 quit\n");
+        assert_eq!(Replay::parse(&text), Ok(replay));
+        assert_eq!(Replay::parse("invaders replay 1\nseed 7\n3 left\n").unwrap().field, Field::DEFAULT);
+        assert!(Replay::parse("seed 7\n").is_err());
+        assert!(Replay::parse("invaders replay 1\nseed 7\nfield 10x10\n").is_err());
+        assert!(Replay::parse("invaders replay 1\nseed 7\n3 jump\n").is_err());
+    }
+}
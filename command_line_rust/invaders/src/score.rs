@@ -0,0 +1,43 @@
+use crate::frame::{Drawable, Frame};
+
+// printable ASCII, so the HUD can be drawn into the frame one `&'static str` at a time
+const GLYPHS: &str = " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+
+/// The score, level and lives shown on the top row
+pub struct Score {
+    pub points: u32,
+    pub level: u32,
+    pub lives: u32
+}
+
+impl Score {
+    pub fn new(lives: u32) -> Self {
+        Self { points: 0, level: 1, lives }
+    }
+
+    pub fn add(&mut self, points: u32) {
+        self.points += points;
+    }
+}
+
+/// Writes `text` on row `y` of the frame, starting at column `x`
+pub fn draw_text(frame: &mut Frame, x: usize, y: usize, text: &str) {
+    for (i, c) in text.chars().enumerate().take(frame.len().saturating_sub(x)) {
+        frame[x + i][y] = glyph(c);
+    }
+}
+
+fn glyph(c: char) -> &'static str {
+    match (c as usize).checked_sub(' ' as usize) {
+        Some(i) if i < GLYPHS.len() => &GLYPHS[i..i + 1],
+        _ => "?",
+    }
+}
+
+impl Drawable for Score {
+    fn draw(&self, frame: &mut Frame) {
+        draw_text(frame, 0, 0, &format!("SCORE {:05}  LEVEL {}", self.points, self.level));
+        let lives = format!("LIVES {}", "A".repeat(self.lives as usize));
+        draw_text(frame, frame.len().saturating_sub(lives.len()), 0, &lives);
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{frame::{Drawable, Frame}, invaders::Invaders, shot::Shot, Field};
+
+// a bunker is this many blocks wide and tall
+const WIDTH: usize = 4;
+const HEIGHT: usize = 2;
+const BUNKERS: usize = 4;
+// hits a block takes before it is gone
+const HEALTH: u8 = 3;
+
+pub struct Block {
+    pub x: usize,
+    pub y: usize,
+    pub health: u8
+}
+
+/// The bunkers between the invaders and the player, worn down by every shot
+pub struct Shields {
+    pub blocks: Vec<Block>
+}
+
+impl Shields {
+    pub fn new(field: Field) -> Self {
+        let mut blocks = Vec::new();
+        // spread evenly over the width, a little above the player
+        let gap = (field.cols - BUNKERS * WIDTH) / (BUNKERS + 1);
+        for bunker in 0..BUNKERS {
+            let left = gap + bunker * (WIDTH + gap);
+            for x in left..left + WIDTH {
+                for y in field.rows - 3 - HEIGHT..field.rows - 3 {
+                    blocks.push(Block { x, y, health: HEALTH });
+                }
+            }
+        }
+        Self { blocks }
+    }
+
+    /// Damages the block at (x, y), if there is one
+    pub fn hit_at(&mut self, x: usize, y: usize) -> bool {
+        let Some(idx) = self.blocks.iter().position(|block| block.x == x && block.y == y) else {
+            return false;
+        };
+        self.blocks[idx].health -= 1;
+        if self.blocks[idx].health == 0 {
+            self.blocks.remove(idx);
+        }
+        true
+    }
+
+    /// Stops the shots of the invaders that hit a block
+    pub fn block(&mut self, shots: &mut [Shot]) {
+        for shot in shots.iter_mut().filter(|shot| !shot.exploding) {
+            if self.hit_at(shot.x, shot.y) {
+                shot.explode();
+            }
+        }
+    }
+
+    /// Invaders marching through the bunkers destroy them
+    pub fn crush(&mut self, invaders: &Invaders) {
+        self.blocks.retain(|block| {
+            !invaders.army.iter().any(|invader| invader.x == block.x && invader.y == block.y)
+        });
+    }
+}
+
+impl Drawable for Shields {
+    fn draw(&self, frame: &mut Frame) {
+        for block in self.blocks.iter() {
+            frame[block.x][block.y] = match block.health {
+                3 => "#",
+                2 => "=",
+                _ => "-",
+            };
+        }
+    }
+}
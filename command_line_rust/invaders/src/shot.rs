@@ -8,6 +8,10 @@ pub struct Shot {
     pub x: usize,
     pub y: usize,
     pub exploding: bool,
+    // shots of the invaders fall down the screen instead of going up
+    pub falling: bool,
+    // the row a falling shot leaves the field at
+    bottom: usize,
     timer: Timer
 }
 
@@ -17,14 +21,29 @@ impl Shot {
             x,
             y,
             exploding: false,
+            falling: false,
+            bottom: 0,
             timer: Timer::new(Duration::from_millis(50))
         }
     }
 
+    pub fn falling(x: usize, y: usize, bottom: usize) -> Self {
+        Self {
+            falling: true,
+            bottom,
+            // the invaders shoot slower than the player
+            timer: Timer::new(Duration::from_millis(150)),
+            ..Self::new(x, y)
+        }
+    }
+
     pub fn update(&mut self, delta: Duration) {
         self.timer.tick(delta);
         if self.timer.finished() && !self.exploding {
-            if self.y > 0 {
+            if self.falling {
+                // one row past the bottom, so it can still hit the player on the last row
+                self.y += 1;
+            } else if self.y > 0 {
                 self.y -= 1;
             }
             self.timer.reset();
@@ -37,12 +56,19 @@ impl Shot {
     }
 
     pub fn dead(&self) -> bool {
-        (self.exploding && self.timer.finished()) || (self.y == 0)
+        let gone = if self.falling { self.y >= self.bottom } else { self.y == 0 };
+        (self.exploding && self.timer.finished()) || gone
     }
 }
 
 impl Drawable for Shot {
     fn draw(&self, frame: &mut crate::frame::Frame) {
-        frame[self.x][self.y] = if self.exploding {"*"} else {"|"};
+        frame[self.x][self.y] = if self.exploding {
+            "*"
+        } else if self.falling {
+            ":"
+        } else {
+            "|"
+        };
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,98 @@
+use invaders::{
+    audio::NullAudio,
+    frame::{new_frame, Drawable},
+    game::{Game, Input, InputSource, Status, TICK},
+    replay::{Recorder, Replay},
+    Field,
+};
+
+// ten minutes of play
+const MAX_TICKS: u64 = 10 * 60 * 1000 / TICK.as_millis() as u64;
+
+/// Never touches the keyboard
+struct Idle;
+
+impl InputSource for Idle {
+    fn inputs(&mut self, _game: &Game) -> Vec<Input> {
+        vec![]
+    }
+}
+
+/// Walks under the lowest invader nearest to it and shoots
+struct Bot;
+
+impl InputSource for Bot {
+    fn inputs(&mut self, game: &Game) -> Vec<Input> {
+        let x = game.player.x();
+        let target = game
+            .invaders
+            .army
+            .iter()
+            .max_by_key(|invader| (invader.y, usize::MAX - invader.x.abs_diff(x)));
+        match target {
+            Some(invader) if invader.x < x => vec![Input::Left],
+            Some(invader) if invader.x > x => vec![Input::Right],
+            Some(_) => vec![Input::Shoot],
+            None => vec![],
+        }
+    }
+}
+
+#[test]
+fn idle_player_loses() {
+    let mut game = Game::new(1);
+    assert_eq!(game.run(&mut Idle, &mut NullAudio, MAX_TICKS), Status::Lost);
+    assert_eq!(game.score.points, 0);
+    assert_eq!(game.score.level, 1);
+}
+
+#[test]
+fn bot_wins() {
+    let mut game = Game::new(1).with_max_level(1);
+    assert_eq!(game.run(&mut Bot, &mut NullAudio, MAX_TICKS), Status::Won);
+    // 9 columns of 10 + 10 + 20 + 30 points, and maybe a mothership
+    assert!(game.score.points >= 630);
+    assert_eq!(game.score.level, 1);
+}
+
+#[test]
+fn quit() {
+    let mut game = Game::new(1);
+    assert_eq!(game.step(&[Input::Quit], &mut NullAudio), Status::Quit);
+    assert_eq!(game.tick(), 0);
+}
+
+#[test]
+fn replay_is_deterministic() {
+    let mut game = Game::new(42).with_max_level(1);
+    let mut recorder = Recorder::new(Bot, &game);
+    let status = game.run(&mut recorder, &mut NullAudio, MAX_TICKS);
+    assert_ne!(status, Status::Playing);
+
+    let replay = Replay::parse(&recorder.replay.to_text()).unwrap();
+    let mut again = replay.game().with_max_level(1);
+    assert_eq!(again.run(&mut replay.player(), &mut NullAudio, MAX_TICKS), status);
+    assert_eq!(again.tick(), game.tick());
+    assert_eq!(again.score.points, game.score.points);
+    assert_eq!(again.player.lives, game.player.lives);
+}
+
+/// Draws the game every step, so anything off the field panics
+struct Drawn<S: InputSource>(S);
+
+impl<S: InputSource> InputSource for Drawn<S> {
+    fn inputs(&mut self, game: &Game) -> Vec<Input> {
+        game.draw(&mut new_frame(game.field()));
+        self.0.inputs(game)
+    }
+}
+
+#[test]
+fn plays_on_any_field() {
+    for field in [Field::MIN, Field::DEFAULT, Field::MAX] {
+        let mut game = Game::for_field(1, field).with_max_level(2);
+        let status = game.run(&mut Drawn(Bot), &mut NullAudio, MAX_TICKS);
+        assert_ne!(status, Status::Playing, "{:?}", field);
+        assert!(game.score.points > 0);
+    }
+}
@@ -1,48 +1,101 @@
+use chrono::{Local, NaiveDate};
 use clap::{Command, Arg, ArgAction, value_parser};
 use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
 use regex::{Regex, RegexBuilder};
+use serde_json::json;
 use walkdir::WalkDir;
 use std::{
+    env,
     error::Error,
-    fs::{File, self},
-    io::{BufRead, BufReader}, 
-    path::PathBuf, 
+    fs::{File, OpenOptions, self},
+    io::{BufRead, BufReader, Write}, 
+    path::{Path, PathBuf}, 
     ffi::OsStr
 };
 
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
+const HISTORY_FILE: &str = ".fortuner_history";
+
 #[derive(Debug)]
 pub struct Config {
     sources: Vec<String>,
     pattern: Option<Regex>,
     seed: Option<u64>,
+    daily: bool,
+    user: Option<String>,
+    date: NaiveDate,
+    history: Option<PathBuf>,
+    no_repeat: usize,
+    json: bool,
 }
 
 #[derive(Debug)]
 struct Fortune {
     source: String,//filename containing the record
+    index: usize,//position of the record within its source file
     text: String //contents of the record up to but not including the terminating percent sign
 }
 
+#[derive(Debug, PartialEq)]
+struct HistoryEntry {
+    date: NaiveDate,//the day the fortune was shown
+    source: String,
+    index: usize
+}
+
+impl HistoryEntry {
+    fn is(&self, fortune: &Fortune) -> bool {
+        self.source == fortune.source && self.index == fortune.index
+    }
+}
+
 pub fn run(config: Config) -> MyResult<()> {
     let files = find_files(&config.sources)?;
     let fortunes = read_fortunes(&files)?;
     if let Some(pattern) = config.pattern {
         let mut prev_source = None;
         for fortune in fortunes.iter().filter(|f| pattern.is_match(&f.text)) {
+            if config.json {
+                println!("{}", fortune_to_json(fortune));
+                continue;
+            }
             // print all the fortunes matching the pattern
             // print the source header if the current source is not the same as the previous one seen
-            if prev_source.as_ref().map_or(true, |s| s != &fortune.source) {
+            if prev_source.as_ref() != Some(&fortune.source) {
                 eprintln!("({})\n%", fortune.source);
                 prev_source = Some(fortune.source.clone());
             }
             println!("{}\n%", fortune.text);
         }
     } else {
+        let history = match &config.history {
+            Some(path) => read_history(path)?,
+            None => vec![],
+        };
+        let fortune = if config.daily {
+            // everyone gets the same one, whatever their history
+            pick_daily(&fortunes, daily_seed(config.date, config.user.as_deref()))
+        } else {
+            let recent = &history[history.len().saturating_sub(config.no_repeat)..];
+            pick_fortune(&fortunes, config.seed, recent)
+        };
+        if let (Some(path), Some(fortune)) = (&config.history, fortune) {
+            // the fortune of the day is recorded once, however often it is asked for
+            let recorded = config.daily
+                && history.last().is_some_and(|h| h.date == config.date && h.is(fortune));
+            if !recorded {
+                append_history(path, &HistoryEntry {
+                    date: config.date,
+                    source: fortune.source.clone(),
+                    index: fortune.index
+                })?;
+            }
+        }
         // select and print one fortune
-        match pick_fortune(&fortunes, config.seed) {
-            Some(fortune) => println!("{}", fortune),
+        match fortune {
+            Some(fortune) if config.json => println!("{}", fortune_to_json(fortune)),
+            Some(fortune) => println!("{}", fortune.text),
             None => println!("No fortunes found")
         }
     }   
@@ -85,6 +138,54 @@ pub fn get_args() -> MyResult<Config> {
                 .help("Case-insensitive pattern matching")
                 .action(ArgAction::SetTrue)
         )
+        .arg(
+            Arg::new("daily")
+                .long("daily")
+                .short('d')
+                .help("Fortune of the day, seeded from the date")
+                .conflicts_with("seed")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("user")
+                .long("user")
+                .short('u')
+                .value_name("USER")
+                .help("Mix the user into the daily seed")
+                .requires("daily")
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("date")
+                .long("date")
+                .value_name("YYYY-MM-DD")
+                .help("Date to use instead of today")
+                .value_parser(value_parser!(NaiveDate))
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("history")
+                .long("history")
+                .value_name("FILE")
+                .help("History file [default: ~/.fortuner_history with --no-repeat]")
+                .value_parser(value_parser!(PathBuf))
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("no_repeat")
+                .long("no-repeat")
+                .short('n')
+                .value_name("N")
+                .help("Avoid the last N fortunes in the history, except with --daily")
+                .value_parser(value_parser!(usize))
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Print fortunes as JSON")
+                .action(ArgAction::SetTrue)
+        )
         .get_matches();
     let sources = matches
         .get_many::<String>("sources")
@@ -99,14 +200,35 @@ pub fn get_args() -> MyResult<Config> {
             .map_err(|_| format!("Invalid --pattern \"{}\"", val))
         }) 
         .transpose()?;//The result of Option::map will be an Option<Result>, and Option::transpose will turn this into a Result<Option>.
-    let seed = matches.get_one::<u64>("seed").map(|b| *b);
+    let seed = matches.get_one::<u64>("seed").copied();
+    let no_repeat = matches.get_one::<usize>("no_repeat").copied().unwrap_or(0);
+    let history = match matches.get_one::<PathBuf>("history") {
+        Some(path) => Some(path.clone()),
+        None if no_repeat > 0 => Some(default_history()?),
+        None => None,
+    };
     Ok(Config {
         sources,
         pattern,
-        seed
+        seed,
+        daily: matches.get_flag("daily"),
+        user: matches.get_one::<String>("user").cloned(),
+        date: matches
+            .get_one::<NaiveDate>("date")
+            .copied()
+            .unwrap_or_else(|| Local::now().date_naive()),
+        history,
+        no_repeat,
+        json: matches.get_flag("json"),
     })
 }
 
+fn default_history() -> MyResult<PathBuf> {
+    env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(HISTORY_FILE))
+        .ok_or_else(|| From::from("--no-repeat: cannot locate home directory, use --history"))
+}
+
 fn find_files(paths: &[String]) -> MyResult<Vec<PathBuf>> {
     let dat = OsStr::new("dat");
     let mut res = vec![];
@@ -136,12 +258,14 @@ fn read_fortunes(paths: &[PathBuf]) -> MyResult<Vec<Fortune>> {
             .map_err(|e| {
                 format!("{}: {}", path.to_string_lossy().into_owned(), e)
             })?;
-        for line in BufReader::new(file).lines().filter_map(Result::ok) {
+        let mut index = 0;
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
             if line == "%" {
                 //read the lines into a buffer and dump the buffer when the record or file terminator is found
                 if !buf.is_empty() {
-                    res.push(Fortune {source: basename.clone(), text: buf.join("\n")});
+                    res.push(Fortune {source: basename.clone(), index, text: buf.join("\n")});
                     buf.clear();
+                    index += 1;
                 }
             } else {
                 buf.push(line.to_string());
@@ -151,32 +275,93 @@ fn read_fortunes(paths: &[PathBuf]) -> MyResult<Vec<Fortune>> {
     Ok(res)
 }
 
-fn pick_fortune(fortunes: &[Fortune], seed: Option<u64>) -> Option<String> {
-    // if fortunes.len() == 0 {
-    //     None
-    // } else {
-    //     let index = if let Some(seed) = seed {
-    //         rand::rngs::StdRng::seed_from_u64(seed).gen_range(0..fortunes.len())
-    //     } else {
-    //        rand::thread_rng().gen_range(0..fortunes.len())
-    //     };
-    //     Some(fortunes[index].text.to_string())
-    // }
+fn pick_fortune<'a>(
+    fortunes: &'a [Fortune],
+    seed: Option<u64>,
+    recent: &[HistoryEntry]
+) -> Option<&'a Fortune> {
+    // skip the recently shown fortunes unless that would leave nothing to pick
+    let mut candidates: Vec<&Fortune> = fortunes
+        .iter()
+        .filter(|f| !recent.iter().any(|h| h.is(f)))
+        .collect();
+    if candidates.is_empty() {
+        candidates = fortunes.iter().collect();
+    }
     if let Some(val) = seed { 
         let mut rng = StdRng::seed_from_u64(val);
-        fortunes.choose(&mut rng).map(|f| f.text.to_string())
+        candidates.choose(&mut rng).copied()
     } else {
         let mut rng = rand::thread_rng();
-        fortunes.choose(&mut rng).map(|f| f.text.to_string())
+        candidates.choose(&mut rng).copied()
+    }
+}
+
+// plain indexing rather than a seeded rng, whose sequence may change between
+// versions of rand
+fn pick_daily(fortunes: &[Fortune], seed: u64) -> Option<&Fortune> {
+    match fortunes.len() {
+        0 => None,
+        len => fortunes.get((seed % len as u64) as usize),
     }
 }
 
+// FNV-1a, so that the seed of a given day never changes between builds
+fn daily_seed(date: NaiveDate, user: Option<&str>) -> u64 {
+    let key = format!("{}:{}", user.unwrap_or_default(), date);
+    key.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn read_history(path: &Path) -> MyResult<Vec<HistoryEntry>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(From::from(format!("{}: {}", path.display(), e))),
+    };
+    // each line is "date<TAB>source<TAB>index", malformed lines are skipped
+    let entries = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            Some(HistoryEntry {
+                date: fields.next()?.parse().ok()?,
+                source: fields.next()?.to_string(),
+                index: fields.next()?.parse().ok()?
+            })
+        })
+        .collect();
+    Ok(entries)
+}
+
+fn append_history(path: &Path, entry: &HistoryEntry) -> MyResult<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    writeln!(file, "{}\t{}\t{}", entry.date, entry.source, entry.index)?;
+    Ok(())
+}
+
+fn fortune_to_json(fortune: &Fortune) -> String {
+    json!({
+        "source": fortune.source,
+        "index": fortune.index,
+        "text": fortune.text
+    }).to_string()
+}
+
 
 #[cfg(test)]
 mod tests {
     use super::{
-        find_files, read_fortunes, Fortune, pick_fortune,  
+        find_files, read_fortunes, Fortune, pick_fortune, pick_daily, daily_seed,
+        read_history, append_history, HistoryEntry,
     };
+    use chrono::NaiveDate;
     use std::path::PathBuf;
 
 
@@ -245,6 +430,7 @@ mod tests {
                 "Q: What do you call a deer wearing an eye patch?\n\
                 A: A bad idea (bad-eye deer)."
             );
+            assert_eq!(fortunes.last().unwrap().index, 5);
         }
 
         // Filters for matching text
@@ -262,25 +448,94 @@ mod tests {
         let fortunes = &[
             Fortune {
                 source: "fortunes".to_string(),
+                index: 0,
                 text: "You cannot achieve the impossible without \
                       attempting the absurd."
                     .to_string(),
             },
             Fortune {
                 source: "fortunes".to_string(),
+                index: 1,
                 text: "Assumption is the mother of all screw-ups."
                     .to_string(),
             },
             Fortune {
                 source: "fortunes".to_string(),
+                index: 2,
                 text: "Neckties strangle clear thinking.".to_string(),
             },
         ];
 
         // Pick a fortune with a seed
         assert_eq!(
-            pick_fortune(fortunes, Some(1)).unwrap(),
+            pick_fortune(fortunes, Some(1), &[]).unwrap().text,
             "Neckties strangle clear thinking.".to_string()
         );
+
+        // Recently shown fortunes are skipped
+        let today = NaiveDate::from_ymd_opt(2023, 9, 1).unwrap();
+        let recent = &[HistoryEntry {
+            date: today,
+            source: "fortunes".to_string(),
+            index: 2,
+        }];
+        for seed in 0..10 {
+            assert_ne!(pick_fortune(fortunes, Some(seed), recent).unwrap().index, 2);
+        }
+
+        // Unless nothing else is left
+        let recent: Vec<_> = (0..3)
+            .map(|index| HistoryEntry {
+                date: today,
+                source: "fortunes".to_string(),
+                index,
+            })
+            .collect();
+        assert!(pick_fortune(fortunes, Some(1), &recent).is_some());
+    }
+
+    #[test]
+    fn test_daily_seed() {
+        let day = NaiveDate::from_ymd_opt(2023, 9, 1).unwrap();
+        let next_day = day.succ_opt().unwrap();
+        assert_eq!(daily_seed(day, None), daily_seed(day, None));
+        assert_ne!(daily_seed(day, None), daily_seed(next_day, None));
+        assert_ne!(daily_seed(day, None), daily_seed(day, Some("ken")));
+        assert_eq!(daily_seed(day, Some("ken")), daily_seed(day, Some("ken")));
+        // FNV-1a of ":2023-09-01"
+        assert_eq!(daily_seed(day, None), 0x0d195f45469954b4);
+    }
+
+    #[test]
+    fn test_pick_daily() {
+        let fortunes: Vec<Fortune> = (0..3)
+            .map(|index| Fortune {
+                source: "fortunes".to_string(),
+                index,
+                text: format!("fortune {}", index),
+            })
+            .collect();
+        assert_eq!(pick_daily(&fortunes, 4).unwrap().index, 1);
+        assert_eq!(pick_daily(&fortunes, 0).unwrap().index, 0);
+        assert!(pick_daily(&[], 4).is_none());
+    }
+
+    #[test]
+    fn test_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history");
+
+        // A missing history file is empty
+        assert_eq!(read_history(&path).unwrap(), vec![]);
+
+        let entry = HistoryEntry {
+            date: NaiveDate::from_ymd_opt(2023, 9, 1).unwrap(),
+            source: "jokes".to_string(),
+            index: 4,
+        };
+        assert!(append_history(&path, &entry).is_ok());
+        assert!(append_history(&path, &entry).is_ok());
+        assert_eq!(read_history(&path).unwrap().len(), 2);
+        assert_eq!(read_history(&path).unwrap()[1], entry);
     }
 }
\ No newline at end of file
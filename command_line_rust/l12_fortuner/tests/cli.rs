@@ -2,6 +2,7 @@ use assert_cmd::Command;
 use predicates::prelude::*;
 use rand::{distributions::Alphanumeric, Rng};
 use std::fs;
+use tempfile::NamedTempFile;
 
 type TestResult = Result<(), Box<dyn std::error::Error>>;
 
@@ -173,3 +174,94 @@ fn mark_twain_lower_i() -> TestResult {
         "tests/expected/twain_lower_i.err",
     )
 }
+
+// --------------------------------------------------
+#[test]
+fn jokes_daily() -> TestResult {
+    run(
+        &[JOKES, "--daily", "--date", "2023-09-01"],
+        "Q. Why did the honeydew couple get married in a church?\n\
+        A. Their parents told them they cantaloupe.\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn jokes_daily_user() -> TestResult {
+    run(
+        &[JOKES, "-d", "--date", "2023-09-01", "-u", "ken"],
+        "Q: What happens when frogs park illegally?\nA: They get toad.\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_daily_with_seed() -> TestResult {
+    Command::cargo_bin(PRG)?
+        .args([JOKES, "--daily", "--seed", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn jokes_seed_1_json() -> TestResult {
+    run(
+        &[JOKES, "-s", "1", "--json"],
+        "{\"index\":4,\"source\":\"jokes\",\
+        \"text\":\"Q: What happens when frogs park illegally?\\nA: They get toad.\"}\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn jokes_no_repeat() -> TestResult {
+    let history = NamedTempFile::new()?;
+    let history_path = history.path().to_str().unwrap();
+    let mut seen = vec![];
+    // there are six jokes, so none may repeat within six draws
+    for _ in 0..6 {
+        let output = Command::cargo_bin(PRG)?
+            .args([JOKES, "--no-repeat", "5", "--history", history_path])
+            .output()?;
+        assert!(output.status.success());
+        let joke = String::from_utf8(output.stdout)?;
+        assert!(!seen.contains(&joke));
+        seen.push(joke);
+    }
+    assert_eq!(fs::read_to_string(history_path)?.lines().count(), 6);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn jokes_daily_history() -> TestResult {
+    let history = NamedTempFile::new()?;
+    let history_path = history.path().to_str().unwrap();
+    // the fortune of the day stays the same however often it is asked for
+    for _ in 0..3 {
+        run(
+            &[JOKES, "-d", "--date", "2023-09-01", "-n", "5", "--history", history_path],
+            "Q. Why did the honeydew couple get married in a church?\n\
+            A. Their parents told them they cantaloupe.\n",
+        )?;
+    }
+    assert_eq!(fs::read_to_string(history_path)?, "2023-09-01\tjokes\t2\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn jokes_daily_ignores_history() -> TestResult {
+    let history = NamedTempFile::new()?;
+    let history_path = history.path().to_str().unwrap();
+    // the day's fortune was shown yesterday, every machine still shows it today
+    fs::write(history_path, "2023-08-31\tjokes\t2\n")?;
+    run(
+        &[JOKES, "-d", "--date", "2023-09-01", "-n", "5", "--history", history_path],
+        "Q. Why did the honeydew couple get married in a church?\n\
+        A. Their parents told them they cantaloupe.\n",
+    )
+}
@@ -0,0 +1,157 @@
+use clap::{Command, Arg, ArgAction};
+use std::io::{self, BufWriter, ErrorKind, Write};
+
+#[derive(Debug, PartialEq)]
+struct Number {
+    value: f64,
+    precision: usize, //digits after the decimal point in the argument
+}
+
+fn main() {
+    let matches = Command::new("seqr")
+        .version("0.1.0")
+        .author("Zhang Tianwei <zhangtianwei1015@gmail.com>")
+        .about("Rust seq")
+        .override_usage("seqr [OPTIONS] [FIRST [INCREMENT]] LAST")
+        .allow_negative_numbers(true)
+        .arg(
+            Arg::new("numbers")
+                .value_name("NUMBER")
+                .help("FIRST (default 1), INCREMENT (default 1) and LAST")
+                .required(true)
+                .num_args(1..=3)
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("separator")
+                .short('s')
+                .long("separator")
+                .value_name("STRING")
+                .help("Use STRING to separate numbers")
+                .default_value("\n")
+                .hide_default_value(true)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("equal_width")
+                .short('w')
+                .long("equal-width")
+                .help("Equalize width by padding with leading zeros")
+                .action(ArgAction::SetTrue)
+        )
+        .get_matches();
+
+    if let Err(e) = run(
+        matches.get_many::<String>("numbers").unwrap().map(|s| s.as_str()).collect(),
+        matches.get_one::<String>("separator").unwrap(),
+        matches.get_flag("equal_width"),
+    ) {
+        if e.kind() != ErrorKind::BrokenPipe {
+            eprintln!("seqr: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(args: Vec<&str>, separator: &str, equal_width: bool) -> io::Result<()> {
+    let numbers = args
+        .iter()
+        .map(|arg| {
+            parse_number(arg).ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("invalid floating point argument: '{}'", arg),
+                )
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    let one = Number { value: 1.0, precision: 0 };
+    let (first, increment, last) = match numbers.as_slice() {
+        [last] => (&one, &one, last),
+        [first, last] => (first, &one, last),
+        [first, increment, last] => (first, increment, last),
+        _ => unreachable!(),
+    };
+    if increment.value == 0.0 {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid Zero increment value: '{}'", args[1]),
+        ));
+    }
+    // like seq, the output has as many decimals as FIRST or INCREMENT
+    let precision = first.precision.max(increment.precision);
+    let width = if equal_width {
+        format_number(first.value, precision, 0)
+            .len()
+            .max(format_number(last.value, precision, 0).len())
+    } else {
+        0
+    };
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    // the number of steps is worked out first, with some room for float errors,
+    // so that 0.1 0.1 0.3 ends at 0.3 even though 0.1 + 2 * 0.1 > 0.3
+    let steps = ((last.value - first.value) / increment.value + 1e-9).floor();
+    if steps < 0.0 {
+        return Ok(());
+    }
+    for i in 0..=steps as u64 {
+        // multiply instead of adding up the increment so float errors do not accumulate
+        let value = first.value + i as f64 * increment.value;
+        if i > 0 {
+            out.write_all(separator.as_bytes())?;
+        }
+        out.write_all(format_number(value, precision, width).as_bytes())?;
+    }
+    out.write_all(b"\n")?;
+    out.flush()
+}
+
+fn parse_number(arg: &str) -> Option<Number> {
+    let value = arg.parse::<f64>().ok().filter(|v| v.is_finite())?;
+    let precision = match arg.split_once('.') {
+        Some((_, decimals)) if !arg.contains(['e', 'E']) => decimals.len(),
+        _ => 0,
+    };
+    Some(Number { value, precision })
+}
+
+// pads with zeros after the sign up to width
+fn format_number(value: f64, precision: usize, width: usize) -> String {
+    let text = format!("{:.*}", precision, value);
+    // do not print "-0"
+    let text = match text.strip_prefix('-') {
+        Some(rest) if rest.chars().all(|c| c == '0' || c == '.') => rest.to_string(),
+        _ => text,
+    };
+    match text.strip_prefix('-') {
+        Some(digits) => format!("-{:0>w$}", digits, w = width.saturating_sub(1)),
+        None => format!("{:0>w$}", text, w = width),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_number, parse_number, Number};
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("3"), Some(Number { value: 3.0, precision: 0 }));
+        assert_eq!(parse_number("-0.25"), Some(Number { value: -0.25, precision: 2 }));
+        assert_eq!(parse_number("1e2"), Some(Number { value: 100.0, precision: 0 }));
+        assert_eq!(parse_number("abc"), None);
+        assert_eq!(parse_number("inf"), None);
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(7.0, 0, 0), "7");
+        assert_eq!(format_number(7.0, 0, 3), "007");
+        assert_eq!(format_number(-7.0, 0, 3), "-07");
+        assert_eq!(format_number(0.1 + 0.2, 1, 0), "0.3");
+        assert_eq!(format_number(-0.0001, 1, 0), "0.0");
+    }
+}
+
+// cargo run -q --bin seqr -- -w -s , 1 0.5 3
@@ -0,0 +1,67 @@
+use clap::{Command, Arg, ArgAction};
+use std::{thread, time::Duration};
+
+fn main() {
+    let matches = Command::new("sleepr")
+        .version("0.1.0")
+        .author("Zhang Tianwei <zhangtianwei1015@gmail.com>")
+        .about("Rust sleep")
+        .arg(
+            Arg::new("duration")
+                .value_name("NUMBER[SUFFIX]")
+                .help("Time to sleep, the suffix is s (seconds, default), m, h or d; several are added up")
+                .required(true)
+                .action(ArgAction::Append)
+        )
+        .get_matches();
+
+    let mut total = Duration::ZERO;
+    for arg in matches.get_many::<String>("duration").unwrap() {
+        match parse_duration(arg) {
+            Some(d) => total += d,
+            None => {
+                eprintln!("sleepr: invalid time interval '{}'", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+    thread::sleep(total);
+}
+
+fn parse_duration(arg: &str) -> Option<Duration> {
+    let (number, multiplier) = match arg.chars().last()? {
+        's' => (&arg[..arg.len() - 1], 1.0),
+        'm' => (&arg[..arg.len() - 1], 60.0),
+        'h' => (&arg[..arg.len() - 1], 60.0 * 60.0),
+        'd' => (&arg[..arg.len() - 1], 24.0 * 60.0 * 60.0),
+        _ => (arg, 1.0),
+    };
+    // reject what f64 accepts but sleep does not, like "inf", "nan" or "-1"
+    if !number.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let seconds = number.parse::<f64>().ok()? * multiplier;
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_duration;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("2"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_duration("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2m"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_duration(".5h"), Some(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("1d"), Some(Duration::from_secs(86400)));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("s"), None);
+        assert_eq!(parse_duration("-1"), None);
+        assert_eq!(parse_duration("1x"), None);
+        assert_eq!(parse_duration("infs"), None);
+    }
+}
+
+// cargo run -q --bin sleepr -- 1.5 0.5s
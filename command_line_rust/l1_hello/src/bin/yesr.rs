@@ -0,0 +1,43 @@
+use clap::{Command, Arg, ArgAction};
+use std::io::{self, BufWriter, ErrorKind, Write};
+
+// fill the writer with many copies of the line at once instead of one line per write
+const BUF_SIZE: usize = 64 * 1024;
+
+fn main() {
+    let matches = Command::new("yesr")
+        .version("0.1.0")
+        .author("Zhang Tianwei <zhangtianwei1015@gmail.com>")
+        .about("Rust yes")
+        .arg(
+            Arg::new("string")
+                .value_name("STRING")
+                .help("Text to repeat [default: y]")
+                .allow_hyphen_values(true)
+                .action(ArgAction::Append)
+        )
+        .get_matches();
+
+    let text = matches
+        .get_many::<String>("string")
+        .map(|v| v.map(|s| s.as_str()).collect::<Vec<_>>().join(" "))
+        .unwrap_or_else(|| "y".to_string());
+    let line = format!("{}\n", text);
+    let copies = (BUF_SIZE / line.len()).max(1);
+    let chunk = line.repeat(copies);
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::with_capacity(BUF_SIZE, stdout.lock());
+    loop {
+        if let Err(e) = out.write_all(chunk.as_bytes()) {
+            // the reader went away (e.g. `yesr | head`), which is how yes normally ends
+            if e.kind() == ErrorKind::BrokenPipe {
+                std::process::exit(0);
+            }
+            eprintln!("yesr: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// cargo run -q --bin yesr -- hello | head -n 3
@@ -0,0 +1,159 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::{
+    io::Read,
+    process::Stdio,
+    time::{Duration, Instant},
+};
+
+type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+// --------------------------------------------------
+fn run(prg: &str, args: &[&str], expected: &str) -> TestResult {
+    Command::cargo_bin(prg)?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected.to_string());
+    Ok(())
+}
+
+// --------------------------------------------------
+fn dies(prg: &str, args: &[&str], expected: &str) -> TestResult {
+    Command::cargo_bin(prg)?
+        .args(args)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+    Ok(())
+}
+
+// --------------------------------------------------
+// yesr never stops by itself, so read the start of its output and kill it
+fn yesr_head(args: &[&str], len: usize) -> Result<String, Box<dyn std::error::Error>> {
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("yesr"))
+        .args(args)
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut buf = vec![0; len];
+    child.stdout.take().unwrap().read_exact(&mut buf)?;
+    child.kill()?;
+    child.wait()?;
+    Ok(String::from_utf8(buf)?)
+}
+
+// --------------------------------------------------
+#[test]
+fn yesr_default() -> TestResult {
+    assert_eq!(yesr_head(&[], 6)?, "y\ny\ny\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn yesr_args() -> TestResult {
+    assert_eq!(yesr_head(&["hello", "-n"], 18)?, "hello -n\nhello -n\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn yesr_throughput() -> TestResult {
+    // a few MB must come through without line by line writes
+    let out = yesr_head(&["abc"], 4 * 1024 * 1024)?;
+    assert!(out.lines().all(|line| line == "abc"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_last() -> TestResult {
+    run("seqr", &["3"], "1\n2\n3\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_first_last() -> TestResult {
+    run("seqr", &["-2", "1"], "-2\n-1\n0\n1\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_decreasing() -> TestResult {
+    run("seqr", &["5", "-2", "-3"], "5\n3\n1\n-1\n-3\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_empty() -> TestResult {
+    run("seqr", &["3", "1"], "")
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_float_step() -> TestResult {
+    run("seqr", &["0.1", "0.1", "0.5"], "0.1\n0.2\n0.3\n0.4\n0.5\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_float_step_last() -> TestResult {
+    run("seqr", &["0.1", "0.1", "0.3"], "0.1\n0.2\n0.3\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_separator() -> TestResult {
+    run("seqr", &["-s", ", ", "1", "4"], "1, 2, 3, 4\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_equal_width() -> TestResult {
+    run("seqr", &["-w", "8", "10"], "08\n09\n10\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_equal_width_float() -> TestResult {
+    run("seqr", &["-w", "-s", ",", "-1", "0.5", "1"], "-1.0,-0.5,00.0,00.5,01.0\n")
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_dies_zero_increment() -> TestResult {
+    dies("seqr", &["1", "0", "3"], "invalid Zero increment value: '0'")
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_dies_bad_number() -> TestResult {
+    dies("seqr", &["1", "x"], "invalid floating point argument: 'x'")
+}
+
+// --------------------------------------------------
+#[test]
+fn seqr_dies_no_args() -> TestResult {
+    dies("seqr", &[], "Usage")
+}
+
+// --------------------------------------------------
+#[test]
+fn sleepr_sleeps() -> TestResult {
+    let start = Instant::now();
+    run("sleepr", &["0.1", "0.1s"], "")?;
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn sleepr_dies_bad_interval() -> TestResult {
+    dies("sleepr", &["1x"], "invalid time interval '1x'")
+}
+
+// --------------------------------------------------
+#[test]
+fn sleepr_dies_no_args() -> TestResult {
+    dies("sleepr", &[], "Usage")
+}
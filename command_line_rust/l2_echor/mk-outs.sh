@@ -6,4 +6,11 @@ OUTDIR="tests/expected"
 echo "Hello there" > $OUTDIR/hello1.txt
 echo "Hello"  "there" > $OUTDIR/hello2.txt
 echo -n "Hello  there" > $OUTDIR/hello1.n.txt
-echo -n "Hello"  "there" > $OUTDIR/hello2.n.txt
\ No newline at end of file
+echo -n "Hello"  "there" > $OUTDIR/hello2.n.txt
+echo -e "Hello\tthere\n\x41\0102" > $OUTDIR/hello.e.txt
+echo -ne "Hello\cthere" > $OUTDIR/hello.ec.txt
+echo -E "Hello\tthere" > $OUTDIR/hello.E.txt
+
+printf "%s=%d\n" a 1 b 2 c > $OUTDIR/printf.recycle.txt
+printf "[%5.2f][%-6s][%x][%#o][%05d]\n" 3.14159 hi 255 8 -42 > $OUTDIR/printf.numbers.txt
+printf "%b|%s\n" 'a\tb' 'a\tb' > $OUTDIR/printf.b.txt
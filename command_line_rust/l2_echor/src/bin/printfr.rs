@@ -0,0 +1,47 @@
+use clap::{Command, Arg, ArgAction};
+use std::io::{self, ErrorKind, Write};
+
+fn main() {
+    let matches = Command::new("printfr")
+        .version("0.1.0")
+        .author("Zhang Tianwei <zhangtianwei1015@gmail.com>")
+        .about("Rust printf")
+        .arg(
+            Arg::new("format")
+                .value_name("FORMAT")
+                .help("Format string")
+                .required(true)
+        )
+        .arg(
+            Arg::new("arguments")
+                .value_name("ARGUMENT")
+                .help("Arguments for the format, the format is reused until all are consumed")
+                .allow_hyphen_values(true)
+                .action(ArgAction::Append)
+        )
+        .get_matches();
+
+    let format = matches.get_one::<String>("format").unwrap();
+    let args = matches
+                                .get_many::<String>("arguments")
+                                .unwrap_or_default()
+                                .map(|v| v.into())
+                                .collect::<Vec<String>>();
+    let (out, errors) = l2_echor::printf(format, &args);
+    let mut stdout = io::stdout();
+    if let Err(e) = stdout.write_all(&out).and_then(|_| stdout.flush()) {
+        if e.kind() != ErrorKind::BrokenPipe {
+            eprintln!("printfr: {}", e);
+            std::process::exit(1);
+        }
+    }
+    //like printf, report bad arguments but still print everything else
+    for e in &errors {
+        eprintln!("printfr: {}", e);
+    }
+    if !errors.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+// cargo run --bin printfr -- '%s=%d\n' a 1 b 2
@@ -0,0 +1,536 @@
+use std::{iter::Peekable, num::IntErrorKind, str::Chars};
+
+/// Interprets the backslash escapes understood by `echo -e`.
+/// The flag is true when `\c` was found, meaning that no further output
+/// (not even the trailing newline) should be produced.
+pub fn interpret_escapes(text: &str) -> (Vec<u8>, bool) {
+    let mut out = vec![];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if escape(&mut chars, &mut out, true) {
+                return (out, true);
+            }
+        } else {
+            push_char(&mut out, c);
+        }
+    }
+    (out, false)
+}
+
+/// Formats `args` according to the POSIX `printf` format string.
+/// The format is reused as long as it consumes arguments (argument recycling),
+/// missing arguments are treated as empty strings or zero.
+/// Returns the output together with the diagnostics for malformed arguments.
+pub fn printf(format: &str, args: &[String]) -> (Vec<u8>, Vec<String>) {
+    let mut printer = Printer {
+        out: vec![],
+        errors: vec![],
+        args,
+        next_arg: 0,
+    };
+    loop {
+        let start = printer.next_arg;
+        if printer.format_once(format) {
+            break;
+        }
+        // stop when all the args are used or the format does not consume any
+        if printer.next_arg >= args.len() || printer.next_arg == start {
+            break;
+        }
+    }
+    (printer.out, printer.errors)
+}
+
+struct Printer<'a> {
+    out: Vec<u8>,
+    errors: Vec<String>,
+    args: &'a [String],
+    next_arg: usize,
+}
+
+#[derive(Debug, Default)]
+struct Spec {
+    left: bool,  // '-'
+    plus: bool,  // '+'
+    space: bool, // ' '
+    alt: bool,   // '#'
+    zero: bool,  // '0'
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl<'a> Printer<'a> {
+    // runs through the format a single time, returns true if the output must stop
+    fn format_once(&mut self, format: &str) -> bool {
+        let mut chars = format.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    if escape(&mut chars, &mut self.out, false) {
+                        return true;
+                    }
+                }
+                '%' => {
+                    if self.conversion(&mut chars) {
+                        return true;
+                    }
+                }
+                _ => push_char(&mut self.out, c),
+            }
+        }
+        false
+    }
+
+    fn next_arg(&mut self) -> Option<&'a str> {
+        let arg = self.args.get(self.next_arg).map(|s| s.as_str());
+        self.next_arg += 1;
+        arg
+    }
+
+    // handles one '%' directive, returns true if the output must stop
+    fn conversion(&mut self, chars: &mut Peekable<Chars>) -> bool {
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            self.out.push(b'%');
+            return false;
+        }
+        let mut spec = Spec::default();
+        while let Some(&c) = chars.peek() {
+            match c {
+                '-' => spec.left = true,
+                '+' => spec.plus = true,
+                ' ' => spec.space = true,
+                '#' => spec.alt = true,
+                '0' => spec.zero = true,
+                _ => break,
+            }
+            chars.next();
+        }
+        spec.width = match self.number_or_star(chars) {
+            Some(w) if w < 0 => {
+                spec.left = true;
+                w.unsigned_abs() as usize
+            }
+            Some(w) => w as usize,
+            None => 0,
+        };
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            spec.precision = Some(self.number_or_star(chars).unwrap_or(0).max(0) as usize);
+        }
+        let conversion = match chars.next() {
+            Some(c) => c,
+            None => {
+                self.errors.push("%: missing conversion specifier".to_string());
+                return true;
+            }
+        };
+        match conversion {
+            's' => {
+                let arg = self.next_arg().unwrap_or("");
+                let text: String = match spec.precision {
+                    Some(p) => arg.chars().take(p).collect(),
+                    None => arg.to_string(),
+                };
+                self.pad(text.into_bytes(), &spec);
+            }
+            'b' => {
+                let (mut bytes, stop) = interpret_escapes(self.next_arg().unwrap_or(""));
+                if let Some(p) = spec.precision {
+                    bytes.truncate(p);
+                }
+                self.pad(bytes, &spec);
+                return stop;
+            }
+            'c' => {
+                let text: String = self.next_arg().unwrap_or("").chars().take(1).collect();
+                self.pad(text.into_bytes(), &spec);
+            }
+            'd' | 'i' => {
+                let n = self.int_arg();
+                let sign = sign(n < 0, &spec);
+                let digits = int_digits(n.unsigned_abs().to_string(), spec.precision);
+                self.pad_number(sign, digits, &spec);
+            }
+            'o' | 'u' | 'x' | 'X' => {
+                // negative values wrap around like in C
+                let n = self.int_arg() as u64;
+                let (prefix, digits) = match conversion {
+                    'o' => (if spec.alt && n != 0 { "0" } else { "" }, format!("{:o}", n)),
+                    'x' => (if spec.alt && n != 0 { "0x" } else { "" }, format!("{:x}", n)),
+                    'X' => (if spec.alt && n != 0 { "0X" } else { "" }, format!("{:X}", n)),
+                    _ => ("", n.to_string()),
+                };
+                let digits = int_digits(digits, spec.precision);
+                self.pad_number(prefix.to_string(), digits, &spec);
+            }
+            'f' | 'F' | 'e' | 'E' | 'g' | 'G' => {
+                let x = self.float_arg();
+                let sign = sign(x.is_sign_negative() && x != 0.0, &spec);
+                let digits = float_digits(x.abs(), conversion, spec.precision, spec.alt);
+                if x.is_finite() {
+                    self.pad_number(sign, digits, &spec);
+                } else {
+                    // no zero padding for inf and nan
+                    self.pad(format!("{}{}", sign, digits).into_bytes(), &spec);
+                }
+            }
+            _ => {
+                self.errors.push(format!("%{}: invalid conversion specification", conversion));
+                return true;
+            }
+        }
+        false
+    }
+
+    // reads a width or precision, '*' takes it from the next argument
+    fn number_or_star(&mut self, chars: &mut Peekable<Chars>) -> Option<i64> {
+        if chars.peek() == Some(&'*') {
+            chars.next();
+            return Some(self.int_arg());
+        }
+        let mut digits = String::new();
+        while let Some(&c) = chars.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            digits.push(c);
+            chars.next();
+        }
+        digits.parse().ok()
+    }
+
+    fn int_arg(&mut self) -> i64 {
+        let arg = match self.next_arg() {
+            Some(arg) => arg,
+            None => return 0,
+        };
+        match parse_int(arg) {
+            Ok(n) => n,
+            //the closest value is used, as in coreutils
+            Err(IntError::OutOfRange(n)) => {
+                self.errors.push(format!("'{}': Numerical result out of range", arg));
+                n
+            }
+            Err(IntError::Invalid) => {
+                self.errors.push(format!("'{}': expected a numeric value", arg));
+                0
+            }
+        }
+    }
+
+    fn float_arg(&mut self) -> f64 {
+        let arg = match self.next_arg() {
+            Some(arg) => arg,
+            None => return 0.0,
+        };
+        match char_value(arg)
+            .map(|n| n as f64)
+            .or_else(|| arg.trim().parse().ok())
+            .or_else(|| parse_int(arg).ok().map(|n| n as f64))
+        {
+            Some(x) => x,
+            None => {
+                self.errors.push(format!("'{}': expected a numeric value", arg));
+                0.0
+            }
+        }
+    }
+
+    // the width counts bytes, as in coreutils, not characters
+    fn pad(&mut self, body: Vec<u8>, spec: &Spec) {
+        let fill = spec.width.saturating_sub(body.len());
+        if spec.left {
+            self.out.extend(body);
+            self.out.extend(std::iter::repeat_n(b' ', fill));
+        } else {
+            self.out.extend(std::iter::repeat_n(b' ', fill));
+            self.out.extend(body);
+        }
+    }
+
+    // zero padding goes between the sign or prefix and the digits
+    fn pad_number(&mut self, prefix: String, digits: String, spec: &Spec) {
+        let len = prefix.len() + digits.len();
+        if spec.zero && !spec.left && spec.width > len {
+            self.out.extend(prefix.bytes());
+            self.out.extend(std::iter::repeat_n(b'0', spec.width - len));
+            self.out.extend(digits.bytes());
+        } else {
+            self.pad(format!("{}{}", prefix, digits).into_bytes(), spec);
+        }
+    }
+}
+
+// handles the character(s) after a backslash, returns true for "\c"
+// `zero_octal` selects the echo/%b form "\0NNN" over the printf form "\NNN"
+fn escape(chars: &mut Peekable<Chars>, out: &mut Vec<u8>, zero_octal: bool) -> bool {
+    let c = match chars.next() {
+        Some(c) => c,
+        None => {
+            out.push(b'\\');
+            return false;
+        }
+    };
+    match c {
+        '\\' => out.push(b'\\'),
+        'a' => out.push(0x07),
+        'b' => out.push(0x08),
+        'c' => return true,
+        'e' => out.push(0x1b),
+        'f' => out.push(0x0c),
+        'n' => out.push(b'\n'),
+        'r' => out.push(b'\r'),
+        't' => out.push(b'\t'),
+        'v' => out.push(0x0b),
+        '"' if !zero_octal => out.push(b'"'),
+        '\'' if !zero_octal => out.push(b'\''),
+        'x' => match take_digits(chars, 16, 2) {
+            Some(n) => out.push(n as u8),
+            None => out.extend(b"\\x"),
+        },
+        '0' if zero_octal => out.push(take_digits(chars, 8, 3).unwrap_or(0) as u8),
+        '0'..='7' if !zero_octal => {
+            let mut n = c.to_digit(8).unwrap();
+            for _ in 0..2 {
+                match chars.peek().and_then(|d| d.to_digit(8)) {
+                    Some(d) => {
+                        n = n * 8 + d;
+                        chars.next();
+                    }
+                    None => break,
+                }
+            }
+            out.push(n as u8);
+        }
+        _ => {
+            // unknown escapes are printed as is
+            out.push(b'\\');
+            push_char(out, c);
+        }
+    }
+    false
+}
+
+// reads up to `max` digits in the given radix
+fn take_digits(chars: &mut Peekable<Chars>, radix: u32, max: usize) -> Option<u32> {
+    let mut value = None;
+    for _ in 0..max {
+        match chars.peek().and_then(|d| d.to_digit(radix)) {
+            Some(d) => {
+                value = Some(value.unwrap_or(0) * radix + d);
+                chars.next();
+            }
+            None => break,
+        }
+    }
+    value
+}
+
+fn push_char(out: &mut Vec<u8>, c: char) {
+    let mut buf = [0; 4];
+    out.extend(c.encode_utf8(&mut buf).bytes());
+}
+
+enum IntError {
+    Invalid,
+    OutOfRange(i64), //the closest i64
+}
+
+// parses decimal, 0x hex, 0 octal and 'c (character code) arguments
+fn parse_int(arg: &str) -> Result<i64, IntError> {
+    if let Some(n) = char_value(arg) {
+        return Ok(n);
+    }
+    let arg = arg.trim();
+    let (negative, digits) = match arg.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, arg.strip_prefix('+').unwrap_or(arg)),
+    };
+    let (digits, radix) = if let Some(hex) = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")) {
+        (hex, 16)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        (&digits[1..], 8)
+    } else {
+        (digits, 10)
+    };
+    let out_of_range = IntError::OutOfRange(if negative { i64::MIN } else { i64::MAX });
+    let n = match u64::from_str_radix(digits, radix) {
+        Ok(n) => n,
+        Err(e) if *e.kind() == IntErrorKind::PosOverflow => return Err(out_of_range),
+        Err(_) => return Err(IntError::Invalid),
+    };
+    let n = if negative { 0i64.checked_sub_unsigned(n) } else { i64::try_from(n).ok() };
+    n.ok_or(out_of_range)
+}
+
+fn char_value(arg: &str) -> Option<i64> {
+    let rest = arg.strip_prefix('\'').or_else(|| arg.strip_prefix('"'))?;
+    rest.chars().next().map(|c| c as i64)
+}
+
+fn sign(negative: bool, spec: &Spec) -> String {
+    if negative {
+        "-"
+    } else if spec.plus {
+        "+"
+    } else if spec.space {
+        " "
+    } else {
+        ""
+    }
+    .to_string()
+}
+
+// the precision of an integer conversion is its minimum number of digits
+fn int_digits(digits: String, precision: Option<usize>) -> String {
+    match precision {
+        Some(0) if digits == "0" => String::new(),
+        Some(p) if p > digits.len() => format!("{}{}", "0".repeat(p - digits.len()), digits),
+        _ => digits,
+    }
+}
+
+fn float_digits(x: f64, conversion: char, precision: Option<usize>, alt: bool) -> String {
+    let upper = conversion.is_ascii_uppercase();
+    let digits = if x.is_nan() {
+        "nan".to_string()
+    } else if x.is_infinite() {
+        "inf".to_string()
+    } else {
+        let precision = precision.unwrap_or(6);
+        match conversion.to_ascii_lowercase() {
+            'f' => format!("{:.*}", precision, x),
+            'e' => exponential(x, precision),
+            _ => general(x, precision, alt),
+        }
+    };
+    if upper {
+        digits.to_uppercase()
+    } else {
+        digits
+    }
+}
+
+// C style "d.ddde+dd"
+fn exponential(x: f64, precision: usize) -> String {
+    let formatted = format!("{:.*e}", precision, x);
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{}e{}{:02}", mantissa, sign, exponent.abs())
+}
+
+// %g picks %e or %f depending on the exponent and drops trailing zeros
+fn general(x: f64, precision: usize, alt: bool) -> String {
+    let precision = precision.max(1);
+    let exponent: i32 = if x == 0.0 {
+        0
+    } else {
+        let formatted = format!("{:.*e}", precision - 1, x);
+        formatted.split_once('e').unwrap().1.parse().unwrap()
+    };
+    let formatted = if exponent < -4 || exponent >= precision as i32 {
+        exponential(x, precision - 1)
+    } else {
+        format!("{:.*}", (precision as i32 - 1 - exponent) as usize, x)
+    };
+    if alt {
+        return formatted;
+    }
+    match formatted.split_once('e') {
+        Some((mantissa, exponent)) => format!("{}e{}", trim_zeros(mantissa), exponent),
+        None => trim_zeros(&formatted).to_string(),
+    }
+}
+
+fn trim_zeros(digits: &str) -> &str {
+    if digits.contains('.') {
+        digits.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        digits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interpret_escapes, printf};
+
+    fn run(format: &str, args: &[&str]) -> String {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let (out, errors) = printf(format, &args);
+        assert!(errors.is_empty(), "{:?}", errors);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_interpret_escapes() {
+        assert_eq!(interpret_escapes("a\\tb\\n"), (b"a\tb\n".to_vec(), false));
+        assert_eq!(interpret_escapes("\\x41\\x4a"), (b"AJ".to_vec(), false));
+        assert_eq!(interpret_escapes("\\0101\\0"), (b"A\0".to_vec(), false));
+        assert_eq!(interpret_escapes("\\\\ \\q"), (b"\\ \\q".to_vec(), false));
+        assert_eq!(interpret_escapes("ab\\ccd"), (b"ab".to_vec(), true));
+        assert_eq!(interpret_escapes("\\xg"), (b"\\xg".to_vec(), false));
+        assert_eq!(interpret_escapes("trailing\\"), (b"trailing\\".to_vec(), false));
+    }
+
+    #[test]
+    fn test_printf_strings() {
+        assert_eq!(run("%s-%s\\n", &["a", "b"]), "a-b\n");
+        assert_eq!(run("[%5s][%-5s][%.2s]", &["ab", "ab", "abc"]), "[   ab][ab   ][ab]");
+        assert_eq!(run("%b|%s", &["a\\tb", "a\\tb"]), "a\tb|a\\tb");
+        assert_eq!(run("%c%c", &["xyz", "é"]), "xé");
+        assert_eq!(run("[%5s][%-4s]", &["é", "日"]), "[   é][日 ]");
+        assert_eq!(run("%%\\101\\n", &[]), "%A\n");
+        assert_eq!(run("%b after", &["stop\\c"]), "stop");
+    }
+
+    #[test]
+    fn test_printf_integers() {
+        assert_eq!(run("%d %i %d", &["42", "-7", "0x1f"]), "42 -7 31");
+        assert_eq!(run("%05d|%-4d|%+d|% d", &["-42", "3", "5", "5"]), "-0042|3   |+5| 5");
+        assert_eq!(run("%.3d", &["7"]), "007");
+        assert_eq!(run("%x %X %#x %o %#o", &["255", "255", "255", "8", "8"]), "ff FF 0xff 10 010");
+        assert_eq!(run("%u", &["-1"]), "18446744073709551615");
+        assert_eq!(run("%d", &["'A"]), "65");
+        assert_eq!(run("%*d|%-*d", &["4", "1", "3", "2"]), "   1|2  ");
+    }
+
+    #[test]
+    fn test_printf_floats() {
+        assert_eq!(run("%5.2f", &["3.14159"]), " 3.14");
+        assert_eq!(run("%f", &["1"]), "1.000000");
+        assert_eq!(run("%08.3f", &["-3.5"]), "-003.500");
+        assert_eq!(run("%e", &["12345.678"]), "1.234568e+04");
+        assert_eq!(run("%.2E", &["0.000123"]), "1.23E-04");
+        assert_eq!(run("%g %g %g", &["100000", "1000000", "0.0001"]), "100000 1e+06 0.0001");
+        assert_eq!(run("%g %G", &["0.00001234", "2.5"]), "1.234e-05 2.5");
+    }
+
+    #[test]
+    fn test_printf_recycling() {
+        assert_eq!(run("%s=%d\\n", &["a", "1", "b", "2", "c"]), "a=1\nb=2\nc=0\n");
+        assert_eq!(run("no args\\n", &["x", "y"]), "no args\n");
+        assert_eq!(run("%s\\n", &[]), "\n");
+    }
+
+    #[test]
+    fn test_printf_errors() {
+        let (out, errors) = printf("%d|%d", &["x".to_string(), "3".to_string()]);
+        assert_eq!(out, b"0|3");
+        assert_eq!(errors, vec!["'x': expected a numeric value".to_string()]);
+
+        let big = ["99999999999999999999", "-0x8000000000000001", "9223372036854775808"].map(String::from);
+        let (out, errors) = printf("%d|%d|%d", &big);
+        assert_eq!(out, b"9223372036854775807|-9223372036854775808|9223372036854775807");
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0], "'99999999999999999999': Numerical result out of range");
+        let (out, errors) = printf("%d", &["-9223372036854775808".to_string()]);
+        assert_eq!((out, errors.len()), (b"-9223372036854775808".to_vec(), 0));
+
+        let (out, errors) = printf("a%zb", &[]);
+        assert_eq!(out, b"a");
+        assert_eq!(errors.len(), 1);
+    }
+}
@@ -1,4 +1,5 @@
 use clap::{Command, Arg, ArgAction};
+use std::io::{self, ErrorKind, Write};
 
 fn main() {
     let matches = Command::new("echor")
@@ -18,6 +19,20 @@ fn main() {
                 .help("Do not print newline, i.e. omit the trailing newline")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("escapes")
+                .short('e')
+                .help("Enable interpretation of backslash escapes")
+                .overrides_with("no_escapes")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("no_escapes")
+                .short('E')
+                .help("Disable interpretation of backslash escapes (default)")
+                .overrides_with("escapes")
+                .action(ArgAction::SetTrue),
+        )
         .get_matches();
 
     let text = matches
@@ -28,13 +43,29 @@ fn main() {
     let omit_newline = matches.get_flag("omit_newline");
     let ending = if omit_newline {""} else {"\n"};
     //print all the positional cmd args joined on spaces
-    print!("{}{}", text.join(" "), ending);
+    if matches.get_flag("escapes") {
+        //"\c" suppresses all further output, including the newline
+        let (mut bytes, stop) = l2_echor::interpret_escapes(&text.join(" "));
+        if !stop {
+            bytes.extend(ending.as_bytes());
+        }
+        let mut stdout = io::stdout();
+        if let Err(e) = stdout.write_all(&bytes).and_then(|_| stdout.flush()) {
+            if e.kind() != ErrorKind::BrokenPipe {
+                eprintln!("echor: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        print!("{}{}", text.join(" "), ending);
+    }
 
     
     // println!("{:#?}", matches);
 }
 
 // cargo run -- -n Hello world
+// cargo run -- -e 'Hello\tworld'
 // cargo run -- -h
 // cargo run -- -V
 // cargo run 1>out 2>err
\ No newline at end of file
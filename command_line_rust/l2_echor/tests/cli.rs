@@ -42,3 +42,92 @@ fn hello3() -> TestResult {
 fn hello4() -> TestResult {
     runs(&["-n", "Hello", "there"], "tests/expected/hello2.n.txt")
 }
+
+#[test]
+fn hello_escapes() -> TestResult {
+    runs(&["-e", "Hello\\tthere\\n\\x41\\0102"], "tests/expected/hello.e.txt")
+}
+
+#[test]
+fn hello_escapes_stop() -> TestResult {
+    runs(&["-ne", "Hello\\cthere"], "tests/expected/hello.ec.txt")
+}
+
+#[test]
+fn hello_no_escapes() -> TestResult {
+    runs(&["-e", "-E", "Hello\\tthere"], "tests/expected/hello.E.txt")
+}
+
+fn runs_printfr(args: &[&str], expected_file: &str) -> TestResult {
+    let expected = fs::read_to_string(expected_file)?;
+    Command::cargo_bin("printfr")?
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+#[test]
+fn printfr_dies_no_args() -> TestResult {
+    Command::cargo_bin("printfr")?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Usage"));
+    Ok(())
+}
+
+#[test]
+fn printfr_recycle() -> TestResult {
+    runs_printfr(&["%s=%d\\n", "a", "1", "b", "2", "c"], "tests/expected/printf.recycle.txt")
+}
+
+#[test]
+fn printfr_numbers() -> TestResult {
+    runs_printfr(
+        &["[%5.2f][%-6s][%x][%#o][%05d]\\n", "3.14159", "hi", "255", "8", "-42"],
+        "tests/expected/printf.numbers.txt",
+    )
+}
+
+#[test]
+fn printfr_b() -> TestResult {
+    runs_printfr(&["%b|%s\\n", "a\\tb", "a\\tb"], "tests/expected/printf.b.txt")
+}
+
+#[test]
+fn printfr_bad_number() -> TestResult {
+    Command::cargo_bin("printfr")?
+        .args(["%d,%d\\n", "x", "2"])
+        .assert()
+        .failure()
+        .stdout("0,2\n")
+        .stderr("printfr: 'x': expected a numeric value\n");
+    Ok(())
+}
+
+#[test]
+fn printfr_out_of_range() -> TestResult {
+    Command::cargo_bin("printfr")?
+        .args(["%d,%d\\n", "99999999999999999999", "2"])
+        .assert()
+        .failure()
+        .stdout("9223372036854775807,2\n")
+        .stderr("printfr: '99999999999999999999': Numerical result out of range\n");
+    Ok(())
+}
+
+#[test]
+fn printfr_closed_stdout() -> TestResult {
+    //more than a pipe holds, so the write fails once the reader is gone
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_printfr"))
+        .args(["%0100000d", "1"])
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()?;
+    drop(child.stdout.take());
+    let out = child.wait_with_output()?;
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stderr)?, "");
+    Ok(())
+}
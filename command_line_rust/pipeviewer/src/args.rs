@@ -1,10 +1,21 @@
-use clap::{Arg, ArgAction, Command};
-use std::env;
+use crate::hash::Algorithm;
+use clap::{Arg, ArgAction, ArgGroup, Command};
+use std::{env, fs};
 
 pub struct Args {
-    pub infile: String,
+    pub infiles: Vec<String>,         //read in order, "-" is stdin
+    pub file_sizes: Vec<Option<u64>>, //size of each input, if known
     pub outfile: String,
+    pub tees: Vec<String>,      //more files to write the stream to
+    pub hashes: Vec<Algorithm>, //digests of the stream to print at the end
     pub silent: bool,
+    pub size: Option<u64>, //total number of bytes (or lines) expected, if known
+    pub rate_limit: Option<u64>, //bytes per second
+    pub line_mode: bool,
+    pub name: Option<String>,
+    pub format: Option<String>,
+    pub listen: bool,        //accept changes from `-R`
+    pub remote: Option<u32>, //PID of the pipeviewer to change
 }
 
 impl Args {
@@ -14,10 +25,10 @@ impl Args {
             .author("Zhang Tianwei <zhangtianwei1015@gmail.com>")
             .about("Rust pipe viewer")
             .arg(
-                Arg::new("infile")
+                Arg::new("infiles")
                     .value_name("INFILE")
-                    .help("Read from a file instead of stdin")
-                    .action(ArgAction::Set),
+                    .help("Read from files, one after the other, instead of stdin")
+                    .action(ArgAction::Append),
             )
             .arg(
                 Arg::new("outfile")
@@ -27,6 +38,22 @@ impl Args {
                     .help("Write output to a file instead of stdout")
                     .action(ArgAction::Set),
             )
+            .arg(
+                Arg::new("tee")
+                    .value_name("FILE")
+                    .long("tee")
+                    .help("Also write the output to FILE, may be repeated")
+                    .action(ArgAction::Append),
+            )
+            .arg(
+                Arg::new("hash")
+                    .value_name("ALGORITHMS")
+                    .long("hash")
+                    .help("Print digests of the stream at the end: md5, sha256, sha512")
+                    .value_parser(|s: &str| s.parse::<Algorithm>())
+                    .value_delimiter(',')
+                    .action(ArgAction::Append),
+            )
             .arg(
                 Arg::new("silent")
                     .value_name("SILENT")
@@ -35,21 +62,168 @@ impl Args {
                     .help("Do not print additional messages")
                     .action(ArgAction::SetTrue),
             )
+            .arg(
+                Arg::new("size")
+                    .value_name("SIZE")
+                    .short('S')
+                    .long("size")
+                    .help("Total size of the input, e.g. 512K or 1.5G [default: size of the INFILEs]")
+                    .value_parser(parse_size)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("rate_limit")
+                    .value_name("RATE")
+                    .short('L')
+                    .long("rate-limit")
+                    .help("Limit the transfer to RATE bytes per second, e.g. 1M, 0 for no limit")
+                    .value_parser(parse_size)
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("line_mode")
+                    .short('l')
+                    .long("line-mode")
+                    .help("Count lines instead of bytes, SIZE is then a number of lines")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("name")
+                    .value_name("NAME")
+                    .short('N')
+                    .long("name")
+                    .help("Prefix the progress with NAME, named pipeviewers in a pipeline get a line each")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("format")
+                    .value_name("FORMAT")
+                    .short('F')
+                    .long("format")
+                    .help("Progress format: %N name, %b total, %t time, %r rate, %p bar, %e ETA, %f file")
+                    .action(ArgAction::Set),
+            )
+            .arg(
+                Arg::new("listen")
+                    .long("listen")
+                    .help("Accept changes from `pipeviewer -R PID` while running")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("remote")
+                    .value_name("PID")
+                    .short('R')
+                    .long("remote")
+                    .help("Change the -L, -F or -N of the pipeviewer running as PID with --listen")
+                    .value_parser(clap::value_parser!(u32))
+                    .requires("changes")
+                    .conflicts_with_all([
+                        "infiles",
+                        "outfile",
+                        "tee",
+                        "hash",
+                        "size",
+                        "line_mode",
+                        "silent",
+                        "listen",
+                    ])
+                    .action(ArgAction::Set),
+            )
+            .group(
+                ArgGroup::new("changes")
+                    .args(["rate_limit", "format", "name"])
+                    .multiple(true),
+            )
             .get_matches();
-        let infile = matches
-            .get_one::<String>("infile")
-            .map(|f| f.to_string())
-            .unwrap_or_default();
+        let infiles: Vec<String> = matches
+            .get_many::<String>("infiles")
+            .map(|files| files.cloned().collect())
+            .unwrap_or_else(|| vec!["-".to_string()]);
         let outfile = matches
             .get_one::<String>("outfile")
             .map(|f| f.to_string())
             .unwrap_or_default();
         let silent =
             matches.get_flag("silent") || !env::var("PV_SILENT").unwrap_or_default().is_empty();
+        let line_mode = matches.get_flag("line_mode");
+        //regular input files tell their own size, stdin does not
+        let file_sizes: Vec<Option<u64>> = infiles
+            .iter()
+            .map(|infile| {
+                fs::metadata(infile)
+                    .ok()
+                    .filter(|m| m.is_file() && !line_mode && infile != "-")
+                    .map(|m| m.len())
+            })
+            .collect();
+        let size = matches
+            .get_one::<u64>("size")
+            .copied()
+            .or_else(|| file_sizes.iter().copied().sum());
         Self {
-            infile,
+            infiles,
+            file_sizes,
             outfile,
+            tees: matches
+                .get_many::<String>("tee")
+                .unwrap_or_default()
+                .cloned()
+                .collect(),
+            hashes: matches
+                .get_many::<Algorithm>("hash")
+                .unwrap_or_default()
+                .copied()
+                .collect(),
             silent,
+            size,
+            rate_limit: matches.get_one::<u64>("rate_limit").copied(),
+            line_mode,
+            name: matches.get_one::<String>("name").cloned(),
+            format: matches.get_one::<String>("format").cloned(),
+            listen: matches.get_flag("listen"),
+            remote: matches.get_one::<u32>("remote").copied(),
         }
     }
 }
+
+/// Parses a number of bytes with an optional binary suffix (K, M, G or T).
+///
+/// # Example
+/// ```rust
+/// use pipeviewer::args::parse_size;
+/// assert_eq!(parse_size("2K"), Ok(2048));
+/// ```
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let err = || format!("invalid size \"{}\"", size);
+    let upper = size.trim().to_uppercase();
+    let number = upper.trim_end_matches("IB").trim_end_matches('B');
+    let (digits, shift) = match number.chars().last() {
+        Some('K') => (&number[..number.len() - 1], 10),
+        Some('M') => (&number[..number.len() - 1], 20),
+        Some('G') => (&number[..number.len() - 1], 30),
+        Some('T') => (&number[..number.len() - 1], 40),
+        _ => (number, 0),
+    };
+    let value: f64 = digits.parse().map_err(|_| err())?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(err());
+    }
+    Ok((value * (1_u64 << shift) as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_size;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("100"), Ok(100));
+        assert_eq!(parse_size("1k"), Ok(1024));
+        assert_eq!(parse_size("1.5M"), Ok(1536 * 1024));
+        assert_eq!(parse_size("2GiB"), Ok(2 << 30));
+        assert_eq!(parse_size("1T"), Ok(1 << 40));
+        assert!(parse_size("").is_err());
+        assert!(parse_size("big").is_err());
+        assert!(parse_size("-5").is_err());
+    }
+}
@@ -0,0 +1,105 @@
+//! The hash module computes digests of the stream as it passes through.
+use md5::Md5;
+use sha2::{Digest, Sha256, Sha512};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Md5,
+    Sha256,
+    Sha512,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "md5" => Ok(Algorithm::Md5),
+            "sha256" => Ok(Algorithm::Sha256),
+            "sha512" => Ok(Algorithm::Sha512),
+            _ => Err(format!(
+                "unknown hash \"{}\", expected md5, sha256 or sha512",
+                s
+            )),
+        }
+    }
+}
+
+impl Algorithm {
+    /// The tag used by `sha256sum --tag` and friends.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha512 => "SHA512",
+        }
+    }
+}
+
+/// A running digest of one of the algorithms.
+pub enum Hasher {
+    Md5(Md5),
+    Sha256(Sha256),
+    Sha512(Sha512),
+}
+
+impl Hasher {
+    pub fn new(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Md5 => Hasher::Md5(Md5::new()),
+            Algorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            Algorithm::Sha512 => Hasher::Sha512(Sha512::new()),
+        }
+    }
+
+    pub fn update(&mut self, buf: &[u8]) {
+        match self {
+            Hasher::Md5(h) => h.update(buf),
+            Hasher::Sha256(h) => h.update(buf),
+            Hasher::Sha512(h) => h.update(buf),
+        }
+    }
+
+    /// The digest in lowercase hex.
+    pub fn finish(self) -> String {
+        let bytes = match self {
+            Hasher::Md5(h) => h.finalize().to_vec(),
+            Hasher::Sha256(h) => h.finalize().to_vec(),
+            Hasher::Sha512(h) => h.finalize().to_vec(),
+        };
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Algorithm, Hasher};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("SHA256".parse(), Ok(Algorithm::Sha256));
+        assert_eq!("md5".parse(), Ok(Algorithm::Md5));
+        assert!("crc32".parse::<Algorithm>().is_err());
+    }
+
+    #[test]
+    fn test_digests() {
+        let digest = |algorithm, parts: &[&[u8]]| {
+            let mut hasher = Hasher::new(algorithm);
+            for part in parts {
+                hasher.update(part);
+            }
+            hasher.finish()
+        };
+        assert_eq!(
+            digest(Algorithm::Md5, &[b"hello ", b"world"]),
+            "5eb63bbbe01eeed093cb22bb8f5acdc3"
+        );
+        assert_eq!(
+            digest(Algorithm::Sha256, &[b"hello", b" world"]),
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        assert_eq!(digest(Algorithm::Sha512, &[]).len(), 128);
+    }
+}
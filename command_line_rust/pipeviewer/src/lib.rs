@@ -1,7 +1,9 @@
 //! Pipeviewer library documentation.
 
 pub mod args;
+pub mod hash;
 pub mod read;
+pub mod remote;
 pub mod stats;
 pub mod write;
 
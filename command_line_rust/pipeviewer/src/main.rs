@@ -1,27 +1,86 @@
 use crossbeam::channel::{bounded, unbounded};
+use pipeviewer::remote::{self, Control};
+use pipeviewer::stats::StatsOptions;
+use pipeviewer::write::WriteOptions;
 use pipeviewer::{args::Args, read, stats, write}; //can not use crate here because the binaries in the project are separate crates
 use std::{io::Result, thread};
 
 fn main() -> Result<()> {
     let Args {
-        infile,
+        infiles,
+        file_sizes,
         outfile,
+        tees,
+        hashes,
         silent,
+        size,
+        rate_limit,
+        line_mode,
+        name,
+        format,
+        listen,
+        remote,
     } = Args::parse();
 
+    //`-R PID` only sends the changes to another pipeviewer
+    if let Some(pid) = remote {
+        let controls: Vec<Control> = rate_limit
+            .map(Control::RateLimit)
+            .into_iter()
+            .chain(format.map(Control::Format))
+            .chain(name.map(Control::Name))
+            .collect();
+        return remote::send(pid, &controls);
+    }
+
     let (stats_tx, stats_rx) = unbounded();
     let (write_tx, write_rx) = bounded(1024);
+    let (write_ctl_tx, write_ctl_rx) = unbounded();
+    let (stats_ctl_tx, stats_ctl_rx) = unbounded();
+    //only reachable by `-R` when asked for
+    let socket = match listen {
+        true => Some(remote::listen(write_ctl_tx, stats_ctl_tx)?),
+        false => None,
+    };
 
-    let read_handle = thread::spawn(move || read::read_loop(&infile, stats_tx, write_tx));
-    let stats_handle = thread::spawn(move || stats::stats_loop(silent, stats_rx));
-    let write_handle = thread::spawn(move || write::write_loop(&outfile, write_rx));
+    let options = StatsOptions {
+        silent,
+        line_mode,
+        size,
+        files: infiles.iter().cloned().zip(file_sizes).collect(),
+        name,
+        format,
+    };
+    //the digests name the output they describe, like `sha256sum --tag`
+    let output = match outfile.as_str() {
+        "" => "-".to_string(),
+        _ => outfile.clone(),
+    };
+    let write_options = WriteOptions {
+        outfile,
+        tees,
+        hashes,
+        rate_limit,
+    };
+    let read_handle = thread::spawn(move || read::read_loop(&infiles, write_tx));
+    let stats_handle = thread::spawn(move || stats::stats_loop(options, stats_ctl_rx, stats_rx));
+    let write_handle = thread::spawn(move || {
+        write::write_loop(write_options, write_ctl_rx, write_rx, stats_tx)
+    });
 
     //crash if any thread has crashed
     //`.join()` returns a `thread::Result<io::Result<()>>`
     //return an error if any thread returned an error
-    read_handle.join().unwrap()?;
-    stats_handle.join().unwrap()?;
-    write_handle.join().unwrap()?;
+    let read_result = read_handle.join().unwrap();
+    let stats_result = stats_handle.join().unwrap();
+    let write_result = write_handle.join().unwrap();
+    remote::cleanup(socket);
+    read_result?;
+    stats_result?;
+    //after the stats, so they do not end up on the progress line
+    for (algorithm, digest) in write_result? {
+        eprintln!("{} ({}) = {}", algorithm.tag(), output, digest);
+    }
     Ok(())
 }
 
@@ -31,7 +90,13 @@ fn main() -> Result<()> {
 
 //yes | cargo run | head -n 1 > /dev/null
 //yes | cargo run -- -o yex.txt
+//cat myfile | cargo run -- -S 128K > myfile2
+//yes | cargo run -- -l -L 100 > /dev/null
 //echo "apple" | cargo run -- -o fruit.txt
+//cargo run -- -N first myfile myfile2 | cargo run -- -N second -L 1M > /dev/null
+//cargo run -- myfile --tee copy1 --tee copy2 --hash sha256,md5 -o myfile2
+//yes | cargo run -- --listen > /dev/null
+//cargo run -- -R 12345 -L 10M -F "%N %b %r %e"
 
 
 //cargo doc --no-deps --open
\ No newline at end of file
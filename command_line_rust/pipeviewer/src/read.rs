@@ -3,27 +3,41 @@ use crossbeam::channel::Sender;
 use std::fs::File;
 use std::io::{self, BufReader, Read, Result};
 
-pub fn read_loop(infile: &str, stats_tx: Sender<usize>, write_tx: Sender<Vec<u8>>) -> Result<()> {
-    let mut reader: Box<dyn Read> = if !infile.is_empty() {
-        Box::new(BufReader::new(File::open(infile)?))
-    } else {
-        Box::new(BufReader::new(io::stdin()))
+/// Reads the files one after the other, "-" being stdin.
+pub fn read_loop(infiles: &[String], write_tx: Sender<Vec<u8>>) -> Result<()> {
+    //open them all first, so a missing file stops us before anything is written
+    let readers = infiles
+        .iter()
+        .map(|infile| -> Result<Box<dyn Read>> {
+            match infile.as_str() {
+                "-" => Ok(Box::new(BufReader::new(io::stdin()))),
+                _ => Ok(Box::new(BufReader::new(File::open(infile)?))),
+            }
+        })
+        .collect::<Result<Vec<_>>>();
+    let readers = match readers {
+        Ok(readers) => readers,
+        Err(e) => {
+            //send an empty buffer so the write thread stops too
+            let _ = write_tx.send(Vec::new());
+            return Err(e);
+        }
     };
     let mut buf = [0; CHUNK_SIZE];
-    loop {
-        let num_read = match reader.read(&mut buf) {
-            Ok(0) => break,
-            Ok(x) => x,
-            Err(_) => break,
-        };
-        let _ = stats_tx.send(num_read);
-        //send this buffer to the write thread
-        if write_tx.send(Vec::from(&buf[..num_read])).is_err() {
-            break;
+    'files: for mut reader in readers {
+        loop {
+            let num_read = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(x) => x,
+                Err(_) => break,
+            };
+            //send this buffer to the write thread
+            if write_tx.send(Vec::from(&buf[..num_read])).is_err() {
+                break 'files;
+            }
         }
     }
     //send an empty buffer to the write thread
-    let _ = stats_tx.send(0);
     let _ = write_tx.send(Vec::new());
     Ok(())
 }
@@ -0,0 +1,172 @@
+//! The remote module lets `pipeviewer -R PID` change a running instance
+//! started with `--listen`, through a Unix socket named after its PID.
+//! The socket is only open to the user running it.
+use crossbeam::channel::Sender;
+use std::env;
+use std::fs::{self, DirBuilder, Permissions};
+use std::io::{BufRead, BufReader, Error, ErrorKind, Result, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+
+/// A change requested by a remote `pipeviewer -R PID`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Control {
+    RateLimit(u64),
+    Format(String),
+    Name(String),
+}
+
+impl Control {
+    /// One line of the socket protocol, e.g. `rate-limit 1048576`.
+    pub fn encode(&self) -> String {
+        match self {
+            Control::RateLimit(rate) => format!("rate-limit {}", rate),
+            Control::Format(format) => format!("format {}", format),
+            Control::Name(name) => format!("name {}", name),
+        }
+    }
+
+    pub fn decode(line: &str) -> Option<Self> {
+        let (command, value) = line.split_once(' ')?;
+        match command {
+            "rate-limit" => value.parse().ok().map(Control::RateLimit),
+            "format" => Some(Control::Format(value.to_string())),
+            "name" => Some(Control::Name(value.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// `$XDG_RUNTIME_DIR`, which belongs to the user, or else a directory of
+/// their own in the temporary directory.
+fn socket_dir() -> Result<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    let uid = unsafe { libc::getuid() };
+    let dir = env::temp_dir().join(format!("pipeviewer-remote-{}", uid));
+    match DirBuilder::new().mode(0o700).create(&dir) {
+        Err(e) if e.kind() != ErrorKind::AlreadyExists => return Err(e),
+        _ => {}
+    }
+    //anyone can create it first in the shared temporary directory
+    let meta = fs::symlink_metadata(&dir)?;
+    if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+        return Err(Error::other(format!(
+            "{} is not a private directory",
+            dir.display()
+        )));
+    }
+    Ok(dir)
+}
+
+pub fn socket_path(pid: u32) -> Result<PathBuf> {
+    Ok(socket_dir()?.join(format!("pipeviewer-{}.sock", pid)))
+}
+
+/// Listens for remote changes on a background thread: rate limits go to the
+/// write thread and everything else to the stats thread.
+/// Returns the socket path, to be removed with `cleanup` at the end.
+pub fn listen(write_ctl: Sender<Control>, stats_ctl: Sender<Control>) -> Result<PathBuf> {
+    let path = socket_path(process::id())?;
+    let _ = fs::remove_file(&path); //left over by a process with the same PID
+    let listener = UnixListener::bind(&path)?;
+    fs::set_permissions(&path, Permissions::from_mode(0o600))?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reply = stream.try_clone();
+            for line in BufReader::new(stream).lines().map_while(|l| l.ok()) {
+                let answer = match Control::decode(&line) {
+                    Some(ctl @ Control::RateLimit(_)) => write_ctl.send(ctl).map_err(|_| ()),
+                    Some(ctl) => stats_ctl.send(ctl).map_err(|_| ()),
+                    None => Err(()),
+                };
+                if let Ok(reply) = reply.as_mut() {
+                    let _ = match answer {
+                        Ok(()) => writeln!(reply, "ok"),
+                        Err(()) => writeln!(reply, "error {}", line),
+                    };
+                }
+            }
+        }
+    });
+    Ok(path)
+}
+
+pub fn cleanup(path: Option<PathBuf>) {
+    if let Some(path) = path {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Sends the changes to the pipeviewer running as `pid`.
+pub fn send(pid: u32, controls: &[Control]) -> Result<()> {
+    let stream = UnixStream::connect(socket_path(pid)?).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!("no running pipeviewer with PID {}: {}", pid, e),
+        )
+    })?;
+    let mut writer = stream.try_clone()?;
+    let mut replies = BufReader::new(stream).lines();
+    for ctl in controls {
+        writeln!(writer, "{}", ctl.encode())?;
+        match replies.next() {
+            Some(Ok(reply)) if reply == "ok" => {}
+            _ => {
+                return Err(Error::other(format!(
+                    "pipeviewer {} rejected \"{}\"",
+                    pid,
+                    ctl.encode()
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{listen, send, Control};
+    use crossbeam::channel::unbounded;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_encode_decode() {
+        let controls = vec![
+            Control::RateLimit(1 << 20),
+            Control::Format("%b %r".to_string()),
+            Control::Name("restore db".to_string()),
+        ];
+        for ctl in controls {
+            assert_eq!(Control::decode(&ctl.encode()), Some(ctl));
+        }
+        assert_eq!(Control::decode("rate-limit fast"), None);
+        assert_eq!(Control::decode("explode now"), None);
+        assert_eq!(Control::decode("name"), None);
+    }
+
+    #[test]
+    fn test_listen_send() {
+        let (write_tx, write_rx) = unbounded();
+        let (stats_tx, stats_rx) = unbounded();
+        let path = listen(write_tx, stats_tx).unwrap();
+        let mode = |path: &std::path::Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(mode(path.parent().unwrap()) & 0o077, 0);
+        let pid = std::process::id();
+        send(
+            pid,
+            &[Control::RateLimit(100), Control::Name("a".to_string())],
+        )
+        .unwrap();
+        assert_eq!(write_rx.recv().unwrap(), Control::RateLimit(100));
+        assert_eq!(stats_rx.recv().unwrap(), Control::Name("a".to_string()));
+        super::cleanup(Some(path));
+        assert!(send(pid, &[Control::RateLimit(1)]).is_err());
+    }
+}
@@ -1,69 +1,296 @@
 //! The stats module contains the stats loop
+mod rate;
+mod stack;
 mod timer;
 
+use crate::remote::Control;
 use crossbeam::channel::Receiver;
 use crossterm::{
-    cursor, execute,
+    cursor, execute, queue,
     style::{self, Color, PrintStyledContent, Stylize},
-    terminal::{Clear, ClearType},
+    terminal::{self, Clear, ClearType},
 };
+use rate::RateWindow;
+use stack::Slot;
 use std::io::{self, Result, Stderr, Write};
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use timer::Timer;
 
-pub fn stats_loop(silent: bool, stats_rx: Receiver<usize>) -> Result<()> {
-    let mut total_bytes = 0;
+//the rate is averaged over this many seconds
+const RATE_WINDOW_SECS: u64 = 5;
+
+/// How the stats thread shows the progress.
+pub struct StatsOptions {
+    pub silent: bool,
+    pub line_mode: bool,
+    pub size: Option<u64>,
+    pub files: Vec<(String, Option<u64>)>, //input files and their sizes
+    pub name: Option<String>,
+    pub format: Option<String>,
+}
+
+pub fn stats_loop(
+    mut options: StatsOptions,
+    control_rx: Receiver<Control>,
+    stats_rx: Receiver<Arc<Vec<u8>>>,
+) -> Result<()> {
+    let mut total = 0;
     let start = Instant::now();
     let mut timer = Timer::new();
+    let mut rate = RateWindow::new(Duration::from_secs(RATE_WINDOW_SECS));
     let mut stderr = io::stderr();
+    //named pipeviewers stack their lines, the others share the current line
+    let slot = match (&options.name, options.silent) {
+        (Some(_), false) => Slot::claim().ok(),
+        _ => None,
+    };
+    if let Some(slot) = &slot {
+        slot.reserve(&mut stderr)?;
+    }
     //use the carriage return to return the cursor to the beginning of the line so that
     //it will overwrite the previous value
     loop {
-        //receive the vector of bytes
-        let num_bytes = stats_rx.recv().unwrap();
+        //receive the vector of bytes, an empty one (or a closed channel) means the end
+        let buf = stats_rx.recv().unwrap_or_default();
+        //apply the changes sent by `pipeviewer -R`
+        for control in control_rx.try_iter() {
+            match control {
+                Control::Format(format) => options.format = Some(format),
+                Control::Name(name) => options.name = Some(name),
+                Control::RateLimit(_) => {}
+            }
+        }
         //output progress statistics at a steady rate
         timer.update();
-        let rate_per_second = num_bytes as f64 / timer.delta.as_secs_f64();
-        total_bytes += num_bytes;
-        if !silent && timer.ready {
+        total += if options.line_mode {
+            buf.iter().filter(|&&b| b == b'\n').count() as u64
+        } else {
+            buf.len() as u64
+        };
+        rate.record(timer.last_instant, total);
+        //always show the final numbers
+        if !options.silent && (timer.ready || buf.is_empty()) {
             timer.ready = false;
-            output_progress(
-                &mut stderr,
-                total_bytes,
-                start.elapsed().as_secs().as_time(),
-                rate_per_second,
-            );
+            let progress = Progress {
+                total,
+                elapsed: start.elapsed(),
+                rate: rate.rate(),
+                size: options.size,
+                line_mode: options.line_mode,
+            };
+            let line = render(&progress, &options);
+            match &slot {
+                Some(slot) => slot.draw(&mut stderr, &line)?,
+                None => output_progress(&mut stderr, &line),
+            }
         }
-        if num_bytes == 0 {
+        if buf.is_empty() {
             break;
         }
     }
-    if !silent {
-        eprintln!();
+    match slot {
+        Some(slot) => slot.release(&mut stderr)?,
+        None if !options.silent => eprintln!(),
+        None => {}
     }
     Ok(())
 }
 
-fn output_progress(stderr: &mut Stderr, bytes: usize, elapsed: String, rate: f64) {
-    let bytes = style::style(format!("total bytes: {}, ", bytes)).with(Color::Red);
-    let elapsed = style::style(format!("time elapsed: {}, ", elapsed)).with(Color::Green);
-    let rate = style::style(format!("rate per second: [{:.0}b/s]", rate)).with(Color::Blue);
+/// A snapshot of the transfer, as shown on the progress line.
+pub struct Progress {
+    pub total: u64, //bytes, or lines in line mode
+    pub elapsed: Duration,
+    pub rate: f64, //per second
+    pub size: Option<u64>,
+    pub line_mode: bool,
+}
+
+impl Progress {
+    /// Fraction of `size` done, between 0 and 1.
+    pub fn fraction(&self) -> Option<f64> {
+        self.size.map(|size| match size {
+            0 => 1.0,
+            _ => (self.total as f64 / size as f64).min(1.0),
+        })
+    }
+
+    /// Seconds left at the current rate.
+    pub fn eta(&self) -> Option<u64> {
+        let left = self.size?.saturating_sub(self.total);
+        match left {
+            0 => Some(0),
+            _ if self.rate > 0.0 => Some((left as f64 / self.rate).ceil() as u64),
+            _ => None,
+        }
+    }
+
+    /// The input file being read, as `(index, name, fraction of it done)`.
+    /// Only known while the sizes of all the files before it are known.
+    pub fn current_file<'a>(
+        &self,
+        files: &'a [(String, Option<u64>)],
+    ) -> Option<(usize, &'a str, f64)> {
+        let mut before = 0;
+        for (i, (name, size)) in files.iter().enumerate() {
+            let size = (*size)?;
+            if self.total < before + size || i == files.len() - 1 {
+                let fraction = match size {
+                    0 => 1.0,
+                    _ => ((self.total - before) as f64 / size as f64).min(1.0),
+                };
+                return Some((i, name, fraction));
+            }
+            before += size;
+        }
+        None
+    }
+}
+
+fn output_progress(stderr: &mut Stderr, line: &[u8]) {
     let _ = execute!(
         stderr,
         cursor::MoveToColumn(0),
         Clear(ClearType::CurrentLine),
-        PrintStyledContent(bytes),
-        PrintStyledContent(elapsed),
-        PrintStyledContent(rate)
     );
+    let _ = stderr.write_all(line);
     let _ = stderr.flush();
 }
 
+/// Renders the progress line, with the user's --format if there is one.
+/// The format may use %N (name), %b (total), %t (time elapsed), %r (rate),
+/// %p (bar and percentage), %e (ETA), %f (current file) and %%.
+fn render(progress: &Progress, options: &StatsOptions) -> Vec<u8> {
+    let (total, rate) = if progress.line_mode {
+        (
+            format!("{}", progress.total),
+            format!("{:.0} lines/s", progress.rate),
+        )
+    } else {
+        (
+            progress.total.as_size(),
+            format!("{}/s", (progress.rate as u64).as_size()),
+        )
+    };
+    let elapsed = progress.elapsed.as_secs().as_time();
+    let eta = match progress.eta() {
+        Some(secs) => format!("ETA {}", secs.as_time()),
+        None => "ETA -:--:--".to_string(),
+    };
+    let file = match (options.files.len(), progress.current_file(&options.files)) {
+        (2.., Some((i, name, fraction))) => format!(
+            "({}/{} {} {:.0}%)",
+            i + 1,
+            options.files.len(),
+            name,
+            fraction * 100.0
+        ),
+        _ => String::new(),
+    };
+    let columns = terminal::size().map_or(80, |(w, _)| w as usize);
+
+    if let Some(format) = &options.format {
+        let mut line = String::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                line.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('N') => line.push_str(options.name.as_deref().unwrap_or_default()),
+                Some('b') => line.push_str(&total),
+                Some('t') => line.push_str(&elapsed),
+                Some('r') => line.push_str(&rate),
+                Some('e') => line.push_str(&eta),
+                Some('f') => line.push_str(&file),
+                Some('%') => line.push('%'),
+                Some('p') => {
+                    if let Some(fraction) = progress.fraction() {
+                        let used = format.len() + total.len() + rate.len() + eta.len() + file.len();
+                        let width = columns.saturating_sub(used).clamp(10, 50);
+                        line.push_str(&format!(
+                            "{} {:3.0}%",
+                            progress_bar(fraction, width),
+                            fraction * 100.0
+                        ));
+                    }
+                }
+                Some(other) => {
+                    line.push('%');
+                    line.push(other);
+                }
+                None => line.push('%'),
+            }
+        }
+        return line.into_bytes();
+    }
+
+    let name = options
+        .name
+        .as_ref()
+        .map(|n| format!("{}: ", n))
+        .unwrap_or_default();
+    let unit = if progress.line_mode { "lines" } else { "bytes" };
+    let total = format!("total {}: {}, ", unit, total);
+    let elapsed = format!("time elapsed: {}, ", elapsed);
+    let rate = format!("rate per second: [{}]", rate);
+    //the bar takes whatever room is left on the line
+    let bar = progress.fraction().map(|fraction| {
+        let used = name.len()
+            + total.len()
+            + elapsed.len()
+            + rate.len()
+            + eta.len()
+            + file.len()
+            + "  [] 100%  ".len();
+        format!(
+            " {} {:3.0}% {}",
+            progress_bar(fraction, columns.saturating_sub(used).clamp(10, 50)),
+            fraction * 100.0,
+            eta
+        )
+    });
+    let file = if file.is_empty() {
+        file
+    } else {
+        format!(" {}", file)
+    };
+    let mut line = vec![];
+    let _ = queue!(
+        line,
+        PrintStyledContent(style::style(name).bold()),
+        PrintStyledContent(style::style(total).with(Color::Red)),
+        PrintStyledContent(style::style(elapsed).with(Color::Green)),
+        PrintStyledContent(style::style(rate).with(Color::Blue)),
+        PrintStyledContent(style::style(bar.unwrap_or_default()).with(Color::Yellow)),
+        PrintStyledContent(style::style(file).with(Color::Magenta))
+    );
+    line
+}
+
+/// Draws `fraction` of a bar `width` characters wide, e.g. `[=====>    ]`.
+///
+/// # Example
+/// ```rust
+/// use pipeviewer::stats::progress_bar;
+/// assert_eq!(progress_bar(0.5, 10), String::from("[====>     ]"));
+/// ```
+pub fn progress_bar(fraction: f64, width: usize) -> String {
+    let done = ((fraction.clamp(0.0, 1.0) * width as f64) as usize).min(width);
+    let bar = match done {
+        0 => " ".repeat(width),
+        _ if done == width => "=".repeat(width),
+        _ => format!("{}>{}", "=".repeat(done - 1), " ".repeat(width - done)),
+    };
+    format!("[{}]", bar)
+}
+
 /// The TimeOutput trait adds a `.as_time()` method to `u64`.
-/// 
+///
 /// # Example
 /// Here is an example of how to use it.
-/// 
+///
 /// ```rust
 /// use pipeviewer::stats::TimeOutput;
 /// assert_eq!(65_u64.as_time(), String::from("0:01:05"));
@@ -81,9 +308,40 @@ impl TimeOutput for u64 {
     }
 }
 
+/// The SizeOutput trait adds a `.as_size()` method to `u64`.
+///
+/// # Example
+/// Sizes are shown in binary units.
+///
+/// ```rust
+/// use pipeviewer::stats::SizeOutput;
+/// assert_eq!(1536_u64.as_size(), String::from("1.50 KiB"));
+/// ```
+pub trait SizeOutput {
+    fn as_size(&self) -> String;
+}
+
+impl SizeOutput for u64 {
+    /// Renders the u64 as a number of bytes in B, KiB, MiB, ...
+    fn as_size(&self) -> String {
+        const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+        let mut value = *self as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        match unit {
+            0 => format!("{} B", self),
+            _ => format!("{:.2} {}", value, UNITS[unit]),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::TimeOutput;
+    use super::{progress_bar, render, Progress, SizeOutput, StatsOptions, TimeOutput};
+    use std::time::Duration;
 
     #[test]
     fn test_as_time() {
@@ -91,10 +349,101 @@ mod tests {
             (5_u64, "0:00:05"),
             (60_u64, "0:01:00"),
             (154_u64, "0:02:34"),
-            (3603_u64, "1:00:03")
+            (3603_u64, "1:00:03"),
         ];
         for (input, output) in pairs {
             assert_eq!(input.as_time().as_str(), output);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_as_size() {
+        let pairs = vec![
+            (0_u64, "0 B"),
+            (1023_u64, "1023 B"),
+            (1024_u64, "1.00 KiB"),
+            (5 * 1024 * 1024_u64, "5.00 MiB"),
+            (3 * 1024 * 1024 * 1024_u64 / 2, "1.50 GiB"),
+        ];
+        for (input, output) in pairs {
+            assert_eq!(input.as_size().as_str(), output);
+        }
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        assert_eq!(progress_bar(0.0, 4), "[    ]");
+        assert_eq!(progress_bar(0.5, 4), "[=>  ]");
+        assert_eq!(progress_bar(1.0, 4), "[====]");
+        assert_eq!(progress_bar(2.0, 4), "[====]");
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut progress = Progress {
+            total: 250,
+            elapsed: Duration::from_secs(5),
+            rate: 50.0,
+            size: Some(1000),
+            line_mode: false,
+        };
+        assert_eq!(progress.fraction(), Some(0.25));
+        assert_eq!(progress.eta(), Some(15));
+        progress.rate = 0.0;
+        assert_eq!(progress.eta(), None);
+        progress.total = 1200;
+        assert_eq!(progress.fraction(), Some(1.0));
+        assert_eq!(progress.eta(), Some(0));
+        progress.size = None;
+        assert_eq!(progress.fraction(), None);
+        assert_eq!(progress.eta(), None);
+    }
+
+    #[test]
+    fn test_current_file() {
+        let files = vec![
+            ("a".to_string(), Some(100)),
+            ("b".to_string(), Some(0)),
+            ("c".to_string(), Some(50)),
+        ];
+        let mut progress = Progress {
+            total: 0,
+            elapsed: Duration::from_secs(1),
+            rate: 0.0,
+            size: Some(150),
+            line_mode: false,
+        };
+        assert_eq!(progress.current_file(&files), Some((0, "a", 0.0)));
+        progress.total = 100;
+        assert_eq!(progress.current_file(&files), Some((2, "c", 0.0)));
+        progress.total = 125;
+        assert_eq!(progress.current_file(&files), Some((2, "c", 0.5)));
+        progress.total = 150;
+        assert_eq!(progress.current_file(&files), Some((2, "c", 1.0)));
+        let files = vec![("-".to_string(), None), ("c".to_string(), Some(50))];
+        assert_eq!(progress.current_file(&files), None);
+    }
+
+    #[test]
+    fn test_render_format() {
+        let progress = Progress {
+            total: 2048,
+            elapsed: Duration::from_secs(65),
+            rate: 1024.0,
+            size: Some(4096),
+            line_mode: false,
+        };
+        let options = StatsOptions {
+            silent: false,
+            line_mode: false,
+            size: Some(4096),
+            files: vec![("a".to_string(), Some(1024)), ("b".to_string(), Some(3072))],
+            name: Some("backup".to_string()),
+            format: Some("%N: %b at %r, %t %e %f 100%%".to_string()),
+        };
+        assert_eq!(
+            String::from_utf8(render(&progress, &options)).unwrap(),
+            "backup: 2.00 KiB at 1.00 KiB/s, 0:01:05 ETA 0:00:02 (2/2 b 33%) 100%"
+        );
+    }
+}
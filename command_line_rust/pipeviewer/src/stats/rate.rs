@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The window keeps at most one sample per slice of this many
+const SLICES: u32 = 32;
+
+/// Average transfer rate over a sliding time window, which is much steadier
+/// than the rate of a single chunk.
+pub struct RateWindow {
+    pub window: Duration,
+    samples: VecDeque<(Instant, u64)>, //when, and the running total at that time
+}
+
+impl RateWindow {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Records the running total at `now`.
+    pub fn record(&mut self, now: Instant, total: u64) {
+        //a sample less than a slice after the one before it is replaced,
+        //so many small chunks do not grow the window
+        let len = self.samples.len();
+        if len >= 2 && now - self.samples[len - 2].0 < self.window / SLICES {
+            self.samples.pop_back();
+        }
+        self.samples.push_back((now, total));
+        //keep one sample older than the window as the starting point
+        while self.samples.len() > 2 && now - self.samples[1].0 >= self.window {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Units per second between the oldest and newest sample.
+    pub fn rate(&self) -> f64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(&(t0, n0)), Some(&(t1, n1))) if t1 > t0 => {
+                (n1 - n0) as f64 / (t1 - t0).as_secs_f64()
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateWindow;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_rate() {
+        let start = Instant::now();
+        let mut window = RateWindow::new(Duration::from_secs(5));
+        assert_eq!(window.rate(), 0.0);
+        window.record(start, 0);
+        assert_eq!(window.rate(), 0.0);
+        window.record(start + Duration::from_secs(1), 100);
+        assert_eq!(window.rate(), 100.0);
+        window.record(start + Duration::from_secs(2), 300);
+        assert_eq!(window.rate(), 150.0);
+    }
+
+    #[test]
+    fn test_window_slides() {
+        let start = Instant::now();
+        let mut window = RateWindow::new(Duration::from_secs(2));
+        //fast at first, then slow: the old samples must be forgotten
+        window.record(start, 0);
+        window.record(start + Duration::from_secs(1), 1000);
+        for i in 2..10 {
+            window.record(start + Duration::from_secs(i), 1000 + (i - 1) * 10);
+        }
+        assert_eq!(window.rate(), 10.0);
+    }
+
+    #[test]
+    fn test_many_chunks() {
+        let start = Instant::now();
+        let mut window = RateWindow::new(Duration::from_secs(5));
+        //a chunk every millisecond for 10 seconds
+        for i in 0..10_000 {
+            window.record(start + Duration::from_millis(i), i * 100);
+        }
+        assert!(window.samples.len() <= super::SLICES as usize + 2);
+        assert!((window.rate() - 100_000.0).abs() < 1.0);
+    }
+}
@@ -0,0 +1,168 @@
+use crossterm::{
+    cursor, queue,
+    terminal::{Clear, ClearType},
+};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{Error, ErrorKind, Result, Write};
+use std::os::unix::process::parent_id;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// No terminal has room for more lines than this
+const MAX_SLOTS: u16 = 256;
+
+/// The line a named pipeviewer draws on, below the line where the pipeline started.
+/// The pipeviewers of one pipeline share a parent (the shell), and claim their
+/// lines with files in a directory named after it.
+pub struct Slot {
+    pub index: u16,
+    dir: PathBuf,
+}
+
+impl Slot {
+    pub fn claim() -> Result<Self> {
+        Self::claim_in(env::temp_dir().join(format!("pipeviewer-{}", parent_id())))
+    }
+
+    pub fn claim_in(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let mut index = 0;
+        while index < MAX_SLOTS {
+            let path = dir.join(index.to_string());
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    write!(file, "{}", process::id())?;
+                    return Ok(Self { index, dir });
+                }
+                //reuse the lines of pipeviewers that died without cleaning up
+                Err(e) if e.kind() == ErrorKind::AlreadyExists && !is_alive(&path) => {
+                    match fs::remove_file(&path) {
+                        //another pipeviewer got there first
+                        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+                        _ => {}
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => index += 1,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(Error::other(format!("more than {} pipeviewers in {}", MAX_SLOTS, dir.display())))
+    }
+
+    /// Makes room for the line of this slot below the cursor.
+    pub fn reserve(&self, out: &mut impl Write) -> Result<()> {
+        if self.index > 0 {
+            write!(out, "{}", "\n".repeat(self.index as usize))?;
+            queue!(out, cursor::MoveUp(self.index))?;
+        }
+        out.flush()
+    }
+
+    /// Writes `line` on the line of this slot, and moves the cursor back to
+    /// where the pipeline started so the other pipeviewers find it there.
+    pub fn draw(&self, out: &mut impl Write, line: &[u8]) -> Result<()> {
+        //one write, so that the moves of several processes do not interleave
+        let mut buf = vec![];
+        if self.index > 0 {
+            queue!(buf, cursor::MoveDown(self.index))?;
+        }
+        queue!(buf, cursor::MoveToColumn(0), Clear(ClearType::CurrentLine))?;
+        buf.extend(line);
+        if self.index > 0 {
+            queue!(buf, cursor::MoveUp(self.index))?;
+        }
+        queue!(buf, cursor::MoveToColumn(0))?;
+        out.write_all(&buf)?;
+        out.flush()
+    }
+
+    /// Gives the slot back. The last pipeviewer to finish moves the cursor
+    /// below all the lines and removes the directory.
+    pub fn release(self, out: &mut impl Write) -> Result<()> {
+        fs::write(self.dir.join(format!("{}.done", self.index)), "")?;
+        let names: Vec<String> = fs::read_dir(&self.dir)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        let slots = names.iter().filter(|n| n.parse::<u16>().is_ok()).count();
+        let done = names.iter().filter(|n| n.ends_with(".done")).count();
+        if done >= slots
+            && OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(self.dir.join("finished"))
+                .is_ok()
+        {
+            write!(out, "{}", "\n".repeat(slots))?;
+            out.flush()?;
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+        Ok(())
+    }
+}
+
+fn is_alive(path: &Path) -> bool {
+    match fs::read_to_string(path)
+        .ok()
+        .and_then(|pid| pid.parse::<i32>().ok())
+    {
+        //signal 0 only checks that the process exists
+        Some(pid) => {
+            let alive = unsafe { libc::kill(pid, 0) == 0 };
+            alive || Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+        }
+        //still being written by its owner
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Slot;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_slots() {
+        let dir = env::temp_dir().join(format!("pipeviewer-test-{}", std::process::id()));
+        let first = Slot::claim_in(dir.clone()).unwrap();
+        let second = Slot::claim_in(dir.clone()).unwrap();
+        assert_eq!((first.index, second.index), (0, 1));
+
+        //a slot of a dead process is taken over
+        fs::write(dir.join("2"), i32::MAX.to_string()).unwrap();
+        let third = Slot::claim_in(dir.clone()).unwrap();
+        assert_eq!(third.index, 2);
+
+        let mut out = vec![];
+        second.draw(&mut out, b"hello").unwrap();
+        assert_eq!(out, b"\x1b[1B\x1b[1G\x1b[2Khello\x1b[1A\x1b[1G");
+
+        //only the last one to finish moves below the lines
+        let mut out = vec![];
+        first.release(&mut out).unwrap();
+        second.release(&mut out).unwrap();
+        assert!(out.is_empty());
+        third.release(&mut out).unwrap();
+        assert_eq!(out, b"\n\n\n");
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_errors() {
+        let dir = env::temp_dir().join(format!("pipeviewer-test-errors-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        //every slot taken by a live process
+        for index in 0..super::MAX_SLOTS {
+            fs::write(dir.join(index.to_string()), std::process::id().to_string()).unwrap();
+        }
+        assert!(Slot::claim_in(dir.clone()).is_err());
+
+        //a slot that cannot be created is an error, not a taken slot
+        fs::remove_dir_all(&dir).unwrap();
+        fs::write(&dir, "").unwrap();
+        assert!(Slot::claim_in(dir.join("sub")).is_err());
+        fs::remove_file(&dir).unwrap();
+    }
+}
@@ -1,25 +1,225 @@
-use crossbeam::channel::Receiver;
+//! The write module contains the write loop, which also shapes the throughput
+mod bucket;
+
+use crate::hash::{Algorithm, Hasher};
+use crate::remote::Control;
+use bucket::TokenBucket;
+use crossbeam::channel::{bounded, Receiver, Sender};
 use std::fs::File;
 use std::io::{self, BufWriter, ErrorKind, Result, Write};
+use std::sync::Arc;
+use std::thread;
+
+/// Where and how the write thread writes the stream.
+pub struct WriteOptions {
+    pub outfile: String,
+    pub tees: Vec<String>, //extra copies, each written on its own thread
+    pub hashes: Vec<Algorithm>,
+    pub rate_limit: Option<u64>, //bytes per second
+}
 
-pub fn write_loop(outfile: &str, write_rx: Receiver<Vec<u8>>) -> Result<()> {
-    let mut writer: Box<dyn Write> = if !outfile.is_empty() {
-        Box::new(BufWriter::new(File::create(outfile)?))
+/// Writes the stream and returns the digests asked for, in order.
+pub fn write_loop(
+    options: WriteOptions,
+    control_rx: Receiver<Control>,
+    write_rx: Receiver<Vec<u8>>,
+    stats_tx: Sender<Arc<Vec<u8>>>,
+) -> Result<Vec<(Algorithm, String)>> {
+    let mut writer: Box<dyn Write> = if !options.outfile.is_empty() {
+        Box::new(BufWriter::new(File::create(&options.outfile)?))
     } else {
         Box::new(BufWriter::new(io::stdout()))
     };
+    //the copies get bounded channels too, so the slowest output sets the pace
+    let mut tees = vec![];
+    for tee in &options.tees {
+        let file = File::create(tee)?;
+        let (tee_tx, tee_rx) = bounded(1024);
+        tees.push((tee_tx, thread::spawn(move || tee_loop(file, tee_rx))));
+    }
+    let hasher = match options.hashes.is_empty() {
+        true => None,
+        false => {
+            let (hash_tx, hash_rx) = bounded(1024);
+            let hashes = options.hashes.clone();
+            Some((hash_tx, thread::spawn(move || hash_loop(&hashes, hash_rx))))
+        }
+    };
+    let mut bucket = options.rate_limit.and_then(TokenBucket::limit);
+    let mut result = Ok(());
     loop {
         //receive buffer from the read thread
         let buf = write_rx.recv().unwrap();
         if buf.is_empty() {
             break;
         }
-        if let Err(e) = writer.write_all(&buf) {
-            if e.kind() == ErrorKind::BrokenPipe {
-                return Ok(()); //ignore the broken pipe error, stop the program cleanly
+        if let Err(e) = write_buf(&mut writer, &mut bucket, &control_rx, &buf) {
+            if e.kind() != ErrorKind::BrokenPipe {
+                result = Err(e);
+                break;
+            }
+            //ignore the broken pipe error, stop the program cleanly, unless
+            //the copies and digests still need the rest of the stream
+            if tees.is_empty() && hasher.is_none() {
+                break;
+            }
+            writer = Box::new(io::sink());
+        }
+        //one copy of the buffer is shared by the threads
+        let buf = Arc::new(buf);
+        //a copy thread only hangs up when it failed, its error is collected below
+        if tees
+            .iter()
+            .any(|(tee_tx, _)| tee_tx.send(Arc::clone(&buf)).is_err())
+        {
+            break;
+        }
+        if let Some((hash_tx, _)) = &hasher {
+            let _ = hash_tx.send(Arc::clone(&buf));
+        }
+        //the stats count what has been written, so they show the shaped rate
+        let _ = stats_tx.send(buf);
+    }
+    //send an empty buffer to the stats thread
+    let _ = stats_tx.send(Arc::default());
+    //closing the channels ends the other threads
+    for (tee_tx, handle) in tees {
+        drop(tee_tx);
+        let tee_result = handle.join().unwrap();
+        result = result.and(tee_result);
+    }
+    let digests = match hasher {
+        Some((hash_tx, handle)) => {
+            drop(hash_tx);
+            handle.join().unwrap()
+        }
+        None => vec![],
+    };
+    result.map(|_| digests)
+}
+
+fn tee_loop(file: File, tee_rx: Receiver<Arc<Vec<u8>>>) -> Result<()> {
+    let mut writer = BufWriter::new(file);
+    for buf in tee_rx {
+        writer.write_all(&buf)?;
+    }
+    writer.flush()
+}
+
+fn hash_loop(
+    algorithms: &[Algorithm],
+    hash_rx: Receiver<Arc<Vec<u8>>>,
+) -> Vec<(Algorithm, String)> {
+    let mut hashers: Vec<Hasher> = algorithms.iter().map(|&a| Hasher::new(a)).collect();
+    for buf in hash_rx {
+        for hasher in hashers.iter_mut() {
+            hasher.update(&buf);
+        }
+    }
+    algorithms
+        .iter()
+        .copied()
+        .zip(hashers.into_iter().map(Hasher::finish))
+        .collect()
+}
+
+fn write_buf(
+    writer: &mut Box<dyn Write>,
+    bucket: &mut Option<TokenBucket>,
+    control_rx: &Receiver<Control>,
+    mut buf: &[u8],
+) -> Result<()> {
+    while !buf.is_empty() {
+        //a new rate from `pipeviewer -R` applies to the next piece, 0 lifts the limit
+        for control in control_rx.try_iter() {
+            if let Control::RateLimit(rate) = control {
+                *bucket = TokenBucket::limit(rate);
+            }
+        }
+        match bucket {
+            None => return writer.write_all(buf),
+            Some(bucket) => {
+                //small pieces, flushed right away, give a smooth rate
+                let (piece, rest) = buf.split_at(bucket.burst().min(buf.len()));
+                bucket.take(piece.len());
+                writer.write_all(piece)?;
+                writer.flush()?;
+                buf = rest;
             }
-            return Err(e);
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{write_loop, WriteOptions};
+    use crate::hash::Algorithm;
+    use crossbeam::channel::{bounded, unbounded};
+    use std::{env, fs, process};
+
+    #[test]
+    fn test_tee_and_hash() {
+        let dir = env::temp_dir().join(format!("pipeviewer-write-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        let options = WriteOptions {
+            outfile: path("out"),
+            tees: vec![path("copy1"), path("copy2")],
+            hashes: vec![Algorithm::Md5, Algorithm::Sha256],
+            rate_limit: None,
+        };
+        let (write_tx, write_rx) = bounded(1);
+        let (_control_tx, control_rx) = unbounded();
+        let (stats_tx, stats_rx) = unbounded();
+        let handle =
+            std::thread::spawn(move || write_loop(options, control_rx, write_rx, stats_tx));
+        for buf in [b"hello ".to_vec(), b"world".to_vec(), vec![]] {
+            write_tx.send(buf).unwrap();
+        }
+        let digests = handle.join().unwrap().unwrap();
+        assert_eq!(
+            digests,
+            vec![
+                (
+                    Algorithm::Md5,
+                    "5eb63bbbe01eeed093cb22bb8f5acdc3".to_string()
+                ),
+                (
+                    Algorithm::Sha256,
+                    "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string()
+                ),
+            ]
+        );
+        for name in ["out", "copy1", "copy2"] {
+            assert_eq!(fs::read(dir.join(name)).unwrap(), b"hello world");
+        }
+        let written: usize = stats_rx.iter().map(|buf| buf.len()).sum();
+        assert_eq!(written, 11);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_zero_rate_is_no_limit() {
+        let dir = env::temp_dir().join(format!("pipeviewer-write-zero-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let options = WriteOptions {
+            outfile: dir.join("out").to_string_lossy().into_owned(),
+            tees: vec![],
+            hashes: vec![],
+            rate_limit: Some(0),
+        };
+        let (write_tx, write_rx) = bounded(1);
+        let (_control_tx, control_rx) = unbounded();
+        let (stats_tx, _stats_rx) = unbounded();
+        let handle =
+            std::thread::spawn(move || write_loop(options, control_rx, write_rx, stats_tx));
+        //would take a day at 1 byte per second
+        for buf in [vec![b'x'; 100_000], vec![]] {
+            write_tx.send(buf).unwrap();
+        }
+        handle.join().unwrap().unwrap();
+        assert_eq!(fs::read(dir.join("out")).unwrap().len(), 100_000);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A token bucket: tokens (bytes) flow in at `rate` per second, up to a small
+/// burst, and writing has to spend them first.
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_instant: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: u64) -> Self {
+        assert!(rate > 0, "a rate of 0 is no limit, see TokenBucket::limit");
+        let rate = rate as f64;
+        Self {
+            rate,
+            capacity: (rate / 10.0).max(1.0), //at most a tenth of a second in one burst
+            tokens: 0.0,
+            last_instant: Instant::now(),
+        }
+    }
+
+    /// The bucket for a `-L RATE`, where 0 means no limit, both on the
+    /// command line and from `pipeviewer -R`.
+    pub fn limit(rate: u64) -> Option<Self> {
+        (rate > 0).then(|| Self::new(rate))
+    }
+
+    /// The largest piece worth writing at once.
+    pub fn burst(&self) -> usize {
+        self.capacity as usize
+    }
+
+    /// Spends `amount` tokens, sleeping until they are available.
+    pub fn take(&mut self, amount: usize) {
+        let wait = self.reserve(Instant::now(), amount);
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+
+    /// Spends `amount` tokens at `now` and returns how long to wait before using them.
+    /// The bucket may go into debt, which the following refills pay back.
+    pub fn reserve(&mut self, now: Instant, amount: usize) -> Duration {
+        let elapsed = now
+            .saturating_duration_since(self.last_instant)
+            .as_secs_f64();
+        self.last_instant = now;
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity) - amount as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokenBucket;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_reserve() {
+        let mut bucket = TokenBucket::new(1000);
+        let start = bucket.last_instant;
+        assert_eq!(bucket.burst(), 100);
+        //empty at first
+        assert_eq!(bucket.reserve(start, 100), Duration::from_millis(100));
+        //the debt is paid back after waiting
+        let now = start + Duration::from_millis(100);
+        assert_eq!(bucket.reserve(now, 0), Duration::ZERO);
+        let now = now + Duration::from_millis(50);
+        assert_eq!(bucket.reserve(now, 50), Duration::ZERO);
+        assert_eq!(bucket.reserve(now, 50), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_zero_is_no_limit() {
+        assert!(TokenBucket::limit(0).is_none());
+        assert_eq!(TokenBucket::limit(10).unwrap().rate, 10.0);
+    }
+
+    #[test]
+    fn test_burst_is_capped() {
+        let mut bucket = TokenBucket::new(1000);
+        //a long pause only refills up to the capacity
+        let now = Instant::now() + Duration::from_secs(60);
+        assert_eq!(bucket.reserve(now, 100), Duration::ZERO);
+        assert_eq!(bucket.reserve(now, 100), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_shapes_rate() {
+        let mut bucket = TokenBucket::new(1000);
+        let start = bucket.last_instant;
+        let mut now = start;
+        //1000 pieces of 10 bytes take 10 seconds at 1000 bytes per second
+        for _ in 0..1000 {
+            now += bucket.reserve(now, 10);
+        }
+        let secs = (now - start).as_secs_f64();
+        assert!((secs - 10.0).abs() < 0.01, "{}", secs);
+    }
+}
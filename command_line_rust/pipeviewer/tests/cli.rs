@@ -0,0 +1,163 @@
+use assert_cmd::Command;
+use pipeviewer::hash::{Algorithm, Hasher};
+use predicates::prelude::*;
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{self, Child, Stdio};
+use std::time::{Duration, Instant};
+
+const PRG: &str = env!("CARGO_BIN_EXE_pipeviewer");
+
+// --------------------------------------------------
+#[test]
+fn tee_and_hash_outlive_a_closed_stdout() {
+    let dir = tempfile::tempdir().unwrap();
+    let copy = dir.path().join("copy");
+    let data: Vec<u8> = (0..4_000_000u32).map(|i| (i % 251) as u8).collect();
+    let mut child = process::Command::new(PRG)
+        .args(["-s", "--hash", "md5", "--tee"])
+        .arg(&copy)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    //the reader of stdout goes away before the stream ends
+    drop(child.stdout.take());
+    child.stdin.take().unwrap().write_all(&data).unwrap();
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    assert_eq!(fs::read(&copy).unwrap(), data);
+    let stderr = String::from_utf8(out.stderr).unwrap();
+    let mut hasher = Hasher::new(Algorithm::Md5);
+    hasher.update(&data);
+    let digest = hasher.finish();
+    assert!(stderr.contains(&digest), "{}", stderr);
+}
+
+// --------------------------------------------------
+#[test]
+fn reads_the_files_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let (first, second) = (dir.path().join("first"), dir.path().join("second"));
+    fs::write(&first, "apple\n").unwrap();
+    fs::write(&second, "banana\n").unwrap();
+    //"-" is stdin, in between
+    Command::cargo_bin("pipeviewer")
+        .unwrap()
+        .arg(&first)
+        .arg("-")
+        .arg(&second)
+        .write_stdin("cherry\n")
+        .assert()
+        .success()
+        .stdout("apple\ncherry\nbanana\n");
+}
+
+// --------------------------------------------------
+#[test]
+fn rate_limit_in_line_mode() {
+    //2000 bytes at 1K per second, the lines do not change the rate
+    let input = "123456789\n".repeat(200);
+    let start = Instant::now();
+    Command::cargo_bin("pipeviewer")
+        .unwrap()
+        .args(["-l", "-L", "1K"])
+        .write_stdin(input.clone())
+        .assert()
+        .success()
+        .stdout(input)
+        .stderr(predicate::str::contains("total lines: 200"));
+    assert!(start.elapsed() >= Duration::from_millis(1500));
+}
+
+/// Starts a pipeviewer reading from a pipe, and waits until the first line
+/// went through it. It is rate limited so that its output is flushed as it goes.
+fn start(args: &[&str], runtime_dir: &std::path::Path) -> Child {
+    let mut child = process::Command::new(PRG)
+        .args(args)
+        .args(["-L", "1G"])
+        .env("XDG_RUNTIME_DIR", runtime_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    writeln!(child.stdin.as_mut().unwrap(), "ping").unwrap();
+    let mut line = String::new();
+    BufReader::new(child.stdout.as_mut().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    assert_eq!(line, "ping\n");
+    child
+}
+
+/// Closes the input of `child`, and returns what it printed on stderr.
+fn finish(mut child: Child) -> String {
+    drop(child.stdin.take());
+    let out = child.wait_with_output().unwrap();
+    assert!(out.status.success());
+    String::from_utf8(out.stderr).unwrap()
+}
+
+// --------------------------------------------------
+#[test]
+fn named_pipeviewers_stack() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = start(&["-N", "first"], dir.path());
+    //started by the same process, so in the same pipeline: one line lower
+    Command::cargo_bin("pipeviewer")
+        .unwrap()
+        .args(["-N", "second"])
+        .write_stdin("pong\n")
+        .assert()
+        .success()
+        .stdout("pong\n")
+        .stderr(predicate::str::contains("\x1b[1B").and(predicate::str::contains("second")));
+    let stderr = finish(first);
+    assert!(stderr.contains("first") && !stderr.contains("\x1b[1B"), "{:?}", stderr);
+    //the last one to finish cleaned up
+    assert!(!env::temp_dir()
+        .join(format!("pipeviewer-{}", process::id()))
+        .exists());
+}
+
+// --------------------------------------------------
+#[test]
+fn remote_changes_a_listening_pipeviewer() {
+    let dir = tempfile::tempdir().unwrap();
+    let child = start(&["--listen", "-F", "%N %b"], dir.path());
+    let pid = child.id().to_string();
+    let socket = dir.path().join(format!("pipeviewer-{}.sock", pid));
+    assert!(socket.exists());
+    Command::cargo_bin("pipeviewer")
+        .unwrap()
+        .args(["-R", &pid, "-N", "renamed", "-L", "1M"])
+        .env("XDG_RUNTIME_DIR", dir.path())
+        .assert()
+        .success()
+        .stdout("");
+    assert!(finish(child).contains("renamed"));
+    assert!(!socket.exists());
+}
+
+// --------------------------------------------------
+#[test]
+fn remote_needs_listen() {
+    let dir = tempfile::tempdir().unwrap();
+    let child = start(&["-s"], dir.path());
+    let pid = child.id().to_string();
+    Command::cargo_bin("pipeviewer")
+        .unwrap()
+        .args(["-R", &pid, "-N", "renamed"])
+        .env("XDG_RUNTIME_DIR", dir.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "no running pipeviewer with PID {}",
+            pid
+        )));
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    finish(child);
+}
@@ -0,0 +1,352 @@
+use clap::{Arg, ArgAction, Command};
+use std::error::Error;
+
+type MyResult<T> = Result<T, Box<dyn Error>>;
+
+// the table is printed in this many columns, filled top to bottom
+const TABLE_COLUMNS: usize = 5;
+
+// abbreviations and names of 0..=32 and 127, which have no printable glyph
+const CONTROLS: [(&str, &str); 33] = [
+    ("NUL", "null"),
+    ("SOH", "start of heading"),
+    ("STX", "start of text"),
+    ("ETX", "end of text"),
+    ("EOT", "end of transmission"),
+    ("ENQ", "enquiry"),
+    ("ACK", "acknowledge"),
+    ("BEL", "bell"),
+    ("BS", "backspace"),
+    ("HT", "horizontal tab"),
+    ("LF", "line feed"),
+    ("VT", "vertical tab"),
+    ("FF", "form feed"),
+    ("CR", "carriage return"),
+    ("SO", "shift out"),
+    ("SI", "shift in"),
+    ("DLE", "data link escape"),
+    ("DC1", "device control 1"),
+    ("DC2", "device control 2"),
+    ("DC3", "device control 3"),
+    ("DC4", "device control 4"),
+    ("NAK", "negative acknowledge"),
+    ("SYN", "synchronous idle"),
+    ("ETB", "end of transmission block"),
+    ("CAN", "cancel"),
+    ("EM", "end of medium"),
+    ("SUB", "substitute"),
+    ("ESC", "escape"),
+    ("FS", "file separator"),
+    ("GS", "group separator"),
+    ("RS", "record separator"),
+    ("US", "unit separator"),
+    ("SP", "space"),
+];
+const DEL: (&str, &str) = ("DEL", "delete");
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Dec,
+    Hex,
+    Oct,
+    Bin,
+}
+
+#[derive(Debug)]
+pub struct Config {
+    all: bool,
+    formats: Vec<Format>,
+    chars: Vec<String>,
+    decimal: bool,
+    unicode: Option<String>,
+}
+
+// --------------------------------------------------
+pub fn get_args() -> MyResult<Config> {
+    let matches = Command::new("ascii")
+        .version("0.1.0")
+        .author("Zhang Tianwei <zhangtianwei1015@gmail.com>")
+        .about("ASCII table and character lookup")
+        .arg(
+            Arg::new("chars")
+                .value_name("CHAR")
+                .help("Characters to look up: A, 0x41, 0o101, 0b1000001 or U+0041")
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("decimal")
+                .long("decimal")
+                .short('d')
+                .help("Read the CHARs as decimal code points, 65 for A")
+                .requires("chars")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .short('a')
+                .help("Include the control characters 0-32")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .short('f')
+                .value_name("FORMAT")
+                .help("Columns of the table")
+                .value_parser(["dec", "hex", "oct", "bin"])
+                .value_delimiter(',')
+                .default_value("dec")
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("unicode")
+                .long("unicode")
+                .short('u')
+                .value_name("NAME|RANGE")
+                .help("Look up Unicode characters by name ('greek small') or range (U+0391..U+03A9)")
+                .conflicts_with("chars")
+                .action(ArgAction::Set)
+        )
+        .get_matches();
+
+    let formats = matches
+        .get_many::<String>("format")
+        .unwrap()
+        .map(|f| match f.as_str() {
+            "hex" => Format::Hex,
+            "oct" => Format::Oct,
+            "bin" => Format::Bin,
+            _ => Format::Dec,
+        })
+        .collect();
+    Ok(Config {
+        all: matches.get_flag("all"),
+        formats,
+        chars: matches
+            .get_many::<String>("chars")
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        decimal: matches.get_flag("decimal"),
+        unicode: matches.get_one::<String>("unicode").cloned(),
+    })
+}
+
+// --------------------------------------------------
+pub fn run(config: Config) -> MyResult<()> {
+    if let Some(query) = &config.unicode {
+        let chars = unicode_search(query);
+        if chars.is_empty() {
+            return Err(From::from(format!("No characters match \"{}\"", query)));
+        }
+        for c in chars {
+            println!("U+{:04X}\t{}\t{}", c as u32, glyph(c), name(c).unwrap_or_default());
+        }
+    } else if !config.chars.is_empty() {
+        let chars = config
+            .chars
+            .iter()
+            .map(|arg| {
+                parse_char(arg, config.decimal)
+                    .ok_or_else(|| format!("Invalid character \"{}\"", arg))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let blocks: Vec<String> = chars.into_iter().map(describe).collect();
+        println!("{}", blocks.join("\n\n"));
+    } else {
+        let start = if config.all { 0 } else { 33 };
+        for row in table(start, &config.formats) {
+            println!("{}", row);
+        }
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+// the characters start..=127 in TABLE_COLUMNS columns, filled top to bottom
+fn table(start: u32, formats: &[Format]) -> Vec<String> {
+    let vals: Vec<String> = (start..=127)
+        .map(|i| {
+            let nums: Vec<String> = formats.iter().map(|f| number(i, *f)).collect();
+            format!("{}: {}", nums.join(" "), glyph(char::from_u32(i).unwrap()))
+        })
+        .collect();
+    let rows = vals.len().div_ceil(TABLE_COLUMNS);
+    (0..rows)
+        .map(|r| {
+            (0..TABLE_COLUMNS)
+                .filter_map(|c| vals.get(c * rows + r))
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join("\t")
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+fn number(i: u32, format: Format) -> String {
+    match format {
+        Format::Dec => format!("{:3}", i),
+        Format::Hex => format!("{:02X}", i),
+        Format::Oct => format!("{:03o}", i),
+        Format::Bin => format!("{:07b}", i),
+    }
+}
+
+// --------------------------------------------------
+// the character itself, or its abbreviation if it has no glyph
+fn glyph(c: char) -> String {
+    match control(c) {
+        Some((abbr, _)) => abbr.to_string(),
+        None => c.to_string(),
+    }
+}
+
+// --------------------------------------------------
+fn control(c: char) -> Option<(&'static str, &'static str)> {
+    match c as u32 {
+        i @ 0..=32 => Some(CONTROLS[i as usize]),
+        127 => Some(DEL),
+        _ => None,
+    }
+}
+
+// --------------------------------------------------
+fn name(c: char) -> Option<String> {
+    match control(c) {
+        Some((_, name)) => Some(name.to_uppercase()),
+        None => unicode_names2::name(c).map(|n| n.to_string()),
+    }
+}
+
+// --------------------------------------------------
+fn describe(c: char) -> String {
+    let code = c as u32;
+    let utf8: Vec<String> = c
+        .to_string()
+        .bytes()
+        .map(|b| format!("{:02X}", b))
+        .collect();
+    [
+        format!("char   {}", glyph(c)),
+        format!("name   {}", name(c).unwrap_or_default()),
+        format!("code   U+{:04X}", code),
+        format!("dec    {}", code),
+        format!("hex    0x{:X}", code),
+        format!("oct    0o{:o}", code),
+        format!("bin    0b{:b}", code),
+        format!("utf-8  {}", utf8.join(" ")),
+    ]
+    .join("\n")
+}
+
+// --------------------------------------------------
+// a code point in decimal, or else a single character standing for itself
+// or a code point with a prefix, so that "1" is the digit and not U+0001
+fn parse_char(arg: &str, decimal: bool) -> Option<char> {
+    if decimal {
+        return arg.parse().ok().and_then(char::from_u32);
+    }
+    let mut chars = arg.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c);
+    }
+    let lower = arg.to_lowercase();
+    let code = if let Some(hex) = lower.strip_prefix("u+").or_else(|| lower.strip_prefix("0x")) {
+        u32::from_str_radix(hex, 16)
+    } else if let Some(oct) = lower.strip_prefix("0o") {
+        u32::from_str_radix(oct, 8)
+    } else if let Some(bin) = lower.strip_prefix("0b") {
+        u32::from_str_radix(bin, 2)
+    } else {
+        return None;
+    };
+    code.ok().and_then(char::from_u32)
+}
+
+// --------------------------------------------------
+// a range of code points "START..END" or "START-END", otherwise words of the name
+fn unicode_search(query: &str) -> Vec<char> {
+    let range = query
+        .split_once("..")
+        .or_else(|| query.split_once('-'))
+        .and_then(|(start, end)| Some((parse_char(start, false)?, parse_char(end, false)?)));
+    if let Some((start, end)) = range {
+        return (start..=end).collect();
+    }
+    let words: Vec<String> = query.split_whitespace().map(|w| w.to_uppercase()).collect();
+    if words.is_empty() {
+        return vec![];
+    }
+    (char::MIN..=char::MAX)
+        .filter(|&c| {
+            name(c).is_some_and(|name| {
+                let name_words: Vec<&str> = name.split([' ', '-']).collect();
+                words.iter().all(|w| name_words.contains(&w.as_str()))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{describe, name, parse_char, table, unicode_search, Format};
+
+    #[test]
+    fn test_parse_char() {
+        assert_eq!(parse_char("A", false), Some('A'));
+        assert_eq!(parse_char("1", false), Some('1'));
+        assert_eq!(parse_char("α", false), Some('α'));
+        assert_eq!(parse_char("65", false), None);
+        assert_eq!(parse_char("0x41", false), Some('A'));
+        assert_eq!(parse_char("0X41", false), Some('A'));
+        assert_eq!(parse_char("0o101", false), Some('A'));
+        assert_eq!(parse_char("0b1000001", false), Some('A'));
+        assert_eq!(parse_char("U+03B1", false), Some('α'));
+        assert_eq!(parse_char("0xD800", false), None);
+        assert_eq!(parse_char("AB", false), None);
+        assert_eq!(parse_char("", false), None);
+        assert_eq!(parse_char("65", true), Some('A'));
+        assert_eq!(parse_char("1", true), Some('\u{1}'));
+        assert_eq!(parse_char("A", true), None);
+    }
+
+    #[test]
+    fn test_name() {
+        assert_eq!(name('\0').unwrap(), "NULL");
+        assert_eq!(name(' ').unwrap(), "SPACE");
+        assert_eq!(name('\x7f').unwrap(), "DELETE");
+        assert_eq!(name('A').unwrap(), "LATIN CAPITAL LETTER A");
+    }
+
+    #[test]
+    fn test_table() {
+        let rows = table(33, &[Format::Dec]);
+        assert_eq!(rows.len(), 19);
+        assert_eq!(rows[0], " 33: !\t 52: 4\t 71: G\t 90: Z\t109: m");
+
+        let rows = table(0, &[Format::Dec, Format::Hex]);
+        assert_eq!(rows.len(), 26);
+        assert_eq!(rows[0], "  0 00: NUL\t 26 1A: SUB\t 52 34: 4\t 78 4E: N\t104 68: h");
+        assert_eq!(rows[25], " 25 19: EM\t 51 33: 3\t 77 4D: M\t103 67: g");
+    }
+
+    #[test]
+    fn test_describe() {
+        let text = describe('é');
+        assert!(text.contains("name   LATIN SMALL LETTER E WITH ACUTE"));
+        assert!(text.contains("dec    233"));
+        assert!(text.contains("utf-8  C3 A9"));
+    }
+
+    #[test]
+    fn test_unicode_search() {
+        let greek = unicode_search("greek small letter alpha");
+        assert!(greek.contains(&'α'));
+        assert!(!greek.contains(&'Α'));
+        assert_eq!(unicode_search("U+0391..U+0393"), vec!['Α', 'Β', 'Γ']);
+        assert_eq!(unicode_search("0x41-0x43"), vec!['A', 'B', 'C']);
+        assert!(unicode_search("no such character name").is_empty());
+    }
+}
@@ -14,3 +14,103 @@ fn runs() {
         .success()
         .stdout(expected);
 }
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) {
+    let expected = fs::read_to_string(expected_file).unwrap();
+    Command::cargo_bin(PRG)
+        .unwrap()
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected);
+}
+
+// --------------------------------------------------
+#[test]
+fn runs_all() {
+    run(&["--all"], "./tests/expected/ascii.all.txt");
+}
+
+// --------------------------------------------------
+#[test]
+fn runs_hex_bin() {
+    run(&["-f", "hex,bin"], "./tests/expected/ascii.hex_bin.txt");
+}
+
+// --------------------------------------------------
+#[test]
+fn lookup() {
+    run(&["A"], "./tests/expected/A.txt");
+    run(&["-d", "65"], "./tests/expected/A.txt");
+    run(&["0x41"], "./tests/expected/A.txt");
+    run(&["U+0041"], "./tests/expected/A.txt");
+}
+
+// --------------------------------------------------
+#[test]
+fn lookup_control() {
+    Command::cargo_bin(PRG)
+        .unwrap()
+        .arg("0")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("char   0\n"));
+    Command::cargo_bin(PRG)
+        .unwrap()
+        .arg("0x0")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("char   NUL\nname   NULL\n"));
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_char() {
+    Command::cargo_bin(PRG)
+        .unwrap()
+        .arg("0xZZ")
+        .assert()
+        .failure()
+        .stderr("Invalid character \"0xZZ\"\n");
+    //code points need a prefix, or --decimal
+    Command::cargo_bin(PRG)
+        .unwrap()
+        .arg("65")
+        .assert()
+        .failure()
+        .stderr("Invalid character \"65\"\n");
+}
+
+// --------------------------------------------------
+#[test]
+fn unicode_name() {
+    Command::cargo_bin(PRG)
+        .unwrap()
+        .args(["--unicode", "greek small letter omega"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("U+03C9\tω\tGREEK SMALL LETTER OMEGA\n"));
+}
+
+// --------------------------------------------------
+#[test]
+fn unicode_range() {
+    Command::cargo_bin(PRG)
+        .unwrap()
+        .args(["-u", "U+2190..U+2192"])
+        .assert()
+        .success()
+        .stdout("U+2190\t←\tLEFTWARDS ARROW\nU+2191\t↑\tUPWARDS ARROW\nU+2192\t→\tRIGHTWARDS ARROW\n");
+}
+
+// --------------------------------------------------
+#[test]
+fn unicode_no_match() {
+    Command::cargo_bin(PRG)
+        .unwrap()
+        .args(["-u", "xyzzy"])
+        .assert()
+        .failure()
+        .stderr("No characters match \"xyzzy\"\n");
+}
@@ -1,16 +1,34 @@
+mod template;
+
 use clap::{Command, Arg, ArgAction, value_parser};
-use rand::{distributions::Alphanumeric, Rng};
+use rand::{rngs::StdRng, SeedableRng};
 use std::error::Error;
 use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
+use std::thread;
 use thousands::Separable;
 
+pub use template::{Column, ColumnType, Template};
+
 type MyResult<T> = Result<T, Box<dyn Error>>;
 
+// lines are generated in chunks of this size, each with its own seed,
+// so the output does not depend on the number of threads
+const CHUNK_LINES: u64 = 10_000;
+
 #[derive(Debug)]
 pub struct Config {
     outfile: String,
-    lines: usize,
+    target: Target,
+    seed: Option<u64>,
+    template: Template,
+    threads: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Target {
+    Lines(u64),
+    Bytes(u64),
 }
 
 // --------------------------------------------------
@@ -26,7 +44,16 @@ pub fn get_args() -> MyResult<Config> {
                 .short('n')
                 .help("Number of lines")
                 .default_value("100000")
-                .value_parser(value_parser!(usize))
+                .value_parser(value_parser!(u64))
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("size")
+                .value_name("SIZE")
+                .long("size")
+                .short('s')
+                .help("Target size instead of a number of lines, e.g. 500K, 10M, 2G")
+                .conflicts_with("lines")
                 .action(ArgAction::Set)
         )
         .arg(
@@ -38,31 +65,141 @@ pub fn get_args() -> MyResult<Config> {
                 .default_value("out")
                 .action(ArgAction::Set)
         )
+        .arg(
+            Arg::new("seed")
+                .value_name("SEED")
+                .long("seed")
+                .help("Random seed, the same seed always produces the same file")
+                .value_parser(value_parser!(u64))
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("format")
+                .value_name("FORMAT")
+                .long("format")
+                .short('f')
+                .help("Output format")
+                .value_parser(["words", "csv", "syslog", "nginx", "jsonl"])
+                .default_value("words")
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("columns")
+                .value_name("NAME:TYPE")
+                .long("columns")
+                .short('c')
+                .help("CSV columns, TYPE is int, float, bool, word, text or date")
+                .value_delimiter(',')
+                .action(ArgAction::Append)
+        )
+        .arg(
+            Arg::new("threads")
+                .value_name("THREADS")
+                .long("threads")
+                .short('t')
+                .help("Number of generator threads [default: number of CPUs]")
+                .value_parser(value_parser!(usize))
+                .action(ArgAction::Set)
+        )
         .get_matches();
 
+    let target = match matches.get_one::<String>("size") {
+        Some(size) => Target::Bytes(parse_size(size)?),
+        None => Target::Lines(*matches.get_one("lines").unwrap()),
+    };
+    let columns = match matches.get_many::<String>("columns") {
+        Some(columns) => columns
+            .map(|c| c.parse::<Column>())
+            .collect::<Result<Vec<_>, _>>()?,
+        None => template::default_columns(),
+    };
+    let format = matches.get_one::<String>("format").unwrap();
+    if format != "csv" && matches.contains_id("columns") {
+        return Err(From::from("--columns requires --format csv"));
+    }
+    let threads = match matches.get_one::<usize>("threads") {
+        Some(0) => return Err(From::from("--threads must be greater than 0")),
+        Some(n) => *n,
+        None => thread::available_parallelism().map_or(1, |n| n.get()),
+    };
+
     Ok(Config {
-        lines: *(matches.get_one("lines")).unwrap(),
         outfile: matches.get_one::<String>("outfile").unwrap().to_string(),
+        target,
+        seed: matches.get_one::<u64>("seed").copied(),
+        template: Template::from_name(format, columns)?,
+        threads,
     })
 }
 
 
 // --------------------------------------------------
 pub fn run(config: Config) -> MyResult<()> {
-    let mut file = File::create(&config.outfile)?;
-    for _ in 0..config.lines {
-        let num_words = rand::thread_rng().gen_range(7..15);
-        let mut words = vec![];
-        for _ in 0..num_words {
-            words.push(random_string());
+    let mut file = BufWriter::new(File::create(&config.outfile)?);
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let mut lines = 0;
+    let mut bytes = 0;
+    let mut header_bytes = 0;
+
+    if let Some(header) = config.template.header() {
+        writeln!(file, "{}", header)?;
+        header_bytes = header.len() as u64 + 1;
+    }
+
+    let mut next_chunk = 0;
+    'outer: loop {
+        // the header alone may already fill the target size
+        if matches!(config.target, Target::Bytes(size) if header_bytes + bytes >= size) {
+            break;
+        }
+        let chunks = chunks_in_batch(config.target, next_chunk, config.threads);
+        if chunks == 0 {
+            break;
+        }
+        // generate a batch of chunks in parallel, then write them in order
+        let batch: Vec<Vec<u8>> = thread::scope(|s| {
+            let handles: Vec<_> = (next_chunk..next_chunk + chunks)
+                .map(|chunk| {
+                    let template = &config.template;
+                    let num_lines = chunk_lines(config.target, chunk);
+                    s.spawn(move || generate_chunk(template, seed, chunk, num_lines))
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        next_chunk += chunks;
+
+        for buf in batch {
+            let mut buf = &buf[..];
+            if let Target::Bytes(size) = config.target {
+                // only write whole lines that fit in the target size
+                if header_bytes + bytes + buf.len() as u64 >= size {
+                    let room = size.saturating_sub(header_bytes + bytes) as usize;
+                    let end = buf[..room].iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+                    buf = &buf[..end];
+                    file.write_all(buf)?;
+                    lines += buf.iter().filter(|&&b| b == b'\n').count() as u64;
+                    bytes += buf.len() as u64;
+                    break 'outer;
+                }
+            }
+            file.write_all(buf)?;
+            lines += buf.iter().filter(|&&b| b == b'\n').count() as u64;
+            bytes += buf.len() as u64;
         }
-        writeln!(file, "{}", words.join(" "))?;
     }
+    file.flush()?;
 
     println!(
-        "Done, wrote {} line{} to \"{}\".",
-        config.lines.separate_with_commas(),
-        if config.lines == 1 { "" } else { "s" },
+        "Done, wrote {} line{} ({} bytes){} to \"{}\".",
+        lines.separate_with_commas(),
+        if lines == 1 { "" } else { "s" },
+        bytes.separate_with_commas(),
+        if header_bytes > 0 {
+            format!(" after a {}-byte header", header_bytes)
+        } else {
+            String::new()
+        },
         config.outfile
     );
 
@@ -70,11 +207,99 @@ pub fn run(config: Config) -> MyResult<()> {
 }
 
 // --------------------------------------------------
-fn random_string() -> String {
-    let length = rand::thread_rng().gen_range(2..8);
-    rand::thread_rng()
-        .sample_iter(&Alphanumeric)
-        .take(length)
-        .map(char::from)
-        .collect()
+fn chunks_in_batch(target: Target, next_chunk: u64, threads: usize) -> u64 {
+    match target {
+        Target::Lines(lines) => {
+            let total = lines.div_ceil(CHUNK_LINES);
+            total.saturating_sub(next_chunk).min(threads as u64)
+        }
+        Target::Bytes(0) => 0,
+        Target::Bytes(_) => threads as u64,
+    }
+}
+
+// --------------------------------------------------
+fn chunk_lines(target: Target, chunk: u64) -> u64 {
+    match target {
+        Target::Lines(lines) => (lines - chunk * CHUNK_LINES).min(CHUNK_LINES),
+        Target::Bytes(_) => CHUNK_LINES,
+    }
+}
+
+// --------------------------------------------------
+fn generate_chunk(template: &Template, seed: u64, chunk: u64, num_lines: u64) -> Vec<u8> {
+    let mut rng = StdRng::seed_from_u64(chunk_seed(seed, chunk));
+    let mut out = Vec::with_capacity(num_lines as usize * 64);
+    let first = chunk * CHUNK_LINES;
+    for index in first..first + num_lines {
+        template.write_line(&mut rng, index, &mut out);
+    }
+    out
+}
+
+// --------------------------------------------------
+// splitmix64, so that neighbouring chunks and seeds get unrelated streams
+fn chunk_seed(seed: u64, chunk: u64) -> u64 {
+    let mut z = seed.wrapping_add(chunk.wrapping_add(1).wrapping_mul(0x9e3779b97f4a7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+// --------------------------------------------------
+fn parse_size(size: &str) -> MyResult<u64> {
+    let err = || format!("Invalid --size \"{}\"", size);
+    let upper = size.trim().to_uppercase();
+    let number = upper.trim_end_matches("IB").trim_end_matches('B');
+    let (digits, multiplier) = match number.chars().last() {
+        Some('K') => (&number[..number.len() - 1], 1u64 << 10),
+        Some('M') => (&number[..number.len() - 1], 1u64 << 20),
+        Some('G') => (&number[..number.len() - 1], 1u64 << 30),
+        Some('T') => (&number[..number.len() - 1], 1u64 << 40),
+        _ => (number, 1),
+    };
+    let value: f64 = digits.parse().map_err(|_| err())?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(From::from(err()));
+    }
+    Ok((value * multiplier as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chunk_lines, chunks_in_batch, generate_chunk, parse_size, Target, Template};
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("10K").unwrap(), 10 * 1024);
+        assert_eq!(parse_size("2g").unwrap(), 2 * 1024 * 1024 * 1024);
+        assert_eq!(parse_size("1.5M").unwrap(), 3 * 512 * 1024);
+        assert_eq!(parse_size("3MiB").unwrap(), 3 * 1024 * 1024);
+        assert_eq!(parse_size("1KB").unwrap(), 1024);
+        assert!(parse_size("").is_err());
+        assert!(parse_size("lots").is_err());
+        assert!(parse_size("-1M").is_err());
+    }
+
+    #[test]
+    fn test_chunks() {
+        let target = Target::Lines(25_000);
+        assert_eq!(chunks_in_batch(target, 0, 8), 3);
+        assert_eq!(chunks_in_batch(target, 0, 2), 2);
+        assert_eq!(chunks_in_batch(target, 2, 2), 1);
+        assert_eq!(chunks_in_batch(target, 3, 2), 0);
+        assert_eq!(chunk_lines(target, 0), 10_000);
+        assert_eq!(chunk_lines(target, 2), 5_000);
+        assert_eq!(chunks_in_batch(Target::Bytes(1), 100, 4), 4);
+    }
+
+    #[test]
+    fn test_generate_chunk() {
+        let a = generate_chunk(&Template::Words, 1, 0, 100);
+        assert_eq!(a.iter().filter(|&&b| b == b'\n').count(), 100);
+        assert_eq!(a, generate_chunk(&Template::Words, 1, 0, 100));
+        assert_ne!(a, generate_chunk(&Template::Words, 1, 1, 100));
+        assert_ne!(a, generate_chunk(&Template::Words, 2, 0, 100));
+    }
 }
@@ -0,0 +1,275 @@
+use chrono::{DateTime, Utc};
+use rand::{distributions::Alphanumeric, rngs::StdRng, Rng};
+use serde_json::json;
+use std::{io::Write, str::FromStr};
+
+// timestamps start here and advance one second per line, so logs look chronological
+const START_TIME: i64 = 1_672_531_200; // 2023-01-01T00:00:00Z
+
+const HOSTS: &[&str] = &["web01", "web02", "db01", "cache01", "worker03"];
+const APPS: &[&str] = &["sshd", "cron", "kernel", "systemd", "nginx", "postgres"];
+const LEVELS: &[&str] = &["DEBUG", "INFO", "INFO", "INFO", "WARN", "ERROR"];
+const METHODS: &[&str] = &["GET", "GET", "GET", "POST", "PUT", "DELETE"];
+const STATUSES: &[u16] = &[200, 200, 200, 200, 201, 204, 301, 304, 400, 403, 404, 500];
+const AGENTS: &[&str] = &[
+    "Mozilla/5.0 (X11; Linux x86_64; rv:109.0) Gecko/20100101 Firefox/118.0",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 Safari/605.1.15",
+    "curl/8.1.2",
+    "Wget/1.21.4",
+];
+
+/// The shape of the generated lines
+#[derive(Debug, Clone, PartialEq)]
+pub enum Template {
+    Words,
+    Csv(Vec<Column>),
+    Syslog,
+    Nginx,
+    JsonLines,
+}
+
+/// A typed CSV column, written as "name:type" on the command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub kind: ColumnType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnType {
+    Int,
+    Float,
+    Bool,
+    Word,
+    Text,
+    Date,
+}
+
+impl Template {
+    pub fn from_name(name: &str, columns: Vec<Column>) -> Result<Self, String> {
+        match name {
+            "words" => Ok(Template::Words),
+            "csv" => Ok(Template::Csv(columns)),
+            "syslog" => Ok(Template::Syslog),
+            "nginx" => Ok(Template::Nginx),
+            "jsonl" => Ok(Template::JsonLines),
+            _ => Err(format!("Invalid format \"{}\"", name)),
+        }
+    }
+
+    /// The first line of the output, if the format has one
+    pub fn header(&self) -> Option<String> {
+        match self {
+            Template::Csv(columns) => Some(
+                columns
+                    .iter()
+                    .map(|c| c.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Appends the line number `index` (without header) to `out`
+    pub fn write_line(&self, rng: &mut StdRng, index: u64, out: &mut Vec<u8>) {
+        let time = timestamp(index);
+        // writing into a Vec cannot fail
+        let _ = match self {
+            Template::Words => writeln!(out, "{}", random_words(rng, 7..15)),
+            Template::Csv(columns) => {
+                let fields: Vec<String> = columns
+                    .iter()
+                    .map(|c| random_field(rng, c.kind, index))
+                    .collect();
+                writeln!(out, "{}", fields.join(","))
+            }
+            Template::Syslog => writeln!(
+                out,
+                "{} {} {}[{}]: {}",
+                time.format("%b %e %H:%M:%S"),
+                pick(rng, HOSTS),
+                pick(rng, APPS),
+                rng.gen_range(100..32768),
+                random_words(rng, 3..12)
+            ),
+            Template::Nginx => writeln!(
+                out,
+                "{}.{}.{}.{} - - [{}] \"{} /{} HTTP/1.1\" {} {} \"-\" \"{}\"",
+                rng.gen_range(1..255),
+                rng.gen_range(0..255),
+                rng.gen_range(0..255),
+                rng.gen_range(1..255),
+                time.format("%d/%b/%Y:%H:%M:%S %z"),
+                pick(rng, METHODS),
+                random_path(rng),
+                pick(rng, STATUSES),
+                rng.gen_range(0..100_000),
+                pick(rng, AGENTS)
+            ),
+            Template::JsonLines => writeln!(
+                out,
+                "{}",
+                json!({
+                    "id": index,
+                    "timestamp": time.to_rfc3339(),
+                    "level": pick(rng, LEVELS),
+                    "host": pick(rng, HOSTS),
+                    "user": random_string(rng),
+                    "latency_ms": (rng.gen_range(0.0..2000.0_f64) * 100.0).round() / 100.0,
+                    "message": random_words(rng, 3..12),
+                })
+            ),
+        };
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, kind) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid column \"{}\", expected NAME:TYPE", s))?;
+        let kind = match kind {
+            "int" => ColumnType::Int,
+            "float" => ColumnType::Float,
+            "bool" => ColumnType::Bool,
+            "word" => ColumnType::Word,
+            "text" => ColumnType::Text,
+            "date" => ColumnType::Date,
+            _ => return Err(format!("Invalid column type \"{}\"", kind)),
+        };
+        if name.is_empty() {
+            return Err(format!("Invalid column \"{}\", missing name", s));
+        }
+        Ok(Column { name: name.to_string(), kind })
+    }
+}
+
+/// The columns used when --format csv is given without --columns
+pub fn default_columns() -> Vec<Column> {
+    ["id:int", "name:word", "score:float", "active:bool", "created:date", "comment:text"]
+        .iter()
+        .map(|c| c.parse().unwrap())
+        .collect()
+}
+
+fn random_field(rng: &mut StdRng, kind: ColumnType, index: u64) -> String {
+    match kind {
+        ColumnType::Int => rng.gen_range(0..1_000_000).to_string(),
+        ColumnType::Float => format!("{:.3}", rng.gen_range(-1000.0..1000.0_f64)),
+        ColumnType::Bool => rng.gen_bool(0.5).to_string(),
+        ColumnType::Word => random_string(rng),
+        // quoted, since it contains spaces
+        ColumnType::Text => format!("\"{}\"", random_words(rng, 2..8)),
+        ColumnType::Date => timestamp(index).format("%Y-%m-%d").to_string(),
+    }
+}
+
+fn timestamp(index: u64) -> DateTime<Utc> {
+    DateTime::from_timestamp(START_TIME + index as i64, 0).unwrap()
+}
+
+fn pick<T: Copy>(rng: &mut StdRng, choices: &[T]) -> T {
+    choices[rng.gen_range(0..choices.len())]
+}
+
+fn random_path(rng: &mut StdRng) -> String {
+    let depth = rng.gen_range(1..4);
+    (0..depth)
+        .map(|_| random_string(rng).to_lowercase())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn random_words(rng: &mut StdRng, num_words: std::ops::Range<usize>) -> String {
+    let num_words = rng.gen_range(num_words);
+    (0..num_words)
+        .map(|_| random_string(rng))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// --------------------------------------------------
+fn random_string(rng: &mut StdRng) -> String {
+    let length = rng.gen_range(2..8);
+    rng.sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_columns, Column, ColumnType, Template};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn lines(template: &Template, seed: u64, count: u64) -> String {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut out = vec![];
+        for i in 0..count {
+            template.write_line(&mut rng, i, &mut out);
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_column() {
+        assert_eq!(
+            "price:float".parse::<Column>(),
+            Ok(Column { name: "price".to_string(), kind: ColumnType::Float })
+        );
+        assert!("price".parse::<Column>().is_err());
+        assert!("price:money".parse::<Column>().is_err());
+        assert!(":int".parse::<Column>().is_err());
+    }
+
+    #[test]
+    fn test_csv() {
+        let template = Template::Csv(default_columns());
+        assert_eq!(
+            template.header().unwrap(),
+            "id,name,score,active,created,comment"
+        );
+        let out = lines(&template, 1, 10);
+        for line in out.lines() {
+            let fields: Vec<&str> = line.splitn(6, ',').collect();
+            assert_eq!(fields.len(), 6);
+            assert!(fields[0].parse::<u64>().is_ok());
+            assert!(fields[2].parse::<f64>().is_ok());
+            assert!(fields[3].parse::<bool>().is_ok());
+            assert!(fields[4].starts_with("2023-01-01"));
+            assert!(fields[5].starts_with('"') && fields[5].ends_with('"'));
+        }
+    }
+
+    #[test]
+    fn test_logs() {
+        let out = lines(&Template::Syslog, 1, 3);
+        assert!(out.lines().all(|l| l.starts_with("Jan  1 00:00:0")));
+
+        let out = lines(&Template::Nginx, 1, 3);
+        assert!(out
+            .lines()
+            .all(|l| l.contains(" - - [01/Jan/2023:00:00:0") && l.contains(" HTTP/1.1\" ")));
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let out = lines(&Template::JsonLines, 1, 3);
+        for (i, line) in out.lines().enumerate() {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(value["id"], i as u64);
+            assert!(value["message"].is_string());
+        }
+    }
+
+    #[test]
+    fn test_seeded() {
+        for template in [Template::Words, Template::Syslog, Template::Nginx, Template::JsonLines] {
+            assert_eq!(lines(&template, 42, 20), lines(&template, 42, 20));
+            assert_ne!(lines(&template, 42, 20), lines(&template, 43, 20));
+        }
+    }
+}
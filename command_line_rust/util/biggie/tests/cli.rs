@@ -0,0 +1,38 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+const PRG: &str = "biggie";
+
+// --------------------------------------------------
+#[test]
+fn size_smaller_than_header() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out.csv");
+    Command::cargo_bin(PRG)
+        .unwrap()
+        .args(["--format", "csv", "--size", "10", "--seed", "1", "-o"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("wrote 0 lines (0 bytes) after a"));
+    // only the header, no partial line
+    let contents = fs::read_to_string(&out).unwrap();
+    assert_eq!(contents.lines().count(), 1);
+}
+
+// --------------------------------------------------
+#[test]
+fn size_counts_the_header() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("out.csv");
+    Command::cargo_bin(PRG)
+        .unwrap()
+        .args(["--format", "csv", "--size", "1K", "--seed", "1", "-o"])
+        .arg(&out)
+        .assert()
+        .success();
+    let contents = fs::read_to_string(&out).unwrap();
+    assert!(contents.len() <= 1024 && contents.len() > 900);
+    assert!(contents.ends_with('\n'));
+}
@@ -0,0 +1,80 @@
+//Compares the sorts on the shapes real data comes in.
+//cargo bench -- random/ to only run one distribution
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use l2_sorting::b_rand::RandGen;
+use l2_sorting::*;
+
+//bubble and insertion sort only get the small size
+const SMALL: usize = 1_000;
+const LARGE: usize = 100_000;
+
+fn distributions(n: usize) -> Vec<(&'static str, Vec<u64>)> {
+    let mut r = RandGen::new(n);
+    let random: Vec<u64> = (0..n).map(|_| r.next_v(1 << 30) as u64).collect();
+    let sorted: Vec<u64> = (0..n as u64).collect();
+    let reversed: Vec<u64> = sorted.iter().rev().cloned().collect();
+    let few_unique: Vec<u64> = (0..n).map(|_| r.next_v(10) as u64).collect();
+    //sorted, with one in a hundred elements swapped somewhere else
+    let mut nearly_sorted = sorted.clone();
+    for _ in 0..n / 100 {
+        let (a, b) = (r.next_v(n), r.next_v(n));
+        nearly_sorted.swap(a, b);
+    }
+    //lots of short ascending runs
+    let sawtooth: Vec<u64> = (0..n as u64).map(|i| i % 100).collect();
+    vec![
+        ("random", random),
+        ("sorted", sorted),
+        ("reversed", reversed),
+        ("few_unique", few_unique),
+        ("nearly_sorted", nearly_sorted),
+        ("sawtooth", sawtooth),
+    ]
+}
+
+type SliceSort = fn(&mut [u64]);
+
+fn slice_sorts(n: usize) -> Vec<(&'static str, SliceSort)> {
+    let mut sorts: Vec<(&'static str, SliceSort)> = vec![
+        ("heap_sort", heap_sort),
+        ("intro_sort", intro_sort),
+        ("radix_sort", radix_sort),
+        ("std_sort", |v| v.sort()),
+        ("std_sort_unstable", |v| v.sort_unstable()),
+    ];
+    sorts.push(("quick_sort_rayon", quick_sort_rayon));
+    sorts.push(("threaded_quick_sort", threaded_quick_sort));
+    sorts.push(("quick_sort", quick_sort));
+    if n <= SMALL {
+        sorts.push(("bubble_sort", bubble_sort));
+        sorts.push(("insertion_sort", insertion_sort));
+    }
+    sorts
+}
+
+fn bench_sorts(c: &mut Criterion) {
+    for n in [SMALL, LARGE] {
+        for (shape, input) in distributions(n) {
+            let mut group = c.benchmark_group(shape);
+            if n >= LARGE {
+                group.sample_size(10);
+            }
+            for (name, sort) in slice_sorts(n) {
+                group.bench_with_input(BenchmarkId::new(name, n), &input, |b, input| {
+                    b.iter_batched_ref(|| input.clone(), |v| sort(v), BatchSize::LargeInput)
+                });
+            }
+            //the merge sorts take the Vec and hand back a new one
+            group.bench_with_input(BenchmarkId::new("merge_sort", n), &input, |b, input| {
+                b.iter_batched(|| input.clone(), merge_sort, BatchSize::LargeInput)
+            });
+            group.bench_with_input(BenchmarkId::new("tim_sort", n), &input, |b, input| {
+                b.iter_batched(|| input.clone(), tim_sort, BatchSize::LargeInput)
+            });
+            group.finish();
+        }
+    }
+}
+
+criterion_group!(benches, bench_sorts);
+criterion_main!(benches);
@@ -0,0 +1,177 @@
+use clap::{Arg, ArgAction, Command};
+use l2_sorting::external_sort::{external_sort, SortOptions};
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+use tempfile::NamedTempFile;
+
+type MyResult<T> = Result<T, Box<dyn Error>>;
+
+#[derive(Debug)]
+struct Config {
+    in_file: String,
+    out_file: Option<String>,
+    options: SortOptions,
+}
+
+fn main() {
+    if let Err(e) = get_args().and_then(run) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(config: Config) -> MyResult<()> {
+    let input = open(&config.in_file).map_err(|e| format!("{}: {}", config.in_file, e))?;
+    match &config.out_file {
+        Some(out_name) => {
+            //write next to the output and rename it at the end,
+            //so the output can be the input, like sort -o
+            let dir = Path::new(out_name).parent().filter(|dir| !dir.as_os_str().is_empty());
+            let out_file = NamedTempFile::new_in(dir.unwrap_or(Path::new(".")))?;
+            external_sort(input, out_file.as_file(), &config.options)?;
+            out_file.persist(out_name).map_err(|e| format!("{}: {}", out_name, e))?;
+        }
+        None => {
+            external_sort(input, io::stdout().lock(), &config.options)?;
+        }
+    }
+    Ok(())
+}
+
+fn get_args() -> MyResult<Config> {
+    let matches = Command::new("sortr")
+        .version("0.1.0")
+        .author("Zhang Tianwei <zhangtianwei1015@gmail.com>")
+        .about("Rust sort, for files bigger than memory")
+        .arg(
+            Arg::new("in_file")
+                .value_name("IN_FILE")
+                .help("Input file")
+                .default_value("-")
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("out_file")
+                .value_name("OUT_FILE")
+                .short('o')
+                .long("output")
+                .help("Write to OUT_FILE instead of stdout, it may be the input")
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("key")
+                .value_name("FIELD")
+                .short('k')
+                .long("key")
+                .help("Sort by field FIELD, from 1, instead of the whole line")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("separator")
+                .value_name("SEP")
+                .short('t')
+                .long("field-separator")
+                .help("Fields are separated by SEP instead of blanks")
+                .value_parser(parse_separator)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("numeric")
+                .short('n')
+                .long("numeric-sort")
+                .help("Compare the keys as numbers")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("reverse")
+                .short('r')
+                .long("reverse")
+                .help("Sort in reverse order")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("unique")
+                .short('u')
+                .long("unique")
+                .help("Output only the first of the lines with equal keys")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("zero")
+                .short('z')
+                .long("zero-terminated")
+                .help("Records end with a NUL byte instead of a newline")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("buffer_size")
+                .value_name("SIZE")
+                .short('S')
+                .long("buffer-size")
+                .help("Sort chunks of SIZE bytes in memory, with a K, M or G suffix")
+                .default_value("64M")
+                .value_parser(parse_size)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("temp_dir")
+                .value_name("DIR")
+                .short('T')
+                .long("temporary-directory")
+                .help("Put the sorted chunks in DIR instead of the system temp dir")
+                .value_parser(clap::value_parser!(PathBuf))
+                .action(ArgAction::Set)
+        )
+        .get_matches();
+    Ok(Config {
+        in_file: matches.get_one::<String>("in_file").unwrap().to_string(),
+        out_file: matches.get_one::<String>("out_file").map(String::from),
+        options: SortOptions {
+            chunk_size: *matches.get_one("buffer_size").unwrap(),
+            key: matches.get_one::<u64>("key").map(|&k| k as usize),
+            separator: matches.get_one("separator").copied(),
+            numeric: matches.get_flag("numeric"),
+            reverse: matches.get_flag("reverse"),
+            unique: matches.get_flag("unique"),
+            delimiter: if matches.get_flag("zero") { b'\0' } else { b'\n' },
+            temp_dir: matches.get_one::<PathBuf>("temp_dir").cloned(),
+        },
+    })
+}
+
+fn parse_separator(sep: &str) -> Result<u8, String> {
+    match sep.as_bytes() {
+        [b] => Ok(*b),
+        _ => Err(format!("the separator must be a single byte, not \"{}\"", sep)),
+    }
+}
+
+fn parse_size(size: &str) -> Result<usize, String> {
+    let (digits, unit) = match size.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => size.split_at(i),
+        None => (size, ""),
+    };
+    let scale = match unit.to_ascii_uppercase().as_str() {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        _ => return Err(format!("invalid size \"{}\"", size)),
+    };
+    match digits.parse::<usize>().ok().and_then(|n| n.checked_mul(scale)) {
+        Some(n) if n > 0 => Ok(n),
+        _ => Err(format!("invalid size \"{}\"", size)),
+    }
+}
+
+fn open(filename: &str) -> MyResult<Box<dyn BufRead>> {
+    match filename {
+        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
+        _ => Ok(Box::new(BufReader::new(File::open(filename)?)))
+    }
+}
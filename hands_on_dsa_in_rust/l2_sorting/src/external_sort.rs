@@ -0,0 +1,323 @@
+//External merge sort, for inputs bigger than memory
+//The input is read in chunks of about chunk_size bytes, each chunk is sorted
+//with quick_sort_rayon and spilled to a temp file as a sorted run, then the runs
+//are merged k ways, with a heap holding the next record of every run.
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::quick_sort_rayon_by;
+
+#[derive(Debug, Clone)]
+pub struct SortOptions {
+    pub chunk_size: usize,     //bytes of records sorted in memory at once
+    pub key: Option<usize>,    //sort by this field, from 1, instead of the whole record
+    pub separator: Option<u8>, //between fields, runs of blanks when None
+    pub numeric: bool,         //compare the keys as numbers
+    pub reverse: bool,
+    pub unique: bool,            //only the first of the records with equal keys
+    pub delimiter: u8,           //between records, b'\n' or b'\0'
+    pub temp_dir: Option<PathBuf>, //where the runs go, the system temp dir when None
+}
+
+impl Default for SortOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 64 * 1024 * 1024,
+            key: None,
+            separator: None,
+            numeric: false,
+            reverse: false,
+            unique: false,
+            delimiter: b'\n',
+            temp_dir: None,
+        }
+    }
+}
+
+//A record, without its delimiter, and the part of it that is the key
+struct Record {
+    line: Vec<u8>,
+    key: Range<usize>,
+    number: f64,
+}
+
+impl Record {
+    fn new(line: Vec<u8>, options: &SortOptions) -> Self {
+        let key = match options.key {
+            Some(field) => field_range(&line, field, options.separator),
+            None => 0..line.len(),
+        };
+        let number = if options.numeric { parse_number(&line[key.clone()]) } else { 0.0 };
+        Record { line, key, number }
+    }
+
+    fn key(&self) -> &[u8] {
+        &self.line[self.key.clone()]
+    }
+}
+
+//The bytes of field (from 1) in line, empty when there are fewer fields
+fn field_range(line: &[u8], field: usize, separator: Option<u8>) -> Range<usize> {
+    let mut ranges = Vec::new();
+    match separator {
+        Some(sep) => {
+            let mut start = 0;
+            for (i, &b) in line.iter().enumerate() {
+                if b == sep {
+                    ranges.push(start..i);
+                    start = i + 1;
+                }
+            }
+            ranges.push(start..line.len());
+        }
+        None => {
+            let mut start = None;
+            for (i, &b) in line.iter().enumerate() {
+                match (b.is_ascii_whitespace(), start) {
+                    (true, Some(s)) => {
+                        ranges.push(s..i);
+                        start = None;
+                    }
+                    (false, None) => start = Some(i),
+                    _ => {}
+                }
+            }
+            if let Some(s) = start {
+                ranges.push(s..line.len());
+            }
+        }
+    }
+    field
+        .checked_sub(1)
+        .and_then(|i| ranges.get(i).cloned())
+        .unwrap_or(line.len()..line.len())
+}
+
+//The number at the start of the key, like sort -n: 0 when there is none
+fn parse_number(key: &[u8]) -> f64 {
+    let text = String::from_utf8_lossy(key);
+    let text = text.trim_start();
+    let end = text
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && (c == '-' || c == '+'))))
+        .map_or(text.len(), |(i, _)| i);
+    text[..end].parse().unwrap_or(0.0)
+}
+
+//The order of the keys only, which is what unique looks at
+fn compare_keys(a: &Record, b: &Record, options: &SortOptions) -> Ordering {
+    let order = if options.numeric {
+        a.number.total_cmp(&b.number)
+    } else {
+        a.key().cmp(b.key())
+    };
+    if options.reverse { order.reverse() } else { order }
+}
+
+//Equal keys are ordered by the whole record, so the output does not depend on the chunks
+fn compare_records(a: &Record, b: &Record, options: &SortOptions) -> Ordering {
+    compare_keys(a, b, options).then_with(|| {
+        let order = a.line.cmp(&b.line);
+        if options.reverse { order.reverse() } else { order }
+    })
+}
+
+//Writes the records, skipping the ones with the same key as the last one with unique
+struct Output<'a, W: Write> {
+    out: W,
+    last: Option<Record>,
+    options: &'a SortOptions,
+}
+
+impl<'a, W: Write> Output<'a, W> {
+    fn new(out: W, options: &'a SortOptions) -> Self {
+        Output { out, last: None, options }
+    }
+
+    fn push(&mut self, record: Record) -> io::Result<()> {
+        if self.options.unique {
+            if let Some(last) = &self.last {
+                if compare_keys(last, &record, self.options) == Ordering::Equal {
+                    return Ok(());
+                }
+            }
+        }
+        self.out.write_all(&record.line)?;
+        self.out.write_all(&[self.options.delimiter])?;
+        if self.options.unique {
+            self.last = Some(record);
+        }
+        Ok(())
+    }
+}
+
+//The next record of a run, for the heap, the smallest first
+struct Head<'a> {
+    record: Record,
+    run: usize,
+    options: &'a SortOptions,
+}
+
+impl PartialEq for Head<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Head<'_> {}
+
+impl PartialOrd for Head<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Head<'_> {
+    //BinaryHeap is a max heap, so this is backwards
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_records(&other.record, &self.record, self.options).then(other.run.cmp(&self.run))
+    }
+}
+
+fn read_record<R: BufRead>(input: &mut R, delimiter: u8) -> io::Result<Option<Vec<u8>>> {
+    let mut line = Vec::new();
+    if input.read_until(delimiter, &mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.last() == Some(&delimiter) {
+        line.pop();
+    }
+    Ok(Some(line))
+}
+
+//Sort the records of input into output
+//Returns the number of runs spilled to disk, 0 when it all fit in one chunk
+pub fn external_sort<R: BufRead, W: Write>(mut input: R, output: W, options: &SortOptions) -> io::Result<usize> {
+    let mut runs: Vec<BufReader<File>> = Vec::new();
+    loop {
+        let mut chunk = Vec::new();
+        let mut size = 0;
+        //at least one record per chunk
+        let chunk_size = options.chunk_size.max(1);
+        while size < chunk_size {
+            let Some(line) = read_record(&mut input, options.delimiter)? else {
+                break;
+            };
+            size += line.len() + 1;
+            chunk.push(Record::new(line, options));
+        }
+        let last = size < chunk_size;
+        quick_sort_rayon_by(&mut chunk, |a, b| compare_records(a, b, options));
+        if last && runs.is_empty() {
+            //it all fit in memory, no need for temp files
+            let mut output = Output::new(BufWriter::new(output), options);
+            for record in chunk {
+                output.push(record)?;
+            }
+            output.out.flush()?;
+            return Ok(0);
+        }
+        if !chunk.is_empty() {
+            runs.push(spill(chunk, options)?);
+        }
+        if last {
+            break;
+        }
+    }
+    let spilled = runs.len();
+    merge_runs(runs, output, options)?;
+    Ok(spilled)
+}
+
+//Write a sorted chunk to a temp file, and get it ready to be read back
+fn spill(chunk: Vec<Record>, options: &SortOptions) -> io::Result<BufReader<File>> {
+    let file = match &options.temp_dir {
+        Some(dir) => tempfile::tempfile_in(dir)?,
+        None => tempfile::tempfile()?,
+    };
+    let mut run = Output::new(BufWriter::new(file), options);
+    for record in chunk {
+        run.push(record)?;
+    }
+    let mut file = run.out.into_inner().map_err(|e| e.into_error())?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(BufReader::new(file))
+}
+
+fn merge_runs<W: Write>(mut runs: Vec<BufReader<File>>, output: W, options: &SortOptions) -> io::Result<()> {
+    let mut heap = BinaryHeap::with_capacity(runs.len());
+    for (run, input) in runs.iter_mut().enumerate() {
+        if let Some(line) = read_record(input, options.delimiter)? {
+            heap.push(Head { record: Record::new(line, options), run, options });
+        }
+    }
+    let mut output = Output::new(BufWriter::new(output), options);
+    while let Some(Head { record, run, .. }) = heap.pop() {
+        output.push(record)?;
+        if let Some(line) = read_record(&mut runs[run], options.delimiter)? {
+            heap.push(Head { record: Record::new(line, options), run, options });
+        }
+    }
+    output.out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sort(input: &str, options: &SortOptions) -> (String, usize) {
+        let mut out = Vec::new();
+        let runs = external_sort(input.as_bytes(), &mut out, options).unwrap();
+        (String::from_utf8(out).unwrap(), runs)
+    }
+
+    #[test]
+    fn test_in_memory() {
+        let (out, runs) = sort("pear\napple\nfig", &SortOptions::default());
+        assert_eq!(out, "apple\nfig\npear\n");
+        assert_eq!(runs, 0);
+    }
+
+    #[test]
+    fn test_spilled_runs() {
+        //records 0 to 999, out of order, merged from runs of about 100 bytes
+        let input: String = (0..1000).map(|i| format!("{:03}\n", (i * 7) % 1000)).collect();
+        let options = SortOptions { chunk_size: 100, ..Default::default() };
+        let (out, runs) = sort(&input, &options);
+        let expected: String = (0..1000).map(|i| format!("{:03}\n", i)).collect();
+        assert_eq!(out, expected);
+        assert_eq!(runs, 40);
+    }
+
+    #[test]
+    fn test_key_numeric_reverse_unique() {
+        let input = "b 10\na 9\nc 10\nd x\ne -2.5\n";
+        let by_number = SortOptions { key: Some(2), numeric: true, chunk_size: 8, ..Default::default() };
+        assert_eq!(sort(input, &by_number).0, "e -2.5\nd x\na 9\nb 10\nc 10\n");
+        let reverse = SortOptions { reverse: true, ..by_number.clone() };
+        assert_eq!(sort(input, &reverse).0, "c 10\nb 10\na 9\nd x\ne -2.5\n");
+        let unique = SortOptions { unique: true, ..by_number };
+        assert_eq!(sort(input, &unique).0, "e -2.5\nd x\na 9\nb 10\n");
+    }
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(field_range(b"  one two\tthree ", 2, None), 6..9);
+        assert_eq!(field_range(b"a,,c", 2, Some(b',')), 2..2);
+        assert_eq!(field_range(b"a,,c", 3, Some(b',')), 3..4);
+        assert_eq!(field_range(b"a b", 3, None), 3..3);
+        assert_eq!(parse_number(b" 12.5kg"), 12.5);
+        assert_eq!(parse_number(b"-3"), -3.0);
+        assert_eq!(parse_number(b"kg"), 0.0);
+    }
+
+    #[test]
+    fn test_nul_records() {
+        let options = SortOptions { delimiter: b'\0', chunk_size: 4, ..Default::default() };
+        assert_eq!(sort("b\nx\0a\0c\0", &options).0, "a\0b\nx\0c\0");
+    }
+}
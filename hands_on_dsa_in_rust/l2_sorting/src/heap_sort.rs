@@ -0,0 +1,60 @@
+//Heap sort: in place and O(n log n) whatever the input, but not stable
+use std::cmp::Ordering;
+
+use crate::partial_cmp;
+
+pub fn heap_sort<T: PartialOrd>(v: &mut [T]) {
+    heap_sort_by(v, partial_cmp)
+}
+
+pub fn heap_sort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
+    heap_sort_with(v, &mut compare)
+}
+
+pub fn heap_sort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    heap_sort_by(v, |a, b| partial_cmp(&key(a), &key(b)))
+}
+
+pub(crate) fn heap_sort_with<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut F) {
+    //turn the slice into a max heap, the children of i are at 2i+1 and 2i+2
+    for i in (0..v.len() / 2).rev() {
+        sift_down(v, i, compare);
+    }
+    //then move the biggest to the end, one at a time
+    for end in (1..v.len()).rev() {
+        v.swap(0, end);
+        sift_down(&mut v[..end], 0, compare);
+    }
+}
+
+//Move v[root] down until it is bigger than both its children
+fn sift_down<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut root: usize, compare: &mut F) {
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= v.len() {
+            return;
+        }
+        if child + 1 < v.len() && compare(&v[child], &v[child + 1]) == Ordering::Less {
+            child += 1;
+        }
+        if compare(&v[root], &v[child]) != Ordering::Less {
+            return;
+        }
+        v.swap(root, child);
+        root = child;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heap_sort() {
+        let mut v = vec![4,6,1,8,11,13,3,5,11,56,8,1];
+        heap_sort(&mut v);
+        assert_eq!(v, vec![1,1,3,4,5,6,8,8,11,11,13,56]);
+        heap_sort_by(&mut v, |a, b| b.cmp(a));
+        assert_eq!(v, vec![56,13,11,11,8,8,6,5,4,3,1,1]);
+    }
+}
@@ -0,0 +1,43 @@
+//Insertion sort: stable, in place, and quick on short or nearly sorted slices
+use std::cmp::Ordering;
+
+use crate::partial_cmp;
+
+pub fn insertion_sort<T: PartialOrd>(v: &mut [T]) {
+    insertion_sort_by(v, partial_cmp)
+}
+
+pub fn insertion_sort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
+    insertion_sort_with(v, &mut compare)
+}
+
+pub fn insertion_sort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    insertion_sort_by(v, |a, b| partial_cmp(&key(a), &key(b)))
+}
+
+pub(crate) fn insertion_sort_with<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut F) {
+    for i in 1..v.len() {
+        //move v[i] back until the one before it is not bigger
+        let mut j = i;
+        while j > 0 && compare(&v[j - 1], &v[j]) == Ordering::Greater {
+            v.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insertion_sort() {
+        let mut v = vec![4,6,1,8,11,13,3];
+        insertion_sort(&mut v);
+        assert_eq!(v, vec![1,3,4,6,8,11,13]);
+        //equal keys keep their order
+        let mut v = vec![(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')];
+        insertion_sort_by_key(&mut v, |p| p.0);
+        assert_eq!(v, vec![(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+    }
+}
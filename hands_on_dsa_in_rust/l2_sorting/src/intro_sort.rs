@@ -0,0 +1,120 @@
+//Introsort: quick sort with a median of three pivot,
+//that falls back to heap sort when it recurses too deep
+//and to insertion sort on short slices
+use std::cmp::Ordering;
+
+use crate::heap_sort::heap_sort_with;
+use crate::insertion_sort::insertion_sort_with;
+use crate::partial_cmp;
+
+//slices this short are sorted by insertion
+const INSERTION_LEN: usize = 16;
+
+pub fn intro_sort<T: PartialOrd>(v: &mut [T]) {
+    intro_sort_by(v, partial_cmp)
+}
+
+pub fn intro_sort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
+    //about 2 log2(n) levels, more means the pivots are going badly
+    let depth = 2 * (usize::BITS - v.len().leading_zeros());
+    intro_sort_with(v, depth, &mut compare)
+}
+
+pub fn intro_sort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    intro_sort_by(v, |a, b| partial_cmp(&key(a), &key(b)))
+}
+
+fn intro_sort_with<T, F: FnMut(&T, &T) -> Ordering>(mut v: &mut [T], mut depth: u32, compare: &mut F) {
+    loop {
+        if v.len() <= INSERTION_LEN {
+            insertion_sort_with(v, compare);
+            return;
+        }
+        if depth == 0 {
+            heap_sort_with(v, compare);
+            return;
+        }
+        depth -= 1;
+        let p = median_pivot(v, compare);
+        let (a, b) = v.split_at_mut(p);
+        let b = &mut b[1..];
+        //recurse into the smaller side and loop on the bigger one,
+        //so the stack never grows past log2(n)
+        if a.len() < b.len() {
+            intro_sort_with(a, depth, compare);
+            v = b;
+        } else {
+            intro_sort_with(b, depth, compare);
+            v = a;
+        }
+    }
+}
+
+//Put the median of the first, middle and last elements in its place,
+//with nothing bigger before it and nothing smaller after it,
+//return it's location
+//Elements equal to the pivot stop both scans, so they end up on both sides
+//and lots of duplicates do not make it degrade
+pub(crate) fn median_pivot<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut F) -> usize {
+    let (mid, last) = (v.len() / 2, v.len() - 1);
+    if compare(&v[mid], &v[0]) == Ordering::Less {
+        v.swap(0, mid);
+    }
+    if compare(&v[last], &v[mid]) == Ordering::Less {
+        v.swap(mid, last);
+        if compare(&v[mid], &v[0]) == Ordering::Less {
+            v.swap(0, mid);
+        }
+    }
+    v.swap(0, mid);
+
+    let (mut i, mut j) = (1, last);
+    loop {
+        while i <= j && compare(&v[i], &v[0]) == Ordering::Less {
+            i += 1;
+        }
+        while i <= j && compare(&v[j], &v[0]) == Ordering::Greater {
+            j -= 1;
+        }
+        if i >= j {
+            break;
+        }
+        v.swap(i, j);
+        i += 1;
+        j -= 1;
+    }
+    v.swap(0, j);
+    j
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_pivot() {
+        let mut v = vec![4,6,1,19,8,11,13,3];
+        //4, 8 and 3 are sampled
+        let p = median_pivot(&mut v, &mut i32::cmp);
+        assert_eq!(v[p], 4);
+        assert_eq!(p, 2);
+        for x in 0..v.len() {
+            assert!((v[x] < v[p]) == (x < p));
+        }
+    }
+
+    #[test]
+    fn test_intro_sort() {
+        //sorted, reversed and all equal inputs are the usual quick sort killers
+        let sorted: Vec<u32> = (0..1000).collect();
+        for mut v in [sorted.clone(), sorted.iter().rev().cloned().collect(), vec![7; 1000]] {
+            let mut expected = v.clone();
+            expected.sort();
+            intro_sort(&mut v);
+            assert_eq!(v, expected);
+        }
+        let mut v = vec![4,6,1,8,11,13,3];
+        intro_sort_by_key(&mut v, |x| std::cmp::Reverse(*x));
+        assert_eq!(v, vec![13,11,8,6,4,3,1]);
+    }
+}
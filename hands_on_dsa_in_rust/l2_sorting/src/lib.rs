@@ -1,34 +1,75 @@
-use std::fmt::Debug;
+use std::cmp::Ordering;
 
 pub mod b_rand;
+pub mod external_sort;
+pub mod heap_sort;
+pub mod insertion_sort;
+pub mod intro_sort;
+pub mod radix_sort;
+pub mod tim_sort;
 
-pub fn bubble_sort<T: PartialOrd + Debug>(v: &mut [T]) {
+pub use heap_sort::{heap_sort, heap_sort_by, heap_sort_by_key};
+pub use insertion_sort::{insertion_sort, insertion_sort_by, insertion_sort_by_key};
+pub use intro_sort::{intro_sort, intro_sort_by, intro_sort_by_key};
+pub use radix_sort::{radix_sort, radix_sort_by_key, RadixKey};
+pub use tim_sort::{tim_sort, tim_sort_by, tim_sort_by_key};
+
+//The order of two PartialOrd values,
+//values that can not be compared (like NaN) count as equal
+pub(crate) fn partial_cmp<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+pub fn bubble_sort<T: PartialOrd>(v: &mut [T]) {
+    bubble_sort_by(v, partial_cmp)
+}
+
+pub fn bubble_sort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
     for p in 0..v.len() {
         let mut sorted = true;
         for i in 0..(v.len() - 1 - p) {
-            if v[i] > v[i+1] {
+            if compare(&v[i], &v[i+1]) == Ordering::Greater {
                 v.swap(i, i+1);
                 sorted = false;
             }
         }
-        println!("{:?}", v);
         if sorted {
             return;
         }
     }
 }
 
-pub fn merge_sort<T: PartialOrd + Debug>(mut v: Vec<T>) -> Vec<T> {
-    println!("MS: {:?}", v);
+pub fn bubble_sort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    bubble_sort_by(v, |a, b| partial_cmp(&key(a), &key(b)))
+}
+
+pub fn merge_sort<T: PartialOrd>(v: Vec<T>) -> Vec<T> {
+    merge_sort_by(v, partial_cmp)
+}
+
+pub fn merge_sort_by<T, F: FnMut(&T, &T) -> Ordering>(v: Vec<T>, mut compare: F) -> Vec<T> {
+    merge_sort_with(v, &mut compare)
+}
+
+pub fn merge_sort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(v: Vec<T>, mut key: F) -> Vec<T> {
+    merge_sort_by(v, |a, b| partial_cmp(&key(a), &key(b)))
+}
+
+fn merge_sort_with<T, F: FnMut(&T, &T) -> Ordering>(mut v: Vec<T>, compare: &mut F) -> Vec<T> {
     if v.len() <= 1 {
         return v;
     } 
 
-    let mut res = Vec::with_capacity(v.len());
     let b = v.split_off(v.len() / 2);
-    let a = merge_sort(v);
-    let b = merge_sort(b);
+    let a = merge_sort_with(v, compare);
+    let b = merge_sort_with(b, compare);
+    merge(a, b, compare)
+}
 
+//Merge two sorted vecs into one,
+//on a tie the element of a comes first, which keeps the sort stable
+pub(crate) fn merge<T, F: FnMut(&T, &T) -> Ordering>(a: Vec<T>, b: Vec<T>, compare: &mut F) -> Vec<T> {
+    let mut res = Vec::with_capacity(a.len() + b.len());
     let mut a_it = a.into_iter();
     let mut b_it = b.into_iter();
     let mut a_peek = a_it.next();
@@ -38,7 +79,7 @@ pub fn merge_sort<T: PartialOrd + Debug>(mut v: Vec<T>) -> Vec<T> {
         match a_peek {
             Some(ref a_val) => match b_peek {
                 Some(ref b_val) => {
-                    if b_val < a_val {
+                    if compare(b_val, a_val) == Ordering::Less {
                         res.push(b_peek.take().unwrap());
                         b_peek = b_it.next();
                     } else {
@@ -61,20 +102,26 @@ pub fn merge_sort<T: PartialOrd + Debug>(mut v: Vec<T>) -> Vec<T> {
             }
         }
     }
-
-
 }
 
-//Move first element to the correct place
+//Move a random element to the correct place
 //Everything lower should be before it,
 //everything higher should be after it
 //return it's location
 pub fn pivot<T: PartialOrd>(v: &mut [T]) -> usize {
+    pivot_by(v, partial_cmp)
+}
+
+pub fn pivot_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) -> usize {
+    pivot_with(v, &mut compare)
+}
+
+fn pivot_with<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut F) -> usize {
     let mut p = b_rand::rand(v.len());
     v.swap(p, 0);
     p = 0;
     for i in 1..v.len() {
-        if v[i] < v[p] {
+        if compare(&v[i], &v[p]) == Ordering::Less {
             //move our pivot forward 1, and put this element before it
             v.swap(p + 1, i);
             v.swap(p, p + 1);
@@ -85,48 +132,102 @@ pub fn pivot<T: PartialOrd>(v: &mut [T]) -> usize {
 }
 
 pub fn quick_sort<T: PartialOrd>(v: &mut [T]) {
+    quick_sort_by(v, partial_cmp)
+}
+
+pub fn quick_sort_by<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], mut compare: F) {
+    quick_sort_with(v, &mut compare)
+}
+
+pub fn quick_sort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    quick_sort_by(v, |a, b| partial_cmp(&key(a), &key(b)))
+}
+
+fn quick_sort_with<T, F: FnMut(&T, &T) -> Ordering>(v: &mut [T], compare: &mut F) {
     if v.len() <= 1 {
         return;
     }
-    let p = pivot(v);
+    let p = pivot_with(v, compare);
     let (a, b) = v.split_at_mut(p);
-    quick_sort(a);
-    quick_sort(&mut b[1..]);
+    let (pivot, b) = b.split_first_mut().unwrap();
+    let equal = group_equal(b, pivot, compare);
+    quick_sort_with(a, compare);
+    quick_sort_with(&mut b[equal..], compare);
 }
 
-struct RawSend<T>(*mut [T]);
-unsafe impl<T> Send for RawSend<T> {} 
+//slices shorter than this are not worth a thread
+const THREAD_MIN_LEN: usize = 4096;
 
-pub fn threaded_quick_sort<T: 'static + PartialOrd + Debug + Send>(v: &mut [T]) {
-    if v.len() <= 1 {
+pub fn threaded_quick_sort<T: PartialOrd + Send>(v: &mut [T]) {
+    threaded_quick_sort_by(v, partial_cmp)
+}
+
+pub fn threaded_quick_sort_by<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(v: &mut [T], compare: F) {
+    //every level doubles the threads, stop at about two per core
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let depth = cores.next_power_of_two().trailing_zeros() + 1;
+    threaded_quick_sort_with(v, depth, &compare)
+}
+
+pub fn threaded_quick_sort_by_key<T: Send, K: PartialOrd, F: Fn(&T) -> K + Sync>(v: &mut [T], key: F) {
+    threaded_quick_sort_by(v, |a, b| partial_cmp(&key(a), &key(b)))
+}
+
+fn threaded_quick_sort_with<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(v: &mut [T], depth: u32, compare: &F) {
+    if depth == 0 || v.len() < THREAD_MIN_LEN {
+        //intro sort rather than quick sort, its stack stays small whatever the input
+        intro_sort_by(v, compare);
         return;
     }
-    let p = pivot(v);
-    println!("{:?}", v);
-
+    let p = pivot_with(v, &mut |a, b| compare(a, b));
     let (a, b) = v.split_at_mut(p);
-    let raw_a: *mut [T] = a as *mut [T];
-    let raw_s = RawSend(raw_a);
-    unsafe {
-        let handle = std::thread::spawn(move || {
-            let raw_s2 = raw_s;
-            threaded_quick_sort(&mut *raw_s2.0);
-        });
-        threaded_quick_sort(&mut b[1..]);
-        handle.join().ok();
-    }
+    let (pivot, b) = b.split_first_mut().unwrap();
+    let equal = group_equal(b, pivot, &mut |a, b| compare(a, b));
+    //the scope joins the thread before returning, so it can borrow a and compare
+    std::thread::scope(|s| {
+        s.spawn(|| threaded_quick_sort_with(a, depth - 1, compare));
+        threaded_quick_sort_with(&mut b[equal..], depth - 1, compare);
+    });
 }
 
 pub fn quick_sort_rayon<T: Send + PartialOrd>(v: &mut [T]) {
+    quick_sort_rayon_by(v, partial_cmp)
+}
+
+pub fn quick_sort_rayon_by<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(v: &mut [T], compare: F) {
+    quick_sort_rayon_with(v, &compare)
+}
+
+pub fn quick_sort_rayon_by_key<T: Send, K: PartialOrd, F: Fn(&T) -> K + Sync>(v: &mut [T], key: F) {
+    quick_sort_rayon_by(v, |a, b| partial_cmp(&key(a), &key(b)))
+}
+
+fn quick_sort_rayon_with<T: Send, F: Fn(&T, &T) -> Ordering + Sync>(v: &mut [T], compare: &F) {
     if v.len() <= 1 {
         return;
     }
-    let p = pivot(v);
+    let p = pivot_with(v, &mut |a, b| compare(a, b));
     let (a, b) = v.split_at_mut(p);
+    let (pivot, b) = b.split_first_mut().unwrap();
+    let equal = group_equal(b, pivot, &mut |a, b| compare(a, b));
     // put f2 on queue then start f1
     // if another thread is ready it will steal f2
     // this works recursively down the stack
-    rayon::join(|| quick_sort_rayon(a), || quick_sort_rayon(&mut b[1..]));
+    rayon::join(|| quick_sort_rayon_with(a, compare), || quick_sort_rayon_with(&mut b[equal..], compare));
+}
+
+//Move everything in b equal to the pivot to its front, it is in place already
+//return how many there are
+//without this lots of duplicates make the recursion about n deep
+fn group_equal<T, F: FnMut(&T, &T) -> Ordering>(b: &mut [T], pivot: &T, compare: &mut F) -> usize {
+    let mut equal = 0;
+    for i in 0..b.len() {
+        if compare(&b[i], pivot) == Ordering::Equal {
+            b.swap(equal, i);
+            equal += 1;
+        }
+    }
+    equal
 }
 
 pub fn fibonacci(n: i32) -> (i32, i32) {
@@ -181,4 +282,24 @@ mod tests {
         assert_eq!(v, vec![1,1,3,4,5,6,8,8,11,11,13,56]);
     }
 
+    #[test]
+    fn test_by_and_by_key() {
+        let mut v = vec![4,6,1,8,11,13,3];
+        quick_sort_by(&mut v, |a, b| b.cmp(a));
+        assert_eq!(v, vec![13,11,8,6,4,3,1]);
+        bubble_sort_by_key(&mut v, |x| x % 4);
+        assert_eq!(v, vec![8,4,13,1,6,11,3]);
+        quick_sort_rayon_by_key(&mut v, |x| *x);
+        assert_eq!(v, vec![1,3,4,6,8,11,13]);
+        threaded_quick_sort_by(&mut v, |a, b| b.cmp(a));
+        assert_eq!(v, vec![13,11,8,6,4,3,1]);
+        //borrowed data, which needed 'static before
+        let text = String::from("pear fig apple kiwi");
+        let mut words: Vec<&str> = text.split(' ').collect();
+        threaded_quick_sort_by_key(&mut words, |w| w.len());
+        assert_eq!(words[0], "fig");
+        let words = merge_sort_by_key(vec!["pear", "fig", "apple", "kiwi"], |w| w.len());
+        assert_eq!(words, vec!["fig", "pear", "kiwi", "apple"]);
+    }
+
 }
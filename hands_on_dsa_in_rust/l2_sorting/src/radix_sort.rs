@@ -0,0 +1,107 @@
+//LSD radix sort for integers: it sorts by one byte at a time,
+//from the lowest, with a stable counting sort. O(n) per byte
+//and no comparisons, which is why there is no radix_sort_by.
+use std::mem;
+
+//An integer as a u64 in the same order
+pub trait RadixKey: Copy {
+    fn radix_key(self) -> u64;
+}
+
+macro_rules! unsigned_key {
+    ($($t:ty),*) => {$(
+        impl RadixKey for $t {
+            fn radix_key(self) -> u64 {
+                self as u64
+            }
+        }
+    )*};
+}
+
+macro_rules! signed_key {
+    ($($t:ty),*) => {$(
+        impl RadixKey for $t {
+            //flipping the sign bit puts the negative numbers first
+            fn radix_key(self) -> u64 {
+                (self as i64 as u64) ^ (1 << 63)
+            }
+        }
+    )*};
+}
+
+unsigned_key!(u8, u16, u32, u64, usize);
+signed_key!(i8, i16, i32, i64, isize);
+
+pub fn radix_sort<T: RadixKey>(v: &mut [T]) {
+    radix_sort_by_key(v, |x| *x)
+}
+
+//key is called once per element, and equal keys keep their order
+pub fn radix_sort_by_key<T, K: RadixKey, F: FnMut(&T) -> K>(v: &mut [T], mut key: F) {
+    let keys: Vec<u64> = v.iter().map(|x| key(x).radix_key()).collect();
+    //bytes above the highest bit set in any key are all 0, no need to sort on them
+    let bits = keys.iter().fold(0, |acc, k| acc | k);
+    let bytes = (u64::BITS - bits.leading_zeros()).div_ceil(8);
+
+    //sort the indices rather than the elements, which may not be Copy
+    let mut order: Vec<usize> = (0..v.len()).collect();
+    let mut next = vec![0; v.len()];
+    for byte in 0..bytes {
+        let digit = |i: usize| ((keys[i] >> (byte * 8)) & 0xff) as usize;
+        //where each digit starts in the output
+        let mut starts = [0; 257];
+        for &i in &order {
+            starts[digit(i) + 1] += 1;
+        }
+        for d in 0..256 {
+            starts[d + 1] += starts[d];
+        }
+        for &i in &order {
+            let d = digit(i);
+            next[starts[d]] = i;
+            starts[d] += 1;
+        }
+        mem::swap(&mut order, &mut next);
+    }
+    apply_order(v, order);
+}
+
+//Rearrange v so v[k] is what was at v[order[k]],
+//following each cycle of the permutation
+fn apply_order<T>(v: &mut [T], mut order: Vec<usize>) {
+    for start in 0..v.len() {
+        let mut cur = start;
+        loop {
+            let from = order[cur];
+            //mark it done, so later cycles skip it
+            order[cur] = cur;
+            if from == start {
+                break;
+            }
+            v.swap(cur, from);
+            cur = from;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_radix_sort() {
+        let mut v = vec![4,-6,1,800,11,-13000,3,0,i32::MIN,i32::MAX];
+        radix_sort(&mut v);
+        assert_eq!(v, vec![i32::MIN,-13000,-6,0,1,3,4,11,800,i32::MAX]);
+        let mut v: Vec<u64> = vec![1 << 40, 5, 1 << 20, 0];
+        radix_sort(&mut v);
+        assert_eq!(v, vec![0, 5, 1 << 20, 1 << 40]);
+    }
+
+    #[test]
+    fn test_radix_sort_by_key() {
+        let mut v = vec!["pear", "fig", "apple", "kiwi"];
+        radix_sort_by_key(&mut v, |w| w.len());
+        assert_eq!(v, vec!["fig", "pear", "kiwi", "apple"]);
+    }
+}
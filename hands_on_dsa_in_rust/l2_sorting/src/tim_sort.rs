@@ -0,0 +1,118 @@
+//A TimSort-style merge sort: it finds the runs that are already sorted,
+//makes short ones at least MIN_RUN long with binary insertion,
+//and merges them keeping their lengths balanced. Stable, and close to O(n)
+//on data that is mostly in order.
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::mem;
+use std::vec::IntoIter;
+
+use crate::{merge, partial_cmp};
+
+const MIN_RUN: usize = 32;
+
+pub fn tim_sort<T: PartialOrd>(v: Vec<T>) -> Vec<T> {
+    tim_sort_by(v, partial_cmp)
+}
+
+pub fn tim_sort_by<T, F: FnMut(&T, &T) -> Ordering>(v: Vec<T>, mut compare: F) -> Vec<T> {
+    let mut runs: Vec<Vec<T>> = Vec::new();
+    let mut rest = v.into_iter().peekable();
+    while rest.peek().is_some() {
+        let run = next_run(&mut rest, &mut compare);
+        runs.push(run);
+        collapse(&mut runs, &mut compare);
+    }
+    while runs.len() > 1 {
+        let i = runs.len() - 2;
+        merge_at(&mut runs, i, &mut compare);
+    }
+    runs.pop().unwrap_or_default()
+}
+
+pub fn tim_sort_by_key<T, K: PartialOrd, F: FnMut(&T) -> K>(v: Vec<T>, mut key: F) -> Vec<T> {
+    tim_sort_by(v, |a, b| partial_cmp(&key(a), &key(b)))
+}
+
+//Take the next run off rest: ascending, or strictly descending and then reversed
+//(strictly, or equal elements would swap places)
+fn next_run<T, F: FnMut(&T, &T) -> Ordering>(rest: &mut Peekable<IntoIter<T>>, compare: &mut F) -> Vec<T> {
+    let mut run = Vec::with_capacity(MIN_RUN);
+    run.extend(rest.next());
+    if let Some(second) = rest.next() {
+        let descending = compare(&second, &run[0]) == Ordering::Less;
+        run.push(second);
+        while let Some(next) = rest.next_if(|next| {
+            let order = compare(next, &run[run.len() - 1]);
+            if descending { order == Ordering::Less } else { order != Ordering::Less }
+        }) {
+            run.push(next);
+        }
+        if descending {
+            run.reverse();
+        }
+    }
+    while run.len() < MIN_RUN {
+        let Some(next) = rest.next() else {
+            break;
+        };
+        //after the equal elements, to stay stable
+        let at = run.partition_point(|x| compare(x, &next) != Ordering::Greater);
+        run.insert(at, next);
+    }
+    run
+}
+
+//Merge the runs on top of the stack until, from the top, each run is
+//shorter than the one below it, and shorter than the two below it together,
+//so merges are between runs of about the same length
+fn collapse<T, F: FnMut(&T, &T) -> Ordering>(runs: &mut Vec<Vec<T>>, compare: &mut F) {
+    while runs.len() > 1 {
+        let n = runs.len();
+        let (x, y) = (runs[n - 1].len(), runs[n - 2].len());
+        if n > 2 && runs[n - 3].len() <= y + x {
+            //merge the middle one with the shorter of its neighbours
+            if runs[n - 3].len() < x {
+                merge_at(runs, n - 3, compare);
+            } else {
+                merge_at(runs, n - 2, compare);
+            }
+        } else if y <= x {
+            merge_at(runs, n - 2, compare);
+        } else {
+            return;
+        }
+    }
+}
+
+//Merge runs[i] and runs[i + 1]
+fn merge_at<T, F: FnMut(&T, &T) -> Ordering>(runs: &mut Vec<Vec<T>>, i: usize, compare: &mut F) {
+    let b = runs.remove(i + 1);
+    let a = mem::take(&mut runs[i]);
+    runs[i] = merge(a, b, compare);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tim_sort() {
+        assert_eq!(tim_sort(vec![4,6,1,8,11,13,3]), vec![1,3,4,6,8,11,13]);
+        assert_eq!(tim_sort(Vec::<u8>::new()), vec![]);
+        //ascending and descending runs, longer than MIN_RUN
+        let v: Vec<u32> = (0..100).chain((50..150).rev()).chain(0..10).collect();
+        let mut expected = v.clone();
+        expected.sort();
+        assert_eq!(tim_sort(v), expected);
+    }
+
+    #[test]
+    fn test_tim_sort_stable() {
+        let v: Vec<(u32, usize)> = (0..500).map(|i| ((i * 7919) % 13, i as usize)).collect();
+        let sorted = tim_sort_by_key(v, |p| p.0);
+        for w in sorted.windows(2) {
+            assert!(w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1));
+        }
+    }
+}
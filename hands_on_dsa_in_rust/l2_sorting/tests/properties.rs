@@ -0,0 +1,131 @@
+//Every sort against slice::sort, on random inputs from b_rand::RandGen
+use l2_sorting::b_rand::RandGen;
+use l2_sorting::external_sort::{external_sort, SortOptions};
+use l2_sorting::*;
+
+//(value, position in the input), the position shows if equal values kept their order
+type Item = (u32, usize);
+
+//lengths around the cutoffs of the sorts, values from a few to lots
+fn inputs() -> Vec<Vec<Item>> {
+    let mut r = RandGen::new(4513);
+    let mut inputs = Vec::new();
+    for len in [0, 1, 2, 3, 15, 16, 17, 31, 32, 33, 100, 1000] {
+        for max in [2, 50, 1 << 30] {
+            inputs.push((0..len).map(|i| (r.next_v(max) as u32, i)).collect());
+        }
+    }
+    //already in order, backwards, and runs
+    inputs.push((0..500).map(|i| (i as u32, i)).collect());
+    inputs.push((0..500).map(|i| (500 - i as u32, i)).collect());
+    inputs.push((0..500).map(|i| ((i % 40) as u32, i)).collect());
+    inputs
+}
+
+fn expected(input: &[Item]) -> Vec<Item> {
+    let mut v = input.to_vec();
+    //slice::sort is stable
+    v.sort_by_key(|item| item.0);
+    v
+}
+
+fn values(v: &[Item]) -> Vec<u32> {
+    v.iter().map(|item| item.0).collect()
+}
+
+#[test]
+fn stable_sorts_match_slice_sort() {
+    type Stable = fn(Vec<Item>) -> Vec<Item>;
+    let sorts: [(&str, Stable); 5] = [
+        ("bubble_sort", |mut v| { bubble_sort_by_key(&mut v, |item| item.0); v }),
+        ("insertion_sort", |mut v| { insertion_sort_by_key(&mut v, |item| item.0); v }),
+        ("radix_sort", |mut v| { radix_sort_by_key(&mut v, |item| item.0); v }),
+        ("merge_sort", |v| merge_sort_by_key(v, |item| item.0)),
+        ("tim_sort", |v| tim_sort_by_key(v, |item| item.0)),
+    ];
+    for input in inputs() {
+        for (name, sort) in sorts {
+            assert_eq!(sort(input.clone()), expected(&input), "{} of {:?}", name, input);
+        }
+    }
+}
+
+#[test]
+fn unstable_sorts_match_slice_sort() {
+    type Unstable = fn(&mut [Item]);
+    let sorts: [(&str, Unstable); 5] = [
+        ("quick_sort", |v| quick_sort_by_key(v, |item| item.0)),
+        ("threaded_quick_sort", |v| threaded_quick_sort_by_key(v, |item| item.0)),
+        ("quick_sort_rayon", |v| quick_sort_rayon_by_key(v, |item| item.0)),
+        ("heap_sort", |v| heap_sort_by_key(v, |item| item.0)),
+        ("intro_sort", |v| intro_sort_by_key(v, |item| item.0)),
+    ];
+    for input in inputs() {
+        for (name, sort) in sorts {
+            let mut v = input.clone();
+            sort(&mut v);
+            //equal values may be in any order, but all still there
+            assert_eq!(values(&v), values(&expected(&input)), "{} of {:?}", name, input);
+            v.sort();
+            let mut all = input.clone();
+            all.sort();
+            assert_eq!(v, all, "{} lost items of {:?}", name, input);
+        }
+    }
+}
+
+#[test]
+fn sorts_without_comparator_match_slice_sort() {
+    for input in inputs() {
+        let v = values(&input);
+        let mut want = v.clone();
+        want.sort();
+        let runs: [fn(&mut [u32]); 8] = [
+            bubble_sort,
+            insertion_sort,
+            quick_sort,
+            threaded_quick_sort,
+            quick_sort_rayon,
+            heap_sort,
+            intro_sort,
+            radix_sort,
+        ];
+        for sort in runs {
+            let mut got = v.clone();
+            sort(&mut got);
+            assert_eq!(got, want);
+        }
+        assert_eq!(merge_sort(v.clone()), want);
+        assert_eq!(tim_sort(v.clone()), want);
+    }
+}
+
+#[test]
+fn threaded_sorts_big_inputs() {
+    //big enough to start threads, and with lots of duplicates
+    let mut r = RandGen::new(77);
+    for max in [4, 1 << 30] {
+        let input: Vec<u32> = (0..50_000).map(|_| r.next_v(max) as u32).collect();
+        let mut want = input.clone();
+        want.sort();
+        let mut got = input.clone();
+        threaded_quick_sort(&mut got);
+        assert_eq!(got, want);
+        let mut got = input;
+        threaded_quick_sort_by(&mut got, |a, b| b.cmp(a));
+        want.reverse();
+        assert_eq!(got, want);
+    }
+}
+
+#[test]
+fn external_sort_matches_slice_sort() {
+    let mut r = RandGen::new(902);
+    let lines: Vec<String> = (0..3000).map(|_| format!("{:x}", r.next_v(5000))).collect();
+    let mut want = lines.clone();
+    want.sort();
+    let mut out = Vec::new();
+    let options = SortOptions { chunk_size: 1000, ..Default::default() };
+    external_sort(lines.join("\n").as_bytes(), &mut out, &options).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), want.join("\n") + "\n");
+}
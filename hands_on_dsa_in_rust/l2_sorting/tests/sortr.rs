@@ -0,0 +1,65 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+fn sortr() -> Command {
+    Command::cargo_bin("sortr").unwrap()
+}
+
+#[test]
+fn sorts_a_file_in_chunks() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in.txt");
+    let lines: Vec<String> = (0..2000).map(|i| format!("{}\t{}", (i * 37) % 500, i)).collect();
+    fs::write(&input, lines.join("\n")).unwrap();
+
+    //1K chunks make about 20 runs
+    let output = sortr()
+        .arg(&input)
+        .args(["-S", "1K", "-k", "1", "-n", "-u"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let out = String::from_utf8(output).unwrap();
+    let keys: Vec<&str> = out.lines().map(|l| l.split('\t').next().unwrap()).collect();
+    let expected: Vec<String> = (0..500).map(|i| i.to_string()).collect();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn output_may_be_the_input() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("words.txt");
+    fs::write(&file, "pear\napple\nfig\n").unwrap();
+    sortr()
+        .arg(&file)
+        .arg("-o")
+        .arg(&file)
+        .arg("-r")
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(fs::read_to_string(&file).unwrap(), "pear\nfig\napple\n");
+}
+
+#[test]
+fn bad_args() {
+    sortr()
+        .args(["-S", "12Q"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid size"));
+    //2^34 G does not fit in 64 bits
+    sortr()
+        .args(["-S", "17179869184G"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid size \"17179869184G\""));
+    sortr()
+        .arg("does-not-exist.txt")
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("does-not-exist.txt: "));
+}
@@ -0,0 +1,634 @@
+use std::fmt;
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<DbNode<T>>>;
+
+struct DbNode<T> {
+    data: T,
+    next: Link<T>,
+    prev: Link<T>,
+}
+
+// A doubly linked list. The list owns its nodes through raw pointers,
+// every node is a Box leaked on push and taken back on pop, so nothing
+// is shared and the iterators and the cursor can hand out plain references.
+pub struct DbList<T> {
+    first: Link<T>,
+    last: Link<T>,
+    len: usize,
+    // tells the compiler the list drops boxed nodes
+    _owns: PhantomData<Box<DbNode<T>>>,
+}
+
+// the list owns its values like a Vec does
+unsafe impl<T: Send> Send for DbList<T> {}
+unsafe impl<T: Sync> Sync for DbList<T> {}
+
+impl<T> DbList<T> {
+    pub fn new() -> Self {
+        Self {
+            first: None,
+            last: None,
+            len: 0,
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, data: T) {
+        let new_front = new_node(data, None, self.first);
+        match self.first {
+            // tell the first node this is now in front of it
+            Some(first) => unsafe { (*first.as_ptr()).prev = Some(new_front) },
+            None => self.last = Some(new_front),
+        }
+        self.first = Some(new_front);
+        self.len += 1;
+    }
+
+    pub fn push_back(&mut self, data: T) {
+        let new_back = new_node(data, self.last, None);
+        match self.last {
+            // tell the last node this is now behind it
+            Some(last) => unsafe { (*last.as_ptr()).next = Some(new_back) },
+            None => self.first = Some(new_back),
+        }
+        self.last = Some(new_back);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.first.map(|first| unsafe { self.unlink(first) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.last.map(|last| unsafe { self.unlink(last) })
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        self.first.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    pub fn peek_front_mut(&mut self) -> Option<&mut T> {
+        self.first.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        self.last.map(|node| unsafe { &(*node.as_ptr()).data })
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        self.last.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.first,
+            back: self.last,
+            len: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.first,
+            back: self.last,
+            len: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    pub fn reverse(&mut self) {
+        let mut cur = self.first;
+        while let Some(node) = cur {
+            unsafe {
+                let node = node.as_ptr();
+                mem::swap(&mut (*node).next, &mut (*node).prev);
+                // what was next is prev now
+                cur = (*node).prev;
+            }
+        }
+        mem::swap(&mut self.first, &mut self.last);
+    }
+
+    // moves all of other to the back of self, O(1)
+    pub fn append(&mut self, other: &mut Self) {
+        let Some(last) = self.last else {
+            mem::swap(self, other);
+            return;
+        };
+        if let Some(other_first) = other.first.take() {
+            unsafe {
+                (*last.as_ptr()).next = Some(other_first);
+                (*other_first.as_ptr()).prev = Some(last);
+            }
+            self.last = other.last.take();
+            self.len += mem::replace(&mut other.len, 0);
+        }
+    }
+
+    // everything from index at on, self keeps what is before it
+    // walks from the closer end, panics if at > len
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len, "split_off index out of bounds");
+        if at == 0 {
+            return mem::take(self);
+        }
+        if at == self.len {
+            return Self::new();
+        }
+        let split = if at <= self.len / 2 {
+            let mut cursor = self.cursor_front_mut();
+            for _ in 0..at {
+                cursor.move_next();
+            }
+            cursor.current
+        } else {
+            let mut cursor = self.cursor_back_mut();
+            for _ in at..cursor.list.len - 1 {
+                cursor.move_prev();
+            }
+            cursor.current
+        }
+        .unwrap();
+        unsafe {
+            // 0 < at, so there is one before it
+            let prev = (*split.as_ptr()).prev.take().unwrap();
+            (*prev.as_ptr()).next = None;
+            let back = Self {
+                first: Some(split),
+                last: self.last.replace(prev),
+                len: self.len - at,
+                _owns: PhantomData,
+            };
+            self.len = at;
+            back
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.first,
+            index: 0,
+            list: self,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.last,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    // Takes node out of the list and gives back its data
+    // node has to be in this list
+    unsafe fn unlink(&mut self, node: NonNull<DbNode<T>>) -> T {
+        let node = Box::from_raw(node.as_ptr());
+        match node.prev {
+            Some(prev) => (*prev.as_ptr()).next = node.next,
+            None => self.first = node.next,
+        }
+        match node.next {
+            Some(next) => (*next.as_ptr()).prev = node.prev,
+            None => self.last = node.prev,
+        }
+        self.len -= 1;
+        node.data
+    }
+}
+
+fn new_node<T>(data: T, prev: Link<T>, next: Link<T>) -> NonNull<DbNode<T>> {
+    NonNull::from(Box::leak(Box::new(DbNode { data, next, prev })))
+}
+
+impl<T> Default for DbList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for DbList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DbList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Clone> Clone for DbList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T> FromIterator<T> for DbList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = DbList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for DbList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for data in iter {
+            self.push_back(data);
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    // left between front and back, so they do not cross
+    len: usize,
+    _list: PhantomData<&'a DbList<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.map(|node| unsafe {
+            self.len -= 1;
+            self.front = (*node.as_ptr()).next;
+            &(*node.as_ptr()).data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.map(|node| unsafe {
+            self.len -= 1;
+            self.back = (*node.as_ptr()).prev;
+            &(*node.as_ptr()).data
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _list: PhantomData<&'a mut DbList<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.front.map(|node| unsafe {
+            self.len -= 1;
+            self.front = (*node.as_ptr()).next;
+            &mut (*node.as_ptr()).data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.back.map(|node| unsafe {
+            self.len -= 1;
+            self.back = (*node.as_ptr()).prev;
+            &mut (*node.as_ptr()).data
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+pub struct IntoIter<T>(DbList<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for DbList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DbList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DbList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// Walks the list both ways, inserting and removing where it is in O(1)
+// Past the back there is a "ghost" position, with no element, that leads
+// on to the front again, like std's cursors
+pub struct CursorMut<'a, T> {
+    current: Link<T>,
+    // len on the ghost
+    index: usize,
+    list: &'a mut DbList<T>,
+}
+
+impl<T> CursorMut<'_, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = match self.current {
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.first,
+        };
+        next.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = match self.current {
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.last,
+        };
+        prev.map(|node| unsafe { &mut (*node.as_ptr()).data })
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current {
+            Some(node) => {
+                self.current = unsafe { (*node.as_ptr()).next };
+                self.index += 1;
+            }
+            None => {
+                self.current = self.list.first;
+                self.index = 0;
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.current {
+            Some(node) => {
+                self.current = unsafe { (*node.as_ptr()).prev };
+                // from the front onto the ghost
+                self.index = self.index.checked_sub(1).unwrap_or(self.list.len);
+            }
+            None => {
+                self.current = self.list.last;
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+
+    // on the ghost this pushes to the front
+    pub fn insert_after(&mut self, data: T) {
+        let Some(node) = self.current else {
+            self.list.push_front(data);
+            self.index = self.list.len;
+            return;
+        };
+        unsafe {
+            let next = (*node.as_ptr()).next;
+            let new_node = new_node(data, Some(node), next);
+            (*node.as_ptr()).next = Some(new_node);
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(new_node),
+                None => self.list.last = Some(new_node),
+            }
+        }
+        self.list.len += 1;
+    }
+
+    // on the ghost this pushes to the back
+    pub fn insert_before(&mut self, data: T) {
+        let Some(node) = self.current else {
+            self.list.push_back(data);
+            self.index = self.list.len;
+            return;
+        };
+        unsafe {
+            let prev = (*node.as_ptr()).prev;
+            let new_node = new_node(data, prev, Some(node));
+            (*node.as_ptr()).prev = Some(new_node);
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(new_node),
+                None => self.list.first = Some(new_node),
+            }
+        }
+        self.list.len += 1;
+        self.index += 1;
+    }
+
+    // the next element becomes current
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        unsafe {
+            self.current = (*node.as_ptr()).next;
+            Some(self.list.unlink(node))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DbList;
+
+    fn list(values: &[i32]) -> DbList<i32> {
+        values.iter().copied().collect()
+    }
+
+    fn values(list: &DbList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut dl = DbList::new();
+        dl.push_front(6);
+        dl.push_back(11);
+        dl.push_front(5);
+        dl.push_back(15);
+        dl.push_front(4);
+        assert_eq!(values(&dl), [4, 5, 6, 11, 15]);
+        assert_eq!(dl.len(), 5);
+        assert_eq!((dl.peek_front(), dl.peek_back()), (Some(&4), Some(&15)));
+        *dl.peek_front_mut().unwrap() = 3;
+        *dl.peek_back_mut().unwrap() = 16;
+        assert_eq!(dl.pop_front(), Some(3));
+        assert_eq!(dl.pop_back(), Some(16));
+        assert_eq!(dl.pop_back(), Some(11));
+        assert_eq!(dl.pop_back(), Some(6));
+        assert_eq!(dl.pop_front(), Some(5));
+        assert_eq!((dl.pop_front(), dl.pop_back()), (None, None));
+        assert!(dl.is_empty());
+        dl.push_back(1);
+        assert_eq!(values(&dl), [1]);
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut dl = list(&[1, 2, 3, 4]);
+        for x in dl.iter_mut().rev().take(2) {
+            *x *= 10;
+        }
+        assert_eq!(format!("{:?}", dl), "[1, 2, 30, 40]");
+        let mut it = dl.iter();
+        assert_eq!((it.next(), it.next_back(), it.len()), (Some(&1), Some(&40), 2));
+        assert_eq!((it.next_back(), it.next(), it.next(), it.next_back()), (Some(&30), Some(&2), None, None));
+        let mut it = dl.clone().into_iter();
+        assert_eq!((it.next_back(), it.next()), (Some(40), Some(1)));
+        assert_eq!(it.collect::<Vec<_>>(), [2, 30]);
+        assert_eq!((&dl).into_iter().rev().copied().collect::<Vec<_>>(), [40, 30, 2, 1]);
+    }
+
+    #[test]
+    fn test_reverse_append_split_off() {
+        let mut dl = list(&[1, 2, 3]);
+        dl.reverse();
+        assert_eq!(values(&dl), [3, 2, 1]);
+        assert_eq!(dl.iter().rev().copied().collect::<Vec<_>>(), [1, 2, 3]);
+        let mut other = list(&[7, 8]);
+        dl.append(&mut other);
+        assert!(other.is_empty());
+        other.append(&mut dl);
+        std::mem::swap(&mut dl, &mut other);
+        assert_eq!(values(&dl), [3, 2, 1, 7, 8]);
+        // from the front and from the back
+        let mut back = dl.split_off(1);
+        let last = back.split_off(3);
+        assert_eq!((values(&dl), values(&back), values(&last)), (vec![3], vec![2, 1, 7], vec![8]));
+        assert_eq!((dl.len(), back.len(), last.len()), (1, 3, 1));
+        assert_eq!(back.iter().rev().copied().collect::<Vec<_>>(), [7, 1, 2]);
+        assert!(back.split_off(3).is_empty());
+        assert_eq!(values(&back.split_off(0)), [2, 1, 7]);
+        assert!(back.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_split_off_out_of_bounds() {
+        list(&[1]).split_off(2);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut dl = list(&[1, 2, 4]);
+        let mut cursor = dl.cursor_front_mut();
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (Some(1), Some(&mut 2)));
+        cursor.insert_after(3);
+        cursor.insert_before(0);
+        assert_eq!(cursor.index(), Some(2));
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&mut 3)));
+        assert_eq!(cursor.peek_prev(), Some(&mut 0));
+        assert_eq!(cursor.peek_next(), Some(&mut 4));
+        cursor.move_next();
+        cursor.move_next();
+        // the ghost, between the back and the front
+        assert_eq!((cursor.index(), cursor.current()), (None, None));
+        assert_eq!(cursor.peek_prev(), Some(&mut 4));
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+        cursor.insert_after(-1);
+        cursor.insert_before(5);
+        assert_eq!(cursor.remove_current(), None);
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.remove_current()), (Some(5), Some(5)));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(values(&dl), [-1, 1, 0, 3, 4]);
+        assert_eq!(dl.iter().rev().copied().collect::<Vec<_>>(), [4, 3, 0, 1, -1]);
+
+        let mut cursor = dl.cursor_back_mut();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&mut 0)));
+        while cursor.remove_current().is_some() {}
+        assert_eq!(values(&dl), [-1, 1]);
+        assert_eq!(dl.peek_back(), Some(&1));
+    }
+
+    #[test]
+    fn test_drops_everything() {
+        use std::rc::Rc;
+        let counted = Rc::new(());
+        let mut dl: DbList<Rc<()>> = (0..10).map(|_| Rc::clone(&counted)).collect();
+        let mut cursor = dl.cursor_front_mut();
+        cursor.move_next();
+        drop(cursor.remove_current());
+        let back = dl.split_off(4);
+        assert_eq!(Rc::strong_count(&counted), 10);
+        drop((dl, back));
+        assert_eq!(Rc::strong_count(&counted), 1);
+    }
+}
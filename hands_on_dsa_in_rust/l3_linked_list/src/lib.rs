@@ -0,0 +1,7 @@
+// The unsafe code in db_list is checked with `cargo +nightly miri test`
+
+pub mod db_list;
+pub mod linked_list;
+
+pub use db_list::DbList;
+pub use linked_list::LinkedList;
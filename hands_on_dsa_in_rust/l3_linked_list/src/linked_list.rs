@@ -0,0 +1,358 @@
+use std::fmt;
+use std::iter::FusedIterator;
+
+// a list is either empty, or a value and the rest of the list
+pub struct LinkedList<T>(Option<(T, Box<LinkedList<T>>)>);
+
+impl<T> LinkedList<T> {
+    pub fn new() -> Self {
+        LinkedList(None)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_none()
+    }
+
+    // O(n), the list does not keep count
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn push_front(&mut self, data: T) {
+        let t = self.0.take();
+        self.0 = Some((data, Box::new(LinkedList(t))));
+    }
+
+    pub fn push_back(&mut self, data: T) {
+        self.tail().push_front(data);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let (data, mut child) = self.0.take()?;
+        self.0 = child.0.take();
+        Some(data)
+    }
+
+    // O(n), it has to find the one before the last
+    pub fn pop_back(&mut self) -> Option<T> {
+        let mut cur = self;
+        while cur.0.as_ref()?.1.0.is_some() {
+            cur = &mut cur.0.as_mut().unwrap().1;
+        }
+        cur.pop_front()
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        self.0.as_ref().map(|(data, _)| data)
+    }
+
+    pub fn peek_front_mut(&mut self) -> Option<&mut T> {
+        self.0.as_mut().map(|(data, _)| data)
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        self.iter().last()
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        self.iter_mut().last()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut(Some(self))
+    }
+
+    pub fn reverse(&mut self) {
+        let mut rest = self.0.take();
+        // move the front of the rest onto the front of self, one at a time
+        while let Some((data, mut child)) = rest {
+            rest = child.0.take();
+            child.0 = self.0.take();
+            self.0 = Some((data, child));
+        }
+    }
+
+    // moves all of other to the back of self, O(len of self)
+    pub fn append(&mut self, other: &mut Self) {
+        self.tail().0 = other.0.take();
+    }
+
+    // everything from index at on, self keeps what is before it
+    // panics if at > len
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let mut cur = self;
+        for _ in 0..at {
+            match cur.0 {
+                Some((_, ref mut child)) => cur = child,
+                None => panic!("split_off index out of bounds"),
+            }
+        }
+        LinkedList(cur.0.take())
+    }
+
+    // a cursor on the first element
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { rest: Some(self) }
+    }
+
+    // the empty list at the end
+    fn tail(&mut self) -> &mut Self {
+        let mut cur = self;
+        while cur.0.is_some() {
+            cur = &mut cur.0.as_mut().unwrap().1;
+        }
+        cur
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// dropping the boxes one by one, a long list would overflow the stack
+// dropping itself recursively
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        let mut rest = self.0.take();
+        while let Some((_, mut child)) = rest {
+            rest = child.0.take();
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = LinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        // keep the end, rather than looking for it for every value
+        let mut tail = self.tail();
+        for data in iter {
+            tail.push_front(data);
+            tail = &mut tail.0.as_mut().unwrap().1;
+        }
+    }
+}
+
+pub struct Iter<'a, T>(&'a LinkedList<T>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (data, child) = self.0 .0.as_ref()?;
+        self.0 = child;
+        Some(data)
+    }
+}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T>(Option<&'a mut LinkedList<T>>);
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (data, child) = self.0.take()?.0.as_mut()?;
+        self.0 = Some(child);
+        Some(data)
+    }
+}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+pub struct IntoIter<T>(LinkedList<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// Walks the list forwards, inserting and removing where it is in O(1)
+// It points at the rest of the list from the current element on,
+// which is empty once it moved past the last one
+pub struct CursorMut<'a, T> {
+    rest: Option<&'a mut LinkedList<T>>,
+}
+
+impl<T> CursorMut<'_, T> {
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.rest.as_mut()?.peek_front_mut()
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        self.rest.as_mut()?.0.as_mut()?.1.peek_front_mut()
+    }
+
+    // false once past the last element, where it stays
+    pub fn move_next(&mut self) -> bool {
+        let rest = self.rest.take().unwrap();
+        if rest.is_empty() {
+            self.rest = Some(rest);
+            return false;
+        }
+        self.rest = Some(&mut rest.0.as_mut().unwrap().1);
+        true
+    }
+
+    // the current element stays current, past the last one this pushes to the back
+    pub fn insert_before(&mut self, data: T) {
+        self.rest.as_mut().unwrap().push_front(data);
+        self.move_next();
+    }
+
+    // past the last element this pushes to the back, and stays past it
+    pub fn insert_after(&mut self, data: T) {
+        let rest = self.rest.as_mut().unwrap();
+        match rest.0 {
+            Some((_, ref mut child)) => child.push_front(data),
+            None => {
+                rest.push_front(data);
+                self.move_next();
+            }
+        }
+    }
+
+    // the next element becomes current
+    pub fn remove_current(&mut self) -> Option<T> {
+        self.rest.as_mut()?.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkedList;
+
+    fn list(values: &[i32]) -> LinkedList<i32> {
+        values.iter().copied().collect()
+    }
+
+    fn values(list: &LinkedList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut ll = LinkedList::new();
+        ll.push_front(3);
+        ll.push_back(12);
+        ll.push_front(1);
+        assert_eq!(values(&ll), [1, 3, 12]);
+        assert_eq!(ll.len(), 3);
+        assert_eq!((ll.peek_front(), ll.peek_back()), (Some(&1), Some(&12)));
+        assert_eq!(ll.pop_back(), Some(12));
+        assert_eq!(ll.pop_front(), Some(1));
+        assert_eq!(ll.pop_back(), Some(3));
+        assert_eq!((ll.pop_front(), ll.pop_back()), (None, None));
+        assert!(ll.is_empty());
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut ll = list(&[1, 2, 3]);
+        for x in &mut ll {
+            *x *= 10;
+        }
+        *ll.peek_back_mut().unwrap() += 1;
+        assert_eq!((&ll).into_iter().sum::<i32>(), 61);
+        assert_eq!(format!("{:?}", ll), "[10, 20, 31]");
+        assert_eq!(ll.into_iter().collect::<Vec<_>>(), [10, 20, 31]);
+    }
+
+    #[test]
+    fn test_reverse_append_split_off() {
+        let mut ll = list(&[1, 2, 3]);
+        ll.reverse();
+        assert_eq!(values(&ll), [3, 2, 1]);
+        let mut other = list(&[7, 8]);
+        ll.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(values(&ll), [3, 2, 1, 7, 8]);
+        let back = ll.split_off(2);
+        assert_eq!((values(&ll), values(&back)), (vec![3, 2], vec![1, 7, 8]));
+        assert!(ll.split_off(2).is_empty());
+        assert_eq!(list(&[1]).split_off(0).len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_split_off_out_of_bounds() {
+        list(&[1]).split_off(2);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut ll = list(&[1, 2, 4]);
+        let mut cursor = ll.cursor_mut();
+        assert!(cursor.move_next());
+        assert_eq!(cursor.current(), Some(&mut 2));
+        cursor.insert_after(3);
+        cursor.insert_before(0);
+        assert_eq!(cursor.remove_current(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(cursor.peek_next(), Some(&mut 4));
+        while cursor.move_next() {}
+        assert_eq!(cursor.current(), None);
+        cursor.insert_after(5);
+        cursor.insert_before(6);
+        assert_eq!(cursor.remove_current(), None);
+        assert_eq!(values(&ll), [1, 0, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_long_list_drop() {
+        // would overflow the stack with the recursive drop of the boxes
+        let ll: LinkedList<u32> = (0..if cfg!(miri) { 1000 } else { 200_000 }).collect();
+        drop(ll);
+    }
+}
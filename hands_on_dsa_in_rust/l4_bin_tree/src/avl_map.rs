@@ -0,0 +1,529 @@
+use std::borrow::Borrow;
+use std::cmp::{max, Ordering};
+use std::fmt::{self, Debug};
+use std::iter::FusedIterator;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+type Link<K, V> = Option<Box<AvlNode<K, V>>>;
+
+struct AvlNode<K, V> {
+    key: K,
+    value: V,
+    h: u8, //height, 1 for a leaf
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> AvlNode<K, V> {
+    fn new(key: K, value: V) -> Box<Self> {
+        Box::new(Self {
+            key,
+            value,
+            h: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn set_height(&mut self) {
+        self.h = 1 + max(height(&self.left), height(&self.right));
+    }
+
+    // how much higher the left side is than the right
+    fn balance(&self) -> i16 {
+        height(&self.left) as i16 - height(&self.right) as i16
+    }
+}
+
+fn height<K, V>(link: &Link<K, V>) -> u8 {
+    link.as_ref().map_or(0, |node| node.h)
+}
+
+fn rot_left<K, V>(link: &mut Link<K, V>) {
+    let Some(mut node) = link.take() else { return };
+    let Some(mut res) = node.right.take() else {
+        //without right node we cannot rotate
+        *link = Some(node);
+        return;
+    };
+    node.right = res.left.take();
+    node.set_height();
+    res.left = Some(node);
+    res.set_height();
+    *link = Some(res);
+}
+
+fn rot_right<K, V>(link: &mut Link<K, V>) {
+    let Some(mut node) = link.take() else { return };
+    let Some(mut res) = node.left.take() else {
+        //without left node we cannot rotate
+        *link = Some(node);
+        return;
+    };
+    node.left = res.right.take();
+    node.set_height();
+    res.right = Some(node);
+    res.set_height();
+    *link = Some(res);
+}
+
+// Brings the node back within one level of balance after one insert or remove
+// below it, with a double rotation when the higher child leans the other way
+fn rebalance<K, V>(link: &mut Link<K, V>) {
+    let Some(node) = link.as_mut() else { return };
+    node.set_height();
+    match node.balance() {
+        2.. => {
+            if node.left.as_ref().is_some_and(|left| left.balance() < 0) {
+                rot_left(&mut node.left);
+            }
+            rot_right(link);
+        }
+        ..=-2 => {
+            if node.right.as_ref().is_some_and(|right| right.balance() > 0) {
+                rot_right(&mut node.right);
+            }
+            rot_left(link);
+        }
+        _ => {}
+    }
+}
+
+fn insert<K: Ord, V>(link: &mut Link<K, V>, key: K, value: V) -> Option<V> {
+    let Some(node) = link else {
+        *link = Some(AvlNode::new(key, value));
+        return None;
+    };
+    let old = match key.cmp(&node.key) {
+        Ordering::Less => insert(&mut node.left, key, value),
+        Ordering::Greater => insert(&mut node.right, key, value),
+        Ordering::Equal => return Some(mem::replace(&mut node.value, value)),
+    };
+    rebalance(link);
+    old
+}
+
+fn remove<K, V, Q>(link: &mut Link<K, V>, key: &Q) -> Option<(K, V)>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let node = link.as_mut()?;
+    let removed = match key.cmp(node.key.borrow()) {
+        Ordering::Less => remove(&mut node.left, key),
+        Ordering::Greater => remove(&mut node.right, key),
+        Ordering::Equal => {
+            let mut node = link.take()?;
+            *link = match (node.left.take(), node.right.take()) {
+                (None, right) => right,
+                (left, None) => left,
+                // the next key takes the place of the removed one
+                (left, mut right) => {
+                    let mut next = remove_first(&mut right);
+                    next.left = left;
+                    next.right = right;
+                    Some(next)
+                }
+            };
+            Some((node.key, node.value))
+        }
+    };
+    rebalance(link);
+    removed
+}
+
+// takes out the node with the smallest key, link must not be empty
+fn remove_first<K, V>(link: &mut Link<K, V>) -> Box<AvlNode<K, V>> {
+    let node = link.as_mut().expect("remove_first of an empty tree");
+    if node.left.is_some() {
+        let first = remove_first(&mut node.left);
+        rebalance(link);
+        first
+    } else {
+        let mut first = link.take().unwrap();
+        *link = first.right.take();
+        first
+    }
+}
+
+/// An ordered map on an AVL tree: the two sides of every node differ in
+/// height by one at most, so lookups, inserts and removes are O(log n)
+pub struct AvlMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K, V> AvlMap<K, V> {
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Levels from the root down to the deepest leaf, 0 when empty
+    pub fn height(&self) -> usize {
+        height(&self.root) as usize
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// The entry with the smallest key
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = node.left.as_ref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// The entry with the largest key
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = node.right.as_ref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// In key order
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut range = Range::empty();
+        range.push_left(self.root.as_deref());
+        range.push_right(self.root.as_deref());
+        Iter {
+            range,
+            len: self.len,
+        }
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + '_ {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + '_ {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Whether every node has the right height and is balanced, which is
+    /// always true unless there is a bug
+    pub fn is_balanced(&self) -> bool {
+        fn check<K, V>(link: &Link<K, V>) -> Option<u8> {
+            let Some(node) = link else { return Some(0) };
+            let (l, r) = (check(&node.left)?, check(&node.right)?);
+            let h = 1 + max(l, r);
+            (h == node.h && l.abs_diff(r) <= 1).then_some(h)
+        }
+        check(&self.root).is_some()
+    }
+}
+
+impl<K: Ord, V> AvlMap<K, V> {
+    /// Returns the old value if the key was there already, the key is not updated
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old = insert(&mut self.root, key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = remove(&mut self.root, key);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match key.cmp(node.key.borrow()) {
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            link = match key.cmp(node.key.borrow()) {
+                Ordering::Less => &mut node.left,
+                Ordering::Greater => &mut node.right,
+                Ordering::Equal => return Some(&mut node.value),
+            };
+        }
+        None
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// The entries with keys in range, in key order. A range that ends
+    /// before it starts is empty
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut res = Range::empty();
+        // the path down to the first key in range
+        let mut next = self.root.as_deref();
+        while let Some(node) = next {
+            let key = node.key.borrow();
+            next = match range.start_bound() {
+                Bound::Included(start) if key < start => node.right.as_deref(),
+                Bound::Excluded(start) if key <= start => node.right.as_deref(),
+                _ => {
+                    res.front.push(node);
+                    node.left.as_deref()
+                }
+            };
+        }
+        // and to the last one
+        let mut next = self.root.as_deref();
+        while let Some(node) = next {
+            let key = node.key.borrow();
+            next = match range.end_bound() {
+                Bound::Included(end) if key > end => node.left.as_deref(),
+                Bound::Excluded(end) if key >= end => node.left.as_deref(),
+                _ => {
+                    res.back.push(node);
+                    node.right.as_deref()
+                }
+            };
+        }
+        match (res.front.last(), res.back.last()) {
+            (Some(first), Some(last)) if first.key <= last.key => res,
+            _ => Range::empty(),
+        }
+    }
+}
+
+impl<K, V> Default for AvlMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Drop for AvlMap<K, V> {
+    // the tree is balanced so dropping it recursively is fine,
+    // but doing it by hand keeps it off the stack altogether
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<AvlNode<K, V>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for AvlMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for AvlMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = AvlMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for AvlMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a AvlMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Walks the tree from both ends with a stack each, the top of a stack
+/// being the next node from that end
+pub struct Range<'a, K, V> {
+    front: Vec<&'a AvlNode<K, V>>,
+    back: Vec<&'a AvlNode<K, V>>,
+}
+
+impl<'a, K, V> Range<'a, K, V> {
+    fn empty() -> Self {
+        Self {
+            front: Vec::new(),
+            back: Vec::new(),
+        }
+    }
+
+    fn push_left(&mut self, mut next: Option<&'a AvlNode<K, V>>) {
+        while let Some(node) = next {
+            self.front.push(node);
+            next = node.left.as_deref();
+        }
+    }
+
+    fn push_right(&mut self, mut next: Option<&'a AvlNode<K, V>>) {
+        while let Some(node) = next {
+            self.back.push(node);
+            next = node.right.as_deref();
+        }
+    }
+
+    // the ends met, nothing is left in between
+    fn finish(&mut self) {
+        self.front.clear();
+        self.back.clear();
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front.pop()?;
+        if self.back.last().is_some_and(|last| std::ptr::eq(*last, node)) {
+            self.finish();
+        } else {
+            self.push_left(node.right.as_deref());
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back.pop()?;
+        if self.front.last().is_some_and(|first| std::ptr::eq(*first, node)) {
+            self.finish();
+        } else {
+            self.push_right(node.left.as_deref());
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> FusedIterator for Range<'_, K, V> {}
+
+pub struct Iter<'a, K, V> {
+    range: Range<'a, K, V>,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.range.next()?;
+        self.len -= 1;
+        Some(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let next = self.range.next_back()?;
+        self.len -= 1;
+        Some(next)
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+impl<K, V> FusedIterator for Iter<'_, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::AvlMap;
+    use std::ops::Bound;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = AvlMap::new();
+        for i in 0..100 {
+            assert_eq!(map.insert(i, i * 10), None);
+            assert!(map.is_balanced());
+        }
+        // sorted inserts would make a list of a plain tree
+        assert_eq!((map.len(), map.height()), (100, 7));
+        assert_eq!(map.insert(5, 0), Some(50));
+        assert_eq!((map.get(&5), map.get(&100)), (Some(&0), None));
+        *map.get_mut(&6).unwrap() += 1;
+        assert_eq!(map.remove(&6), Some(61));
+        assert_eq!(map.remove(&6), None);
+        assert!(!map.contains_key(&6) && map.contains_key(&7));
+        assert_eq!((map.first(), map.last()), (Some((&0, &0)), Some((&99, &990))));
+        for i in (0..100).rev() {
+            map.remove(&i);
+            assert!(map.is_balanced());
+        }
+        assert!(map.is_empty());
+        assert_eq!((map.first(), map.height()), (None, 0));
+    }
+
+    #[test]
+    fn test_iter_range() {
+        let map: AvlMap<_, _> = [5, 1, 9, 3, 7].into_iter().map(|k| (k, k.to_string())).collect();
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), [1, 3, 5, 7, 9]);
+        assert_eq!(map.values().rev().cloned().collect::<String>(), "97531");
+        let keys = |r: super::Range<'_, i32, String>| r.map(|(k, _)| *k).collect::<Vec<_>>();
+        assert_eq!(keys(map.range(3..7)), [3, 5]);
+        assert_eq!(keys(map.range(2..=9)), [3, 5, 7, 9]);
+        assert_eq!(keys(map.range(..)), [1, 3, 5, 7, 9]);
+        assert_eq!(keys(map.range(6..6)), []);
+        assert_eq!(keys(map.range((Bound::Included(8), Bound::Excluded(2)))), []);
+        let mut range = map.range(1..9);
+        assert_eq!((range.next_back(), range.next()), (Some((&7, &"7".to_string())), Some((&1, &"1".to_string()))));
+        assert_eq!(range.map(|(k, _)| *k).collect::<Vec<_>>(), [3, 5]);
+        assert_eq!(format!("{:?}", map), r#"{1: "1", 3: "3", 5: "5", 7: "7", 9: "9"}"#);
+    }
+}
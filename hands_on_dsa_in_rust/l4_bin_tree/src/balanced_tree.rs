@@ -68,6 +68,12 @@ impl<T> BinTree<T> {
     }
 }
 
+impl<T> Default for BinTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: PartialOrd> BinTree<T> {
     pub fn add_sorted(&mut self, data: T) {
         let rot_direction = match self.0 {
@@ -121,20 +127,3 @@ impl<T: Debug> BinTree<T> {
     }
 }
 
-
-fn main() { 
-    let mut t = BinTree::new();
-    t.add_sorted(4);
-    t.add_sorted(5);
-    t.add_sorted(6);
-    t.add_sorted(10);
-    t.add_sorted(1);
-    t.add_sorted(94);
-    t.add_sorted(54);
-    t.add_sorted(3);
-
-    for i in 0..100000 {
-        t.add_sorted(i);
-    }
-    t.print_lfirst(0);
-}
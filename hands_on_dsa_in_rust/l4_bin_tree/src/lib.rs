@@ -0,0 +1,5 @@
+pub mod avl_map;
+pub mod balanced_tree;
+
+pub use avl_map::AvlMap;
+pub use balanced_tree::BinTree;
@@ -0,0 +1,26 @@
+use l4_bin_tree::{AvlMap, BinTree};
+
+fn main() { 
+    let mut t = BinTree::new();
+    t.add_sorted(4);
+    t.add_sorted(5);
+    t.add_sorted(6);
+    t.add_sorted(10);
+    t.add_sorted(1);
+    t.add_sorted(94);
+    t.add_sorted(54);
+    t.add_sorted(3);
+
+    for i in 0..100000 {
+        t.add_sorted(i);
+    }
+    t.print_lfirst(0);
+
+    let mut m = AvlMap::new();
+    for (i, word) in ["avl", "trees", "stay", "balanced", "after", "every", "insert"].iter().enumerate() {
+        m.insert(*word, i);
+    }
+    m.remove("every");
+    println!("m = {:?}, height {}", m, m.height());
+    println!("b..s = {:?}", m.range("b".."s").collect::<Vec<_>>());
+}
@@ -0,0 +1,82 @@
+//AvlMap against std's BTreeMap, checking the AVL invariant after every operation
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use l4_bin_tree::AvlMap;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn assert_same(avl: &AvlMap<u32, u32>, std: &BTreeMap<u32, u32>) {
+    assert!(avl.is_balanced());
+    assert_eq!(avl.len(), std.len());
+    //an AVL tree of n nodes is never higher than 1.44 * log2(n + 2)
+    assert!(avl.height() as f64 <= 1.44 * ((avl.len() + 2) as f64).log2());
+    assert_eq!((avl.first(), avl.last()), (std.first_key_value(), std.last_key_value()));
+}
+
+fn random_ops(seed: u64, ops: usize, max_key: u32) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut avl = AvlMap::new();
+    let mut std = BTreeMap::new();
+    for _ in 0..ops {
+        let key = rng.gen_range(0..max_key);
+        match rng.gen_range(0..10) {
+            //more inserts than removes, so the maps grow
+            0..=5 => assert_eq!(avl.insert(key, key * 2), std.insert(key, key * 2)),
+            6..=8 => assert_eq!(avl.remove(&key), std.remove(&key)),
+            _ => assert_eq!(avl.get(&key), std.get(&key)),
+        }
+        assert_eq!(avl.contains_key(&key), std.contains_key(&key));
+        assert_same(&avl, &std);
+    }
+    assert!(avl.iter().eq(std.iter()));
+    assert!(avl.iter().rev().eq(std.iter().rev()));
+    for _ in 0..200 {
+        let (a, b) = (rng.gen_range(0..max_key), rng.gen_range(0..max_key));
+        let (start, end) = (a.min(b), a.max(b));
+        assert!(avl.range(start..end).eq(std.range(start..end)));
+        assert!(avl.range(start..=end).rev().eq(std.range(start..=end).rev()));
+        assert!(avl.range(..end).eq(std.range(..end)));
+        let bounds = (Bound::Excluded(start), Bound::Unbounded);
+        assert!(avl.range(bounds).eq(std.range(bounds)));
+        //from both ends at once
+        let mut avl_range = avl.range(start..=end);
+        let mut std_range = std.range(start..=end);
+        loop {
+            let next = (avl_range.next(), avl_range.next_back());
+            assert_eq!(next, (std_range.next(), std_range.next_back()));
+            if next == (None, None) {
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn small_keys_match_btree_map() {
+    //lots of hits on keys that are already there
+    for seed in 0..20 {
+        random_ops(seed, 500, 40);
+    }
+}
+
+#[test]
+fn large_keys_match_btree_map() {
+    random_ops(42, 5000, 1 << 20);
+}
+
+#[test]
+fn in_order_and_reverse_inserts_stay_balanced() {
+    let mut avl = AvlMap::new();
+    let mut std = BTreeMap::new();
+    for key in (0..1000).chain((1000..2000).rev()) {
+        avl.insert(key, key);
+        std.insert(key, key);
+        assert_same(&avl, &std);
+    }
+    for key in (0..2000).step_by(3).chain(0..2000) {
+        assert_eq!(avl.remove(&key), std.remove(&key));
+        assert_same(&avl, &std);
+    }
+    assert!(avl.is_empty());
+}
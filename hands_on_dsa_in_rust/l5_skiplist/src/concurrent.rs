@@ -0,0 +1,479 @@
+use std::borrow::Borrow;
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use std::sync::atomic::{AtomicUsize, Ordering::{AcqRel, Acquire, Relaxed, Release}};
+
+use crossbeam_epoch::{self as epoch, Atomic, Guard, Owned, Shared};
+
+use crate::levels::{Levels, MAX_LEVEL};
+
+// A node is removed in two steps: first it is marked, by setting the tag bit
+// of its own next pointers, top level first, then it is unlinked from every
+// level by whoever walks past it. Marked pointers are never changed again, so
+// nothing can be linked after a removed node.
+struct Node<K, V> {
+    key: K,
+    // taken, leaving null, by the remover once the node is marked,
+    // a value is only replaced while it is still there
+    value: Atomic<V>,
+    // the levels it is linked on, plus one while the inserting thread is still
+    // linking it. Whoever takes it to 0 hands the node to the epoch collector.
+    refs: AtomicUsize,
+    tower: Box<[Atomic<Node<K, V>>]>,
+}
+
+impl<K, V> Node<K, V> {
+    fn marked(&self) -> bool {
+        self.tower[0].load(Acquire, unsafe { epoch::unprotected() }).tag() == 1
+    }
+
+    // Safety: the node has to be unreachable for threads pinned from now on
+    unsafe fn release(node: Shared<'_, Self>, guard: &Guard) {
+        if node.deref().refs.fetch_sub(1, AcqRel) == 1 {
+            guard.defer_destroy(node);
+        }
+    }
+}
+
+impl<K, V> Drop for Node<K, V> {
+    fn drop(&mut self) {
+        // the node is gone so no one else sees the value
+        unsafe {
+            let value = self.value.load(Relaxed, epoch::unprotected());
+            if !value.is_null() {
+                drop(value.into_owned());
+            }
+        }
+    }
+}
+
+// Where a key goes: on every level, the tower before it and the node after it
+struct Position<'g, K, V> {
+    preds: [&'g Atomic<Node<K, V>>; MAX_LEVEL],
+    succs: [Shared<'g, Node<K, V>>; MAX_LEVEL],
+}
+
+/// A lock-free ordered map for many threads reading and writing at once.
+/// Removed nodes are freed by crossbeam's epoch-based reclamation once no
+/// thread can be reading them anymore. Values come out cloned, as a value
+/// may be replaced or removed right after it was read.
+pub struct ConcurrentSkipMap<K, V> {
+    head: [Atomic<Node<K, V>>; MAX_LEVEL],
+    len: AtomicUsize,
+    levels: Levels,
+}
+
+unsafe impl<K: Send + Sync, V: Send + Sync> Send for ConcurrentSkipMap<K, V> {}
+unsafe impl<K: Send + Sync, V: Send + Sync> Sync for ConcurrentSkipMap<K, V> {}
+
+impl<K, V> ConcurrentSkipMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            head: Default::default(),
+            len: AtomicUsize::new(0),
+            levels: Levels::new(seed),
+        }
+    }
+
+    /// Only exact while no other thread is inserting or removing
+    pub fn len(&self) -> usize {
+        self.len.load(Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// The collector may drop removed nodes later on any thread
+impl<K: Ord + Send + 'static, V: Send + 'static> ConcurrentSkipMap<K, V> {
+    // Finds where key goes, unlinking the marked nodes on the way. Starts over
+    // when a node it went through is removed under it.
+    fn find<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Position<'g, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        'retry: loop {
+            let mut pos = Position {
+                preds: [&self.head[0]; MAX_LEVEL],
+                succs: [Shared::null(); MAX_LEVEL],
+            };
+            let mut pred: &'g [Atomic<Node<K, V>>] = &self.head;
+            for level in (0..MAX_LEVEL).rev() {
+                let mut curr = pred[level].load(Acquire, guard);
+                if curr.tag() == 1 {
+                    continue 'retry;
+                }
+                while let Some(c) = unsafe { curr.as_ref() } {
+                    let succ = c.tower[level].load(Acquire, guard);
+                    if succ.tag() == 1 {
+                        match pred[level].compare_exchange(curr, succ.with_tag(0), AcqRel, Acquire, guard) {
+                            Ok(_) => {
+                                // unlinked from this level
+                                unsafe { Node::release(curr, guard) };
+                                curr = succ.with_tag(0);
+                                continue;
+                            }
+                            Err(_) => continue 'retry,
+                        }
+                    }
+                    if c.key.borrow() >= key {
+                        break;
+                    }
+                    pred = &c.tower;
+                    curr = succ;
+                }
+                pos.preds[level] = &pred[level];
+                pos.succs[level] = curr;
+            }
+            return pos;
+        }
+    }
+
+    fn find_node<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<&'g Node<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = unsafe { self.find(key, guard).succs[0].as_ref()? };
+        (node.key.borrow() == key).then_some(node)
+    }
+
+    /// Returns true if the key was new, otherwise its value is replaced
+    pub fn insert(&self, key: K, value: V) -> bool {
+        let guard = &epoch::pin();
+        let height = self.levels.pick();
+        let mut node = Owned::new(Node {
+            key,
+            value: Atomic::new(value),
+            // linked on level 0 and being linked on the rest
+            refs: AtomicUsize::new(2),
+            tower: (0..height).map(|_| Atomic::null()).collect(),
+        });
+        // on the bottom level first, which puts it in the map
+        let (node, pos) = loop {
+            let pos = self.find(&node.key, guard);
+            if let Some(found) = unsafe { pos.succs[0].as_ref() }.filter(|found| found.key == node.key) {
+                // a removed node is unlinked by the next find, the key then goes in a new one
+                if found.marked() {
+                    continue;
+                }
+                let value = node.value.load(Relaxed, guard);
+                let mut old = found.value.load(Acquire, guard);
+                while !old.is_null() {
+                    match found.value.compare_exchange(old, value, AcqRel, Acquire, guard) {
+                        Ok(_) => {
+                            node.value.store(Shared::null(), Relaxed);
+                            unsafe { guard.defer_destroy(old) };
+                            return false;
+                        }
+                        Err(err) => old = err.current,
+                    }
+                }
+                // the remover took the value first
+                continue;
+            }
+            node.tower[0].store(pos.succs[0], Relaxed);
+            match pos.preds[0].compare_exchange(pos.succs[0], node, Release, Relaxed, guard) {
+                Ok(node) => break (node, pos),
+                Err(err) => node = err.new,
+            }
+        };
+        let n = unsafe { node.deref() };
+        self.len.fetch_add(1, Relaxed);
+
+        // then up its tower, stopping if it is removed meanwhile
+        let mut pos = pos;
+        'levels: for level in 1..height {
+            loop {
+                let next = n.tower[level].load(Acquire, guard);
+                if next.tag() == 1 {
+                    break 'levels;
+                }
+                if next != pos.succs[level] && n.tower[level].compare_exchange(next, pos.succs[level], AcqRel, Acquire, guard).is_err() {
+                    continue;
+                }
+                n.refs.fetch_add(1, Relaxed);
+                if pos.preds[level].compare_exchange(pos.succs[level], node, AcqRel, Acquire, guard).is_ok() {
+                    break;
+                }
+                n.refs.fetch_sub(1, Relaxed);
+                pos = self.find(&n.key, guard);
+                if pos.succs[0] != node {
+                    // it was removed
+                    break 'levels;
+                }
+            }
+        }
+        if n.marked() {
+            // it may have been linked after the remover cleaned up
+            self.find(&n.key, guard);
+        }
+        unsafe { Node::release(node, guard) };
+        true
+    }
+
+    /// Returns a clone of the value that was removed
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: Clone,
+    {
+        let guard = &epoch::pin();
+        loop {
+            let node = self.find_node(key, guard)?;
+            for level in (1..node.tower.len()).rev() {
+                let mut next = node.tower[level].load(Acquire, guard);
+                while next.tag() == 0 {
+                    match node.tower[level].compare_exchange(next, next.with_tag(1), AcqRel, Acquire, guard) {
+                        Ok(_) => break,
+                        Err(err) => next = err.current,
+                    }
+                }
+            }
+            // marking the bottom level is what removes it
+            let next = node.tower[0].load(Acquire, guard);
+            if next.tag() == 0 && node.tower[0].compare_exchange(next, next.with_tag(1), AcqRel, Acquire, guard).is_ok() {
+                self.len.fetch_sub(1, Relaxed);
+                // an insert replacing the value before this wins, the ones after it
+                // see null and make a new node
+                let taken = node.value.swap(Shared::null(), AcqRel, guard);
+                let value = unsafe { taken.deref().clone() };
+                unsafe { guard.defer_destroy(taken) };
+                // unlinks it
+                self.find(key, guard);
+                return Some(value);
+            }
+            // someone else removed it first, is there another one by now?
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        V: Clone,
+    {
+        let guard = &epoch::pin();
+        let node = self.find_node(key, guard)?;
+        // null once it is removed
+        unsafe { node.value.load(Acquire, guard).as_ref() }.cloned()
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find_node(key, &epoch::pin()).is_some()
+    }
+
+    /// The entries with keys in range, in key order. The iterator keeps the
+    /// thread pinned, so it holds back freeing memory while it lives.
+    /// It sees the entries added and removed meanwhile or not, but never
+    /// gives a key twice nor out of order.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V, Q, R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let guard = epoch::pin();
+        let next = match range.start_bound() {
+            Bound::Included(start) => self.find(start, &guard).succs[0],
+            Bound::Excluded(start) => {
+                let next = self.find(start, &guard).succs[0];
+                match unsafe { next.as_ref() } {
+                    Some(node) if node.key.borrow() == start => node.tower[0].load(Acquire, &guard),
+                    _ => next,
+                }
+            }
+            Bound::Unbounded => self.head[0].load(Acquire, &guard),
+        }
+        .as_raw();
+        Range {
+            next,
+            range,
+            guard,
+            _map: PhantomData,
+            _key: PhantomData,
+        }
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V, K, std::ops::RangeFull> {
+        self.range(..)
+    }
+}
+
+impl<K, V> Default for ConcurrentSkipMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Drop for ConcurrentSkipMap<K, V> {
+    fn drop(&mut self) {
+        // no other thread has the map now, every node still linked on some
+        // level is freed here, the others went to the collector already
+        unsafe {
+            let guard = epoch::unprotected();
+            let mut linked = std::collections::HashSet::new();
+            for level in 0..MAX_LEVEL {
+                let mut curr = self.head[level].load(Relaxed, guard);
+                while let Some(node) = curr.as_ref() {
+                    linked.insert(curr.as_raw());
+                    curr = node.tower[level].load(Relaxed, guard).with_tag(0);
+                }
+            }
+            for node in linked {
+                drop(Shared::from(node).into_owned());
+            }
+        }
+    }
+}
+
+impl<K: Ord + Clone + Debug + Send + 'static, V: Clone + Debug + Send + 'static> Debug for ConcurrentSkipMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Walks the bottom level, skipping the removed nodes
+pub struct Range<'a, K, V, Q: ?Sized, R> {
+    // valid while the guard keeps the thread pinned
+    next: *const Node<K, V>,
+    range: R,
+    guard: Guard,
+    _map: PhantomData<&'a ConcurrentSkipMap<K, V>>,
+    _key: PhantomData<fn(&Q)>,
+}
+
+impl<K, V, Q, R> Iterator for Range<'_, K, V, Q, R>
+where
+    K: Borrow<Q> + Clone,
+    V: Clone,
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = unsafe { self.next.as_ref() } {
+            let next = node.tower[0].load(Acquire, &self.guard);
+            self.next = next.with_tag(0).as_raw();
+            if next.tag() == 1 {
+                continue;
+            }
+            let key = node.key.borrow();
+            let past_end = match self.range.end_bound() {
+                Bound::Included(end) => key > end,
+                Bound::Excluded(end) => key >= end,
+                Bound::Unbounded => false,
+            };
+            if past_end {
+                self.next = std::ptr::null();
+                return None;
+            }
+            // removed since the check above
+            let Some(value) = (unsafe { node.value.load(Acquire, &self.guard).as_ref() }) else {
+                continue;
+            };
+            return Some((node.key.clone(), value.clone()));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConcurrentSkipMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let map = ConcurrentSkipMap::with_seed(5);
+        for k in [4, 6, 77, 84, 23, 1, 65, 18] {
+            assert!(map.insert(k, k * 10));
+        }
+        assert!(!map.insert(77, 0));
+        assert_eq!((map.len(), map.get(&77), map.get(&83)), (8, Some(0), None));
+        assert_eq!(map.remove(&6), Some(60));
+        assert_eq!((map.remove(&6), map.remove(&5)), (None, None));
+        assert!(!map.contains_key(&6) && map.contains_key(&18));
+        assert_eq!(format!("{:?}", map), "{1: 10, 4: 40, 18: 180, 23: 230, 65: 650, 77: 0, 84: 840}");
+        assert_eq!(map.range(5..=65).map(|(k, _)| k).collect::<Vec<_>>(), [18, 23, 65]);
+        assert_eq!(map.range(4..18).map(|(k, _)| k).collect::<Vec<_>>(), [4]);
+        assert_eq!(map.range((std::ops::Bound::Excluded(4), std::ops::Bound::Unbounded)).count(), 5);
+    }
+
+    #[test]
+    fn test_threads() {
+        let (threads, per_thread) = if cfg!(miri) { (3, 30) } else { (8, 2000) };
+        let map = Arc::new(ConcurrentSkipMap::with_seed(9));
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let map = Arc::clone(&map);
+                thread::spawn(move || {
+                    // every thread inserts its own keys, then removes the odd
+                    // ones, while also reading the keys of the others
+                    for i in 0..per_thread {
+                        assert!(map.insert(i * threads + t, t));
+                        map.get(&(i * threads + (t + 1) % threads));
+                    }
+                    for i in (1..per_thread).step_by(2) {
+                        assert_eq!(map.remove(&(i * threads + t)), Some(t));
+                    }
+                    let mut last = None;
+                    for (k, _) in map.iter() {
+                        assert!(last < Some(k));
+                        last = Some(k);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let expected: Vec<usize> = (0..threads * per_thread).filter(|k| (k / threads) % 2 == 0).collect();
+        assert_eq!(map.len(), expected.len());
+        assert_eq!(map.iter().map(|(k, _)| k).collect::<Vec<_>>(), expected);
+        assert!(expected.iter().all(|k| map.get(k) == Some(k % threads)));
+    }
+
+    #[test]
+    fn test_replace_while_removing() {
+        let (trials, rounds) = if cfg!(miri) { (2, 50) } else { (500, 200) };
+        for trial in 0..trials {
+            let map = ConcurrentSkipMap::with_seed(trial);
+            let done = AtomicBool::new(false);
+            let removed = thread::scope(|s| {
+                let remover = s.spawn(|| {
+                    let mut removed = vec![];
+                    while !done.load(Ordering::Acquire) {
+                        removed.extend(map.remove(&0));
+                    }
+                    removed
+                });
+                for i in 0..rounds {
+                    map.insert(0, i);
+                }
+                done.store(true, Ordering::Release);
+                remover.join().unwrap()
+            });
+            assert!(removed.windows(2).all(|w| w[0] < w[1]));
+            // the last value is still there or was removed, it is never lost
+            // in a node that was being removed
+            assert!(map.get(&0) == Some(rounds - 1) || removed.last() == Some(&(rounds - 1)));
+            assert_eq!(map.len(), map.iter().count());
+        }
+    }
+}
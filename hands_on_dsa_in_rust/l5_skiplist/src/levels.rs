@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The most levels a skip list gets, plenty for a million keys
+pub const MAX_LEVEL: usize = 20;
+
+// Picks tower heights: every node is on level 1 and on each next level
+// with probability 1/2, up to MAX_LEVEL. The numbers come from splitmix64
+// on an atomic counter, so the concurrent map can share it without a lock
+// and the same seed always gives the same heights.
+pub(crate) struct Levels {
+    state: AtomicU64,
+}
+
+impl Levels {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    pub(crate) fn pick(&self) -> usize {
+        const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut z = self.state.fetch_add(GAMMA, Ordering::Relaxed).wrapping_add(GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z.trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Levels, MAX_LEVEL};
+
+    #[test]
+    fn test_levels() {
+        let (a, b) = (Levels::new(7), Levels::new(7));
+        let picks: Vec<usize> = (0..10000).map(|_| a.pick()).collect();
+        assert!(picks.iter().all(|&l| l == b.pick() && (1..=MAX_LEVEL).contains(&l)));
+        // about half of them go up a level
+        let ones = picks.iter().filter(|&&l| l == 1).count();
+        assert!((4500..5500).contains(&ones), "{} on level 1", ones);
+    }
+}
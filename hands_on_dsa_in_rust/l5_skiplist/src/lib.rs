@@ -0,0 +1,13 @@
+// The unsafe code in concurrent is checked with
+// `MIRIFLAGS="-Zmiri-tree-borrows -Zmiri-ignore-leaks" cargo +nightly miri test`,
+// the leaks being the removed nodes the epoch collector had no time to free
+
+pub mod concurrent;
+mod levels;
+pub mod skip_list;
+pub mod skip_map;
+
+pub use concurrent::ConcurrentSkipMap;
+pub use levels::MAX_LEVEL;
+pub use skip_list::SkipList;
+pub use skip_map::SkipMap;
@@ -0,0 +1,349 @@
+use std::borrow::Borrow;
+use std::fmt::{self, Debug};
+use std::iter::FusedIterator;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+use crate::levels::{Levels, MAX_LEVEL};
+
+// Nodes live in a Vec and point at each other by index,
+// None being the head before the first node
+type Link = Option<usize>;
+
+struct SkipNode<K, V> {
+    key: K,
+    value: V,
+    // the next node on every level this node is on
+    next: Vec<Link>,
+}
+
+/// An ordered map on a skip list. Each node is on a random number of levels,
+/// and every level skips about half of the nodes of the level below it, so
+/// finding a key takes O(log n) steps.
+pub struct SkipMap<K, V> {
+    head: [Link; MAX_LEVEL],
+    nodes: Vec<Option<SkipNode<K, V>>>,
+    // slots of removed nodes, to reuse
+    free: Vec<usize>,
+    len: usize,
+    levels: Levels,
+}
+
+impl<K, V> SkipMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// The same seed and the same inserts build the same levels
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            head: [None; MAX_LEVEL],
+            nodes: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+            levels: Levels::new(seed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.head = [None; MAX_LEVEL];
+        self.nodes.clear();
+        self.free.clear();
+        self.len = 0;
+    }
+
+    /// All entries in key order
+    pub fn iter(&self) -> Range<'_, K, V> {
+        Range {
+            map: self,
+            next: self.head[0],
+            end: None,
+        }
+    }
+
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    fn node(&self, idx: usize) -> &SkipNode<K, V> {
+        self.nodes[idx].as_ref().expect("link to a removed node")
+    }
+
+    fn next(&self, link: Link, level: usize) -> Link {
+        match link {
+            Some(idx) => self.node(idx).next[level],
+            None => self.head[level],
+        }
+    }
+
+    fn set_next(&mut self, link: Link, level: usize, next: Link) {
+        match link {
+            Some(idx) => self.nodes[idx].as_mut().expect("link to a removed node").next[level] = next,
+            None => self.head[level] = next,
+        }
+    }
+
+    // The last node on every level for which before(key) holds
+    fn find_prevs(&self, before: impl Fn(&K) -> bool) -> [Link; MAX_LEVEL] {
+        let mut prevs = [None; MAX_LEVEL];
+        let mut prev = None;
+        for level in (0..MAX_LEVEL).rev() {
+            while let Some(next) = self.next(prev, level) {
+                if !before(&self.node(next).key) {
+                    break;
+                }
+                prev = Some(next);
+            }
+            prevs[level] = prev;
+        }
+        prevs
+    }
+}
+
+impl<K: Ord, V> SkipMap<K, V> {
+    /// Returns the old value if the key was there already
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let prevs = self.find_prevs(|k| *k < key);
+        if let Some(idx) = self.next(prevs[0], 0) {
+            if let Some(node) = self.nodes[idx].as_mut().filter(|node| node.key == key) {
+                return Some(mem::replace(&mut node.value, value));
+            }
+        }
+        let height = self.levels.pick();
+        let next = (0..height).map(|level| self.next(prevs[level], level)).collect();
+        let node = Some(SkipNode { key, value, next });
+        let idx = match self.free.pop() {
+            Some(idx) => {
+                self.nodes[idx] = node;
+                idx
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        for (level, &prev) in prevs.iter().enumerate().take(height) {
+            self.set_next(prev, level, Some(idx));
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let prevs = self.find_prevs(|k| k.borrow() < key);
+        let idx = self.next(prevs[0], 0).filter(|&idx| self.node(idx).key.borrow() == key)?;
+        let node = self.nodes[idx].take()?;
+        // on every level it is on, the one before it now skips it
+        for (level, &next) in node.next.iter().enumerate() {
+            self.set_next(prevs[level], level, next);
+        }
+        self.free.push(idx);
+        self.len -= 1;
+        Some((node.key, node.value))
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let idx = self.find(key)?;
+        Some(&self.node(idx).value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let idx = self.find(key)?;
+        Some(&mut self.nodes[idx].as_mut()?.value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(key).is_some()
+    }
+
+    fn find<Q>(&self, key: &Q) -> Link
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // no need to remember the way down
+        let mut prev = None;
+        for level in (0..MAX_LEVEL).rev() {
+            while let Some(next) = self.next(prev, level) {
+                let next_key = self.node(next).key.borrow();
+                if next_key == key {
+                    return Some(next);
+                }
+                if next_key > key {
+                    break;
+                }
+                prev = Some(next);
+            }
+        }
+        None
+    }
+
+    /// The entries with keys in range, in key order
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let start = self.find_prevs(|k| match range.start_bound() {
+            Bound::Included(start) => k.borrow() < start,
+            Bound::Excluded(start) => k.borrow() <= start,
+            Bound::Unbounded => false,
+        });
+        // the first node after the range, where the walk stops
+        let end = self.find_prevs(|k| match range.end_bound() {
+            Bound::Included(end) => k.borrow() <= end,
+            Bound::Excluded(end) => k.borrow() < end,
+            Bound::Unbounded => true,
+        });
+        let (next, end) = (self.next(start[0], 0), self.next(end[0], 0));
+        // a range that ends before it starts
+        let empty = match (next, end) {
+            (Some(next), Some(end)) => self.node(next).key > self.node(end).key,
+            (None, _) => true,
+            (Some(_), None) => false,
+        };
+        Range {
+            map: self,
+            next: if empty { None } else { next },
+            end,
+        }
+    }
+}
+
+impl<K, V> Default for SkipMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for SkipMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = SkipMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Range<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Walks the bottom level, which has every node
+pub struct Range<'a, K, V> {
+    map: &'a SkipMap<K, V>,
+    next: Link,
+    // the node after the range, None to go on to the end
+    end: Link,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let idx = self.next.filter(|&idx| Some(idx) != self.end)?;
+        let node = self.map.node(idx);
+        self.next = node.next[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V> FusedIterator for Range<'_, K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::SkipMap;
+    use std::ops::Bound::{Excluded, Included, Unbounded};
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = SkipMap::with_seed(3);
+        for k in [4, 6, 77, 84, 23, 1, 65, 18] {
+            assert_eq!(map.insert(k, k * 10), None);
+        }
+        assert_eq!(map.insert(77, 0), Some(770));
+        assert_eq!((map.len(), map.get(&77), map.get(&83)), (8, Some(&0), None));
+        *map.get_mut(&6).unwrap() += 1;
+        assert_eq!(map.remove(&6), Some(61));
+        assert_eq!((map.remove(&6), map.remove(&5)), (None, None));
+        assert!(!map.contains_key(&6) && map.contains_key(&18));
+        assert_eq!(map.first(), Some((&1, &10)));
+        // the slot of 6 is used again
+        map.insert(7, 70);
+        assert_eq!(map.nodes.len(), 8);
+        assert_eq!(format!("{:?}", map), "{1: 10, 4: 40, 7: 70, 18: 180, 23: 230, 65: 650, 77: 0, 84: 840}");
+        map.clear();
+        assert!(map.is_empty() && map.iter().next().is_none());
+    }
+
+    #[test]
+    fn test_range() {
+        let map: SkipMap<String, usize> = ["kiwi", "fig", "apple", "lime", "date", "pear"]
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.to_string(), i))
+            .collect();
+        fn keys<'a>(r: super::Range<'a, String, usize>) -> Vec<&'a str> {
+            r.map(|(k, _)| k.as_str()).collect()
+        }
+        assert_eq!(keys(map.range::<str, _>((Included("date"), Excluded("lime")))), ["date", "fig", "kiwi"]);
+        assert_eq!(keys(map.range::<str, _>((Included("e"), Included("lime")))), ["fig", "kiwi", "lime"]);
+        assert_eq!(keys(map.range::<str, _>((Excluded("lime"), Unbounded))), ["pear"]);
+        assert_eq!(keys(map.range::<str, _>((Unbounded, Excluded("b")))), ["apple"]);
+        assert_eq!(keys(map.range::<str, _>((Included("x"), Unbounded))), Vec::<&str>::new());
+        assert_eq!(keys(map.range::<str, _>((Included("g"), Excluded("g")))), Vec::<&str>::new());
+        assert_eq!(keys(map.range::<str, _>((Included("m"), Excluded("c")))), Vec::<&str>::new());
+        assert_eq!(keys(map.range::<String, _>(..)).len(), 6);
+    }
+}
@@ -0,0 +1,87 @@
+//Both maps against std's BTreeMap on random operations
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::thread;
+
+use l5_skiplist::{ConcurrentSkipMap, SkipMap};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[test]
+fn skip_map_matches_btree_map() {
+    for seed in 0..10 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut map = SkipMap::with_seed(seed);
+        let mut std = BTreeMap::new();
+        for _ in 0..2000 {
+            let key = rng.gen_range(0..300u32);
+            match rng.gen_range(0..3) {
+                0 | 1 => assert_eq!(map.insert(key, key + 1), std.insert(key, key + 1)),
+                _ => assert_eq!(map.remove(&key), std.remove(&key)),
+            }
+            assert_eq!(map.get(&key), std.get(&key));
+            assert_eq!(map.len(), std.len());
+        }
+        assert!(map.iter().eq(std.iter()));
+        for _ in 0..100 {
+            let (a, b) = (rng.gen_range(0..300), rng.gen_range(0..300));
+            let (start, end) = (a.min(b), a.max(b));
+            assert!(map.range(start..end).eq(std.range(start..end)));
+            assert!(map.range(start..=end).eq(std.range(start..=end)));
+            assert!(map.range(..end).eq(std.range(..end)));
+            assert!(map.range(start..).eq(std.range(start..)));
+        }
+    }
+}
+
+#[test]
+fn concurrent_map_matches_btree_map() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let map = ConcurrentSkipMap::with_seed(7);
+    let mut std = BTreeMap::new();
+    for _ in 0..2000 {
+        let key = rng.gen_range(0..300u32);
+        match rng.gen_range(0..3) {
+            0 | 1 => assert_eq!(map.insert(key, key + 1), std.insert(key, key + 1).is_none()),
+            _ => assert_eq!(map.remove(&key), std.remove(&key)),
+        }
+        assert_eq!(map.get(&key), std.get(&key).copied());
+    }
+    assert_eq!(map.len(), std.len());
+    assert!(map.iter().eq(std.clone().into_iter()));
+    assert!(map.range(100..200).eq(std.range(100..200).map(|(k, v)| (*k, *v))));
+}
+
+#[test]
+fn threads_fight_over_the_same_keys() {
+    //every thread inserts and removes the same few keys, which races the
+    //linking of towers against their removal
+    let (threads, ops) = if cfg!(miri) { (3, 200) } else { (8, 20000) };
+    let map = Arc::new(ConcurrentSkipMap::with_seed(11));
+    let handles: Vec<_> = (0..threads)
+        .map(|t| {
+            let map = Arc::clone(&map);
+            thread::spawn(move || {
+                let mut rng = StdRng::seed_from_u64(t);
+                for _ in 0..ops {
+                    let key = rng.gen_range(0..32u32);
+                    if rng.gen_bool(0.5) {
+                        map.insert(key, t);
+                    } else {
+                        map.remove(&key);
+                    }
+                    if let Some(v) = map.get(&key) {
+                        assert!(v < threads);
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let keys: Vec<u32> = map.iter().map(|(k, _)| k).collect();
+    assert!(keys.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(keys.len(), map.len());
+    assert!(keys.iter().all(|k| map.contains_key(k)));
+}
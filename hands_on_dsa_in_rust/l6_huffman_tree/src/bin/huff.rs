@@ -0,0 +1,130 @@
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use l6_huffman_tree::{HuffReader, HuffWriter};
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+use tempfile::NamedTempFile;
+
+type MyResult<T> = Result<T, Box<dyn Error>>;
+
+#[derive(Debug)]
+struct Config {
+    decompress: bool,
+    in_file: String,
+    out_file: String,
+    force: bool,
+}
+
+fn main() {
+    if let Err(e) = get_args().and_then(run) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(config: Config) -> MyResult<()> {
+    let input = open(&config.in_file).map_err(|e| format!("{}: {}", config.in_file, e))?;
+    match config.out_file.as_str() {
+        "-" => process(&config, input, BufWriter::new(io::stdout().lock())),
+        out_name => {
+            if !config.force && Path::new(out_name).exists() {
+                return Err(format!("{}: already exists, use --force to overwrite it", out_name).into());
+            }
+            //written next to the output and renamed at the end, so a bad input does not
+            //clobber it, and the output can be the input
+            let dir = Path::new(out_name).parent().filter(|dir| !dir.as_os_str().is_empty());
+            let out_file = NamedTempFile::new_in(dir.unwrap_or(Path::new(".")))?;
+            process(&config, input, BufWriter::new(out_file.as_file()))?;
+            out_file.persist(out_name).map_err(|e| format!("{}: {}", out_name, e))?;
+            Ok(())
+        }
+    }
+}
+
+fn process(config: &Config, mut input: Box<dyn Read>, mut out: impl Write) -> MyResult<()> {
+    let in_err = |e: io::Error| format!("{}: {}", config.in_file, e);
+    if config.decompress {
+        io::copy(&mut HuffReader::new(input), &mut out).map_err(in_err)?;
+        out.flush()?;
+    } else {
+        let mut writer = HuffWriter::new(out);
+        io::copy(&mut input, &mut writer).map_err(in_err)?;
+        writer.finish()?.flush()?;
+    }
+    Ok(())
+}
+
+fn get_args() -> MyResult<Config> {
+    let files = |cmd: Command, out_help: &'static str| {
+        cmd.arg(
+            Arg::new("in_file")
+                .value_name("IN_FILE")
+                .help("Input file")
+                .default_value("-")
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("out_file")
+                .value_name("OUT_FILE")
+                .short('o')
+                .long("output")
+                .help(out_help)
+                .action(ArgAction::Set)
+        )
+        .arg(
+            Arg::new("force")
+                .short('f')
+                .long("force")
+                .help("Overwrite the output file if it exists")
+                .action(ArgAction::SetTrue)
+        )
+    };
+    let matches = Command::new("huff")
+        .version("0.1.0")
+        .author("Zhang Tianwei <zhangtianwei1015@gmail.com>")
+        .about("Rust Huffman coding, for any file")
+        .subcommand_required(true)
+        .subcommand(files(
+            Command::new("compress").about("Compress a file"),
+            "Output file [default: IN_FILE.huf, or stdout for stdin]",
+        ))
+        .subcommand(files(
+            Command::new("decompress").about("Decompress a file"),
+            "Output file [default: IN_FILE without .huf, or stdout for stdin]",
+        ))
+        .get_matches();
+    let (name, matches) = matches.subcommand().unwrap();
+    let decompress = name == "decompress";
+    let in_file = matches.get_one::<String>("in_file").unwrap().to_string();
+    Ok(Config {
+        out_file: out_file(matches, &in_file, decompress)?,
+        decompress,
+        force: matches.get_flag("force"),
+        in_file,
+    })
+}
+
+fn out_file(matches: &ArgMatches, in_file: &str, decompress: bool) -> MyResult<String> {
+    if let Some(out_file) = matches.get_one::<String>("out_file") {
+        return Ok(out_file.to_string());
+    }
+    match (in_file, decompress) {
+        ("-", _) => Ok("-".to_string()),
+        (_, false) => Ok(format!("{}.huf", in_file)),
+        (_, true) => match in_file.strip_suffix(".huf") {
+            Some(out_file) if !out_file.is_empty() => Ok(out_file.to_string()),
+            _ => Err(format!("{}: no .huf suffix, give an output file with -o", in_file).into()),
+        },
+    }
+}
+
+fn open(filename: &str) -> io::Result<Box<dyn Read>> {
+    match filename {
+        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
+        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+    }
+}
+
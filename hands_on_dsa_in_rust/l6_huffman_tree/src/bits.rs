@@ -0,0 +1,149 @@
+use std::io::{self, Read, Write};
+
+/// Packs codes into bytes, the first bit going into the highest bit of a byte
+pub struct BitWriter<W: Write> {
+    inner: W,
+    // bits not written yet, in the low nbits bits
+    acc: u64,
+    nbits: u32,
+}
+
+impl<W: Write> BitWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            acc: 0,
+            nbits: 0,
+        }
+    }
+
+    /// Writes the low len bits of code, the highest one first. len is at most 56
+    pub fn write_bits(&mut self, code: u64, len: u32) -> io::Result<()> {
+        assert!(len <= 56, "codes are at most 56 bits");
+        if len == 0 {
+            return Ok(());
+        }
+        self.acc = (self.acc << len) | (code & ((1 << len) - 1));
+        self.nbits += len;
+        while self.nbits >= 8 {
+            self.nbits -= 8;
+            self.inner.write_all(&[(self.acc >> self.nbits) as u8])?;
+        }
+        self.acc &= (1 << self.nbits) - 1;
+        Ok(())
+    }
+
+    pub fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.write_bits(bit as u64, 1)
+    }
+
+    /// Writes the last byte, padded with zeros, and gives back the writer
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.nbits > 0 {
+            let pad = 8 - self.nbits;
+            self.write_bits(0, pad)?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads bits the way BitWriter writes them. It reads ahead up to 8 bytes
+/// to be able to peek at the next bits.
+pub struct BitReader<R: Read> {
+    inner: R,
+    // the next bit is the highest one
+    acc: u64,
+    nbits: u32,
+}
+
+impl<R: Read> BitReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            acc: 0,
+            nbits: 0,
+        }
+    }
+
+    fn refill(&mut self) -> io::Result<()> {
+        while self.nbits <= 56 {
+            let mut buf = [0];
+            if self.inner.read(&mut buf)? == 0 {
+                break;
+            }
+            self.acc |= (buf[0] as u64) << (56 - self.nbits);
+            self.nbits += 8;
+        }
+        Ok(())
+    }
+
+    /// The next len bits, at most 56, without reading them, and how many of
+    /// them there are. Past the end of the input the bits are zeros.
+    pub fn peek_bits(&mut self, len: u32) -> io::Result<(u64, u32)> {
+        assert!(len <= 56, "peeks at 56 bits at most");
+        if self.nbits < len {
+            self.refill()?;
+        }
+        if len == 0 {
+            return Ok((0, 0));
+        }
+        Ok((self.acc >> (64 - len), self.nbits.min(len)))
+    }
+
+    /// Skips len bits after peeking at them, len being at most what is there
+    pub fn consume(&mut self, len: u32) {
+        assert!(len <= self.nbits, "consumes bits that are not there");
+        self.acc = self.acc.checked_shl(len).unwrap_or(0);
+        self.nbits -= len;
+    }
+
+    /// None at the end of the input
+    pub fn read_bit(&mut self) -> io::Result<Option<bool>> {
+        match self.peek_bits(1)? {
+            (_, 0) => Ok(None),
+            (bit, _) => {
+                self.consume(1);
+                Ok(Some(bit == 1))
+            }
+        }
+    }
+
+    /// Reads len bits, at most 56, the highest one first, failing if the
+    /// input ends before
+    pub fn read_bits(&mut self, len: u32) -> io::Result<u64> {
+        let (bits, there) = self.peek_bits(len)?;
+        if there < len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.consume(len);
+        Ok(bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitReader, BitWriter};
+
+    #[test]
+    fn test_round_trip() {
+        let mut w = BitWriter::new(Vec::new());
+        w.write_bits(0b101, 3).unwrap();
+        w.write_bit(true).unwrap();
+        w.write_bits(0xabcd, 16).unwrap();
+        w.write_bits(0b01, 2).unwrap();
+        let bytes = w.finish().unwrap();
+        assert_eq!(bytes, [0b1011_1010, 0b1011_1100, 0b1101_0100]);
+
+        let mut r = BitReader::new(&bytes[..]);
+        assert_eq!(r.read_bits(3).unwrap(), 0b101);
+        assert_eq!(r.read_bit().unwrap(), Some(true));
+        assert_eq!(r.read_bits(16).unwrap(), 0xabcd);
+        assert_eq!(r.read_bits(2).unwrap(), 0b01);
+        assert_eq!(r.peek_bits(4).unwrap(), (0, 2));
+        // the padding
+        assert_eq!(r.read_bits(2).unwrap(), 0);
+        assert_eq!(r.read_bit().unwrap(), None);
+        assert!(r.read_bits(1).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+use std::io::{self, Read, Write};
+
+use crate::bits::BitReader;
+use crate::tree::build_tree_from_counts;
+
+/// The longest code a table may have, as in DEFLATE. Longer codes are made
+/// shorter by limit_lengths, so any data fits.
+pub const MAX_CODE_LEN: usize = 15;
+
+/// A canonical Huffman code for bytes. Only the code length of every byte is
+/// needed to rebuild it: the codes are given out in order of length, then of
+/// byte, each one the previous one plus one, shifted left when it gets longer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeTable {
+    // 0 for the bytes that are not used
+    lengths: [u8; 256],
+    codes: [u64; 256],
+}
+
+impl CodeTable {
+    /// The code for the bytes in data, from their Huffman tree
+    pub fn from_data(data: &[u8]) -> Self {
+        let mut counts = [0; 256];
+        for &b in data {
+            counts[b as usize] += 1;
+        }
+        Self::from_counts(&counts)
+    }
+
+    /// The code for bytes that come counts[byte] times each
+    pub fn from_counts(counts: &[u64; 256]) -> Self {
+        let mut lengths = [0; 256];
+        let used = (0..=255u8).map(|b| (b, counts[b as usize])).filter(|&(_, n)| n > 0);
+        if let Some(tree) = build_tree_from_counts(used) {
+            for (byte, len) in tree.code_lengths() {
+                // a tree of one leaf has a code of no bits, give it one.
+                // 256 leaves are never deeper than 255
+                lengths[byte as usize] = len.max(1) as u8;
+            }
+        }
+        limit_lengths(&mut lengths, counts);
+        Self::from_lengths(lengths).expect("the lengths of a Huffman tree are a prefix code")
+    }
+
+    /// Fails if the lengths are too long or too short to be a prefix code
+    pub fn from_lengths(lengths: [u8; 256]) -> io::Result<Self> {
+        // every code of length len takes up 2^(MAX - len) of the codes of length MAX
+        let mut used: u128 = 0;
+        for &len in lengths.iter().filter(|&&len| len > 0) {
+            if len as usize > MAX_CODE_LEN {
+                return Err(invalid("a code is too long"));
+            }
+            used += 1 << (MAX_CODE_LEN - len as usize);
+        }
+        if used > 1 << MAX_CODE_LEN {
+            return Err(invalid("the code lengths are not a prefix code"));
+        }
+        let mut codes = [0; 256];
+        let mut code = 0u64;
+        let mut prev_len = 0;
+        for byte in sorted_symbols(&lengths) {
+            let len = lengths[byte as usize];
+            code <<= len - prev_len;
+            codes[byte as usize] = code;
+            code += 1;
+            prev_len = len;
+        }
+        Ok(Self { lengths, codes })
+    }
+
+    /// The code and its length in bits, None for a byte not in the table
+    pub fn code(&self, byte: u8) -> Option<(u64, u32)> {
+        match self.lengths[byte as usize] {
+            0 => None,
+            len => Some((self.codes[byte as usize], len as u32)),
+        }
+    }
+
+    pub fn lengths(&self) -> &[u8; 256] {
+        &self.lengths
+    }
+
+    /// Writes the number of bytes used as a u16, then every byte used with its length
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let used: Vec<u8> = (0..=255).filter(|&b| self.lengths[b as usize] > 0).collect();
+        w.write_all(&(used.len() as u16).to_le_bytes())?;
+        for b in used {
+            w.write_all(&[b, self.lengths[b as usize]])?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(mut r: R) -> io::Result<Self> {
+        let mut count = [0; 2];
+        r.read_exact(&mut count)?;
+        let count = u16::from_le_bytes(count);
+        if count > 256 {
+            return Err(invalid("more than 256 codes"));
+        }
+        let mut lengths = [0; 256];
+        for _ in 0..count {
+            let mut pair = [0; 2];
+            r.read_exact(&mut pair)?;
+            lengths[pair[0] as usize] = pair[1];
+        }
+        Self::from_lengths(lengths)
+    }
+
+    pub fn decoder(&self) -> Decoder {
+        let mut table = vec![(0, 0); 1 << MAX_CODE_LEN];
+        for b in 0..=255u8 {
+            if let Some((code, len)) = self.code(b) {
+                // every entry starting with the code
+                let free = MAX_CODE_LEN as u32 - len;
+                let start = (code << free) as usize;
+                table[start..start + (1 << free)].fill((b, len as u8));
+            }
+        }
+        Decoder { table }
+    }
+}
+
+/// Makes the codes at most MAX_CODE_LEN long, the way miniz does: the codes
+/// that are too long get the longest length, then codes are made longer one at
+/// a time until they are a prefix code again. The bytes that come most often
+/// get the shortest of the new lengths.
+pub fn limit_lengths(lengths: &mut [u8; 256], counts: &[u64; 256]) {
+    if lengths.iter().all(|&len| len as usize <= MAX_CODE_LEN) {
+        return;
+    }
+    // how many codes of every length
+    let mut num = [0u64; MAX_CODE_LEN + 1];
+    for &len in lengths.iter().filter(|&&len| len > 0) {
+        num[(len as usize).min(MAX_CODE_LEN)] += 1;
+    }
+    // every code of length len takes up 2^(MAX - len) of the codes of length MAX
+    let mut used: u64 = (1..=MAX_CODE_LEN).map(|len| num[len] << (MAX_CODE_LEN - len)).sum();
+    while used > 1 << MAX_CODE_LEN {
+        // one code of length MAX less, and one shorter code split in two
+        num[MAX_CODE_LEN] -= 1;
+        let len = (1..MAX_CODE_LEN).rev().find(|&len| num[len] > 0).expect("a shorter code to split");
+        num[len] -= 1;
+        num[len + 1] += 2;
+        used -= 1;
+    }
+    let mut symbols: Vec<u8> = (0..=255).filter(|&b| lengths[b as usize] > 0).collect();
+    symbols.sort_by_key(|&b| std::cmp::Reverse(counts[b as usize]));
+    let mut symbols = symbols.into_iter();
+    for (len, &n) in num.iter().enumerate() {
+        for b in symbols.by_ref().take(n as usize) {
+            lengths[b as usize] = len as u8;
+        }
+    }
+}
+
+// the bytes used, in the order their codes are given out
+fn sorted_symbols(lengths: &[u8; 256]) -> Vec<u8> {
+    let mut symbols: Vec<u8> = (0..=255).filter(|&b| lengths[b as usize] > 0).collect();
+    symbols.sort_by_key(|&b| lengths[b as usize]);
+    symbols
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Decodes a code at a time: it peeks at the next MAX_CODE_LEN bits, and
+/// looks up the byte whose code they start with, and how long that code is
+pub struct Decoder {
+    // (byte, length), a length of 0 for bits that are no code
+    table: Vec<(u8, u8)>,
+}
+
+impl Decoder {
+    pub fn decode<R: Read>(&self, bits: &mut BitReader<R>) -> io::Result<u8> {
+        let (peek, there) = bits.peek_bits(MAX_CODE_LEN as u32)?;
+        let (byte, len) = self.table[peek as usize];
+        if there == 0 || len as u32 > there {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        if len == 0 {
+            return Err(invalid("no byte has this code"));
+        }
+        bits.consume(len as u32);
+        Ok(byte)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{limit_lengths, CodeTable, MAX_CODE_LEN};
+    use crate::bits::{BitReader, BitWriter};
+
+    #[test]
+    fn test_canonical_codes() {
+        let mut lengths = [0; 256];
+        for (b, len) in [(b'a', 2), (b'b', 1), (b'c', 3), (b'd', 3)] {
+            lengths[b as usize] = len;
+        }
+        let table = CodeTable::from_lengths(lengths).unwrap();
+        assert_eq!(table.code(b'b'), Some((0b0, 1)));
+        assert_eq!(table.code(b'a'), Some((0b10, 2)));
+        assert_eq!(table.code(b'c'), Some((0b110, 3)));
+        assert_eq!(table.code(b'd'), Some((0b111, 3)));
+        assert_eq!(table.code(b'e'), None);
+        // one more would not fit
+        lengths[b'e' as usize] = 3;
+        assert!(CodeTable::from_lengths(lengths).is_err());
+    }
+
+    #[test]
+    fn test_table_round_trip() {
+        let data = b"at an apple app";
+        let table = CodeTable::from_data(data);
+        let mut header = Vec::new();
+        table.write_to(&mut header).unwrap();
+        // 7 different bytes
+        assert_eq!(header.len(), 2 + 7 * 2);
+        assert_eq!(CodeTable::read_from(&header[..]).unwrap(), table);
+
+        let mut w = BitWriter::new(Vec::new());
+        for &b in data {
+            let (code, len) = table.code(b).unwrap();
+            w.write_bits(code, len).unwrap();
+        }
+        let bytes = w.finish().unwrap();
+        let (decoder, mut r) = (table.decoder(), BitReader::new(&bytes[..]));
+        let decoded: Vec<u8> = data.iter().map(|_| decoder.decode(&mut r).unwrap()).collect();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_one_byte() {
+        let table = CodeTable::from_data(b"aaaa");
+        assert_eq!(table.code(b'a'), Some((0, 1)));
+        assert_eq!(CodeTable::from_data(b"").code(b'a'), None);
+    }
+
+    #[test]
+    fn test_length_limit() {
+        // fibonacci counts make a tree as deep as there are bytes
+        let mut counts = [0; 256];
+        let (mut a, mut b) = (1, 1);
+        for count in counts.iter_mut().take(30) {
+            *count = a;
+            (a, b) = (b, a + b);
+        }
+        let table = CodeTable::from_counts(&counts);
+        let lengths = table.lengths();
+        assert_eq!(lengths.iter().max(), Some(&(MAX_CODE_LEN as u8)));
+        // still a complete prefix code, the most common byte still the shortest
+        let used: u64 = lengths.iter().filter(|&&len| len > 0).map(|&len| 1 << (MAX_CODE_LEN - len as usize)).sum();
+        assert_eq!(used, 1 << MAX_CODE_LEN);
+        assert_eq!(lengths[29], 1);
+        assert!(lengths[..30].windows(2).all(|w| w[0] >= w[1]));
+
+        // short enough codes are left alone
+        let mut lengths = [0; 256];
+        lengths[..3].copy_from_slice(&[1, 2, 2]);
+        limit_lengths(&mut lengths, &counts);
+        assert_eq!(lengths[..3], [1, 2, 2]);
+    }
+}
@@ -0,0 +1,54 @@
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use crate::stream::{HuffReader, HuffWriter};
+
+/// The first bytes of a compressed file, the last one being the version
+pub const MAGIC: &[u8; 4] = b"HUF\x02";
+
+// A compressed file is
+//   MAGIC
+//   blocks, each of
+//     the number of bytes in the block, u32 little endian, never 0
+//     the length of the codes, u32 little endian
+//     the code table, see CodeTable::write_to
+//   then the codes of the bytes, packed into bytes, the last one padded with zeros
+//   0, u32
+// See HuffWriter and HuffReader for compressing as a stream.
+
+/// Writes data compressed to out, and gives back out
+pub fn compress<W: Write>(data: &[u8], out: W) -> io::Result<W> {
+    let mut w = HuffWriter::new(BufWriter::new(out));
+    w.write_all(data)?;
+    w.finish()?.into_inner().map_err(|e| e.into_error())
+}
+
+pub fn decompress<R: Read>(input: R) -> io::Result<Vec<u8>> {
+    let mut res = Vec::new();
+    HuffReader::new(BufReader::new(input)).read_to_end(&mut res)?;
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, decompress, MAGIC};
+
+    #[test]
+    fn test_round_trip() {
+        let data = b"at an apple app";
+        let compressed = compress(data, Vec::new()).unwrap();
+        assert!(compressed.starts_with(MAGIC));
+        // 4 + 4 + 4 + 16 before the 5 bytes of codes instead of 15, then 4 for the end
+        assert_eq!(compressed.len(), 28 + 5 + 4);
+        assert_eq!(decompress(&compressed[..]).unwrap(), data);
+        assert_eq!(decompress(&compress(b"", Vec::new()).unwrap()[..]).unwrap(), b"");
+    }
+
+    #[test]
+    fn test_bad_input() {
+        assert!(decompress(&b"HUF\x01"[..]).is_err());
+        assert!(decompress(&b"PNG\x02"[..]).is_err());
+        let compressed = compress(b"some data", Vec::new()).unwrap();
+        // cut short
+        assert!(decompress(&compressed[..compressed.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+pub mod bits;
+pub mod code;
+pub mod format;
+pub mod stream;
+pub mod tree;
+
+pub use bits::{BitReader, BitWriter};
+pub use code::CodeTable;
+pub use format::{compress, decompress};
+pub use stream::{HuffReader, HuffWriter};
+pub use tree::{build_tree, build_tree_from, build_tree_from_counts, HuffNode};
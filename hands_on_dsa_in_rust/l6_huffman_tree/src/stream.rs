@@ -0,0 +1,292 @@
+use std::io::{self, Read, Write};
+use std::mem;
+
+use crate::bits::{BitReader, BitWriter};
+use crate::code::{CodeTable, MAX_CODE_LEN};
+use crate::format::MAGIC;
+
+/// How much data a block holds at most, each block having its own code
+pub const BLOCK_SIZE: usize = 64 * 1024;
+
+/// Compresses what is written to it into the writer it wraps. The data is
+/// kept until there is a block of it, flush writes out a smaller block.
+/// finish must be called to end the stream, dropping the writer does it too
+/// but cannot report an error.
+pub struct HuffWriter<W: Write> {
+    // None once finished
+    inner: Option<W>,
+    block: Vec<u8>,
+    block_size: usize,
+    // the codes of a block, before they are written at once
+    payload: Vec<u8>,
+    started: bool,
+}
+
+impl<W: Write> HuffWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self::with_block_size(inner, BLOCK_SIZE)
+    }
+
+    /// Smaller blocks adapt to the data sooner, but every one has a code table
+    pub fn with_block_size(inner: W, block_size: usize) -> Self {
+        assert!(block_size > 0, "blocks hold at least a byte");
+        Self {
+            inner: Some(inner),
+            block: Vec::new(),
+            block_size,
+            payload: Vec::new(),
+            started: false,
+        }
+    }
+
+    fn inner(&mut self) -> &mut W {
+        self.inner.as_mut().expect("the writer is finished")
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        if !self.started {
+            self.inner().write_all(MAGIC)?;
+            self.started = true;
+        }
+        Ok(())
+    }
+
+    fn write_block(&mut self) -> io::Result<()> {
+        self.start()?;
+        let mut counts = [0; 256];
+        for &b in &self.block {
+            counts[b as usize] += 1;
+        }
+        let table = CodeTable::from_counts(&counts);
+        let mut bits = BitWriter::new(mem::take(&mut self.payload));
+        for &b in &self.block {
+            // every byte of the block is in the table
+            let (code, len) = table.code(b).unwrap();
+            bits.write_bits(code, len)?;
+        }
+        self.payload = bits.finish()?;
+        let inner = self.inner.as_mut().expect("the writer is finished");
+        inner.write_all(&(self.block.len() as u32).to_le_bytes())?;
+        inner.write_all(&(self.payload.len() as u32).to_le_bytes())?;
+        table.write_to(&mut *inner)?;
+        inner.write_all(&self.payload)?;
+        self.payload.clear();
+        self.block.clear();
+        Ok(())
+    }
+
+    fn write_end(&mut self) -> io::Result<()> {
+        if !self.block.is_empty() {
+            self.write_block()?;
+        }
+        self.start()?;
+        self.inner().write_all(&0u32.to_le_bytes())?;
+        self.inner().flush()
+    }
+
+    // the writer is taken even on an error, so drop does not end the stream twice
+    fn end(&mut self) -> io::Result<W> {
+        let result = self.write_end();
+        let inner = self.inner.take().expect("the writer is finished");
+        result.map(|()| inner)
+    }
+
+    /// Writes the last block and the end of the stream, and gives back the writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.end()
+    }
+}
+
+impl<W: Write> Write for HuffWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.block_size - self.block.len());
+        self.block.extend_from_slice(&buf[..n]);
+        if self.block.len() == self.block_size {
+            self.write_block()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.block.is_empty() {
+            self.write_block()?;
+        }
+        self.inner().flush()
+    }
+}
+
+impl<W: Write> Drop for HuffWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.end();
+        }
+    }
+}
+
+/// Decompresses what it reads from the reader it wraps, a block at a time
+pub struct HuffReader<R: Read> {
+    inner: R,
+    block: Vec<u8>,
+    // how much of block was read
+    pos: usize,
+    payload: Vec<u8>,
+    started: bool,
+    done: bool,
+}
+
+impl<R: Read> HuffReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            block: Vec::new(),
+            pos: 0,
+            payload: Vec::new(),
+            started: false,
+            done: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        let mut n = [0; 4];
+        self.inner.read_exact(&mut n)?;
+        Ok(u32::from_le_bytes(n))
+    }
+
+    fn next_block(&mut self) -> io::Result<()> {
+        if !self.started {
+            let mut magic = [0; 4];
+            self.inner.read_exact(&mut magic)?;
+            if magic[..3] == MAGIC[..3] && magic[3] != MAGIC[3] {
+                return Err(invalid("a compressed file of another version"));
+            }
+            if &magic != MAGIC {
+                return Err(invalid("not a compressed file"));
+            }
+            self.started = true;
+        }
+        self.block.clear();
+        self.pos = 0;
+        let count = self.read_u32()?;
+        if count == 0 {
+            self.done = true;
+            return Ok(());
+        }
+        let len = self.read_u32()?;
+        // not trusting the lengths too much before the data is there
+        if len as u64 > count as u64 * MAX_CODE_LEN as u64 / 8 + 1 {
+            return Err(invalid("a block longer than its codes"));
+        }
+        let decoder = CodeTable::read_from(&mut self.inner)?.decoder();
+        self.payload.clear();
+        (&mut self.inner).take(len as u64).read_to_end(&mut self.payload)?;
+        if self.payload.len() < len as usize {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let mut bits = BitReader::new(&self.payload[..]);
+        for _ in 0..count {
+            self.block.push(decoder.decode(&mut bits)?);
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for HuffReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.block.len() && !self.done {
+            self.next_block()?;
+        }
+        let n = buf.len().min(self.block.len() - self.pos);
+        buf[..n].copy_from_slice(&self.block[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HuffReader, HuffWriter};
+    use std::io::{self, Read, Write};
+
+    #[test]
+    fn test_blocks() {
+        let data = b"at an apple app, and an apple pie";
+        let mut w = HuffWriter::with_block_size(Vec::new(), 10);
+        for chunk in data.chunks(3) {
+            w.write_all(chunk).unwrap();
+        }
+        let compressed = w.finish().unwrap();
+
+        let mut r = HuffReader::new(&compressed[..]);
+        let mut res = Vec::new();
+        let mut buf = [0; 7];
+        loop {
+            match r.read(&mut buf).unwrap() {
+                0 => break,
+                n => res.extend_from_slice(&buf[..n]),
+            }
+        }
+        assert_eq!(res, data);
+        // nothing after the end
+        assert_eq!(r.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_flush() {
+        let mut w = HuffWriter::new(Vec::new());
+        w.write_all(b"some data").unwrap();
+        w.flush().unwrap();
+        // the block so far can be read before the stream ends
+        let mut r = HuffReader::new(&w.inner.as_ref().unwrap()[..]);
+        let mut buf = [0; 9];
+        r.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"some data");
+        assert!(r.read(&mut buf).is_err());
+
+        w.write_all(b" and more").unwrap();
+        let compressed = w.finish().unwrap();
+        let mut res = String::new();
+        HuffReader::new(&compressed[..]).read_to_string(&mut res).unwrap();
+        assert_eq!(res, "some data and more");
+    }
+
+    #[test]
+    fn test_drop_finishes() {
+        let mut compressed = Vec::new();
+        HuffWriter::new(&mut compressed).write_all(b"dropped").unwrap();
+        let mut res = Vec::new();
+        HuffReader::new(&compressed[..]).read_to_end(&mut res).unwrap();
+        assert_eq!(res, b"dropped");
+    }
+
+    // fails to flush, and counts the tries
+    struct FailingFlush<'a>(&'a mut usize);
+
+    impl Write for FailingFlush<'_> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            *self.0 += 1;
+            Err(io::Error::other("flush failed"))
+        }
+    }
+
+    #[test]
+    fn test_failed_finish_ends_once() {
+        let mut flushes = 0;
+        let mut w = HuffWriter::new(FailingFlush(&mut flushes));
+        w.write_all(b"some data").unwrap();
+        assert!(w.finish().is_err());
+        // dropping the writer does not write another end
+        assert_eq!(flushes, 1);
+    }
+}
@@ -0,0 +1,224 @@
+use std::{cmp::Ordering, collections::{BTreeMap, BinaryHeap}, fmt::Display};
+
+// Leaves are chars by default, bytes for files
+#[derive(Debug)]
+pub enum HuffNode<T = char> {
+    Tree(Box<HuffNode<T>>, Box<HuffNode<T>>),
+    Leaf(T)
+}
+
+impl<T: Display> HuffNode<T> {
+    pub fn print_lfirst(&self, depth: i32, dir: char) {
+        match self {
+            Self::Tree(l, r) => {
+                l.print_lfirst(depth + 1, '/');
+                let mut spc = String::new();
+                for _ in 0..depth {
+                    spc.push('.');
+                }
+                println!("{}{}*", spc, dir);
+                r.print_lfirst(depth+1, '\\');
+            }
+            Self::Leaf(c) => {
+                let mut spc = String::new();
+                for _ in 0..depth {
+                    spc.push('.');
+                }
+                println!("{}{}{}",spc, dir, c);
+            }
+        }
+    }
+}
+
+impl<T: Copy + PartialEq> HuffNode<T> {
+    /// How deep every leaf is, which is the length of its code
+    pub fn code_lengths(&self) -> Vec<(T, usize)> {
+        let mut res = Vec::new();
+        let mut stack = vec![(self, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            match node {
+                Self::Tree(l, r) => {
+                    stack.push((r, depth + 1));
+                    stack.push((l, depth + 1));
+                }
+                Self::Leaf(c) => res.push((*c, depth)),
+            }
+        }
+        res
+    }
+
+    /// The code of every leaf, to look up instead of searching the tree
+    pub fn codes(&self) -> BTreeMap<T, Vec<char>>
+    where
+        T: Ord,
+    {
+        let mut res = BTreeMap::new();
+        let mut stack = vec![(self, Vec::new())];
+        while let Some((node, code)) = stack.pop() {
+            match node {
+                Self::Tree(l, r) => {
+                    let mut r_code = code.clone();
+                    r_code.push('1');
+                    stack.push((r, r_code));
+                    let mut l_code = code;
+                    l_code.push('0');
+                    stack.push((l, l_code));
+                }
+                Self::Leaf(c) => {
+                    res.insert(*c, code);
+                }
+            }
+        }
+        res
+    }
+
+    // searches the whole tree, use codes() for more than one
+    pub fn encode_char(&self, c: T) -> Option<Vec<char>> {
+        match self {
+            Self::Tree(l, r) => {
+                if let Some(mut v) = l.encode_char(c) {
+                    v.insert(0, '0');
+                    return Some(v);
+                }
+                if let Some(mut v) = r.encode_char(c) {
+                    v.insert(0, '1');
+                    return Some(v);
+                }
+                None
+            }
+            Self::Leaf(nc) => {
+                if c == *nc {
+                    Some(Vec::new())
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub fn decode_char(&self, encoding: &[char], start: &mut usize) -> Option<T> {
+        match self {
+            Self::Tree(l, r) => {
+                if encoding.len() <= *start {
+                    return None;
+                }
+                if encoding[*start] == '0' {
+                    *start += 1;
+                    l.decode_char(encoding, start)
+                } else {
+                    *start += 1;
+                    r.decode_char(encoding, start)
+                }
+            }
+            Self::Leaf(nc) => {
+                Some(*nc)
+            }
+        }
+    }
+}
+
+impl HuffNode {
+    pub fn encode_str(&self, s: &str) -> Option<Vec<char>> {
+        let codes = self.codes();
+        let mut res = Vec::new();
+        for c in s.chars() {
+            res.extend(codes.get(&c)?);
+        }
+        Some(res)
+    }
+
+    pub fn decode_str(&self, encoding: &[char]) -> String {
+        let mut res = Vec::new();
+        let mut start = 0;
+        while start < encoding.len() {
+            if let Some(v) = self.decode_char(encoding, &mut start) {
+                res.push(v);
+            }
+        }
+        res.into_iter().collect()
+    }
+}
+
+pub struct HScore<T = char> {
+    h: HuffNode<T>,
+    s: u64,
+    // ties go to the older one, so the same counts always make the same tree
+    id: usize
+}
+
+// BinaryHeap is a max-heap, so the lowest score is the greatest
+impl<T> Ord for HScore<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.s.cmp(&self.s).then(other.id.cmp(&self.id))
+    }
+}
+
+impl<T> PartialOrd for HScore<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for HScore<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for HScore<T> {}
+
+pub fn build_tree(s: &str) -> HuffNode {
+    build_tree_from(s.chars()).expect("no tree for an empty string")
+}
+
+/// The tree for the symbols of items, None if there are none
+pub fn build_tree_from<T: Ord>(items: impl IntoIterator<Item = T>) -> Option<HuffNode<T>> {
+    let mut map = BTreeMap::new();
+    for c in items {
+        *map.entry(c).or_insert(0) += 1;
+    }
+    build_tree_from_counts(map)
+}
+
+/// The tree for symbols that come count times each, None if there are none.
+/// The two lowest scores are merged until one tree is left, O(n log n).
+pub fn build_tree_from_counts<T>(counts: impl IntoIterator<Item = (T, u64)>) -> Option<HuffNode<T>> {
+    let mut heap: BinaryHeap<HScore<T>> = counts
+        .into_iter()
+        .enumerate()
+        .map(|(id, (k, s))| HScore { h: HuffNode::Leaf(k), s, id })
+        .collect();
+    let mut id = heap.len();
+    while heap.len() > 1 {
+        let a_node = heap.pop().unwrap();
+        let b_node = heap.pop().unwrap();
+        let nnode = HuffNode::Tree(Box::new(a_node.h), Box::new(b_node.h));
+        heap.push(HScore {
+            h: nnode,
+            s: a_node.s + b_node.s,
+            id
+        });
+        id += 1;
+    }
+    heap.pop().map(|t| t.h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_tree, build_tree_from_counts};
+
+    #[test]
+    fn test_build_tree() {
+        let t = build_tree_from_counts([('a', 1), ('b', 1), ('c', 2), ('d', 4), ('e', 9)]).unwrap();
+        let mut lengths = t.code_lengths();
+        lengths.sort();
+        assert_eq!(lengths, [('a', 4), ('b', 4), ('c', 3), ('d', 2), ('e', 1)]);
+        assert!(build_tree_from_counts::<char>([]).is_none());
+
+        let s = "at an apple app";
+        let t = build_tree(s);
+        let codes = t.codes();
+        assert_eq!(codes[&'n'], t.encode_char('n').unwrap());
+        assert_eq!(t.decode_str(&t.encode_str(s).unwrap()), s);
+    }
+}
@@ -0,0 +1,64 @@
+use std::fs;
+use std::process::Command;
+
+const HUFF: &str = env!("CARGO_BIN_EXE_huff");
+
+fn huff(args: &[&str]) -> (bool, String) {
+    let out = Command::new(HUFF).args(args).output().unwrap();
+    (out.status.success(), String::from_utf8(out.stderr).unwrap())
+}
+
+#[test]
+fn compresses_and_decompresses_a_binary_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("data.bin");
+    //every byte value, mostly a few of them
+    let data: Vec<u8> = (0..20000u32).map(|i| if i % 7 == 0 { (i % 256) as u8 } else { (i % 3) as u8 }).collect();
+    fs::write(&file, &data).unwrap();
+    let path = file.to_str().unwrap();
+
+    assert!(huff(&["compress", path]).0);
+    let compressed = fs::read(format!("{}.huf", path)).unwrap();
+    assert!(compressed.len() < data.len() / 2);
+    fs::remove_file(&file).unwrap();
+    assert!(huff(&["decompress", &format!("{}.huf", path)]).0);
+    assert_eq!(fs::read(&file).unwrap(), data);
+
+    let out = dir.path().join("copy.bin");
+    assert!(huff(&["decompress", &format!("{}.huf", path), "-o", out.to_str().unwrap()]).0);
+    assert_eq!(fs::read(&out).unwrap(), data);
+}
+
+#[test]
+fn bad_input_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("plain.txt");
+    fs::write(&file, "not compressed").unwrap();
+    let path = file.to_str().unwrap();
+    let (ok, err) = huff(&["decompress", path, "-o", path, "--force"]);
+    assert!(!ok);
+    assert!(err.contains("not a compressed file"), "{}", err);
+    //the output was left alone
+    assert_eq!(fs::read_to_string(&file).unwrap(), "not compressed");
+    let (ok, err) = huff(&["decompress", path]);
+    assert!(!ok && err.contains("no .huf suffix"), "{}", err);
+}
+
+#[test]
+fn existing_output_needs_force() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("notes.txt");
+    fs::write(&file, "some notes, some notes").unwrap();
+    fs::write(dir.path().join("notes.txt.huf"), "older").unwrap();
+    let path = file.to_str().unwrap();
+    let (ok, err) = huff(&["compress", path]);
+    assert!(!ok && err.contains("already exists"), "{}", err);
+    assert_eq!(fs::read_to_string(dir.path().join("notes.txt.huf")).unwrap(), "older");
+    assert!(huff(&["compress", path, "-f"]).0);
+
+    //the output can be the input, it is only replaced at the end
+    assert!(huff(&["compress", path, "-o", path, "--force"]).0);
+    assert!(fs::read(&file).unwrap().starts_with(b"HUF"));
+    assert!(huff(&["decompress", path, "-o", path, "--force"]).0);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "some notes, some notes");
+}
@@ -0,0 +1,84 @@
+//compress then decompress gives back the data, for random data of many shapes
+use l6_huffman_tree::{compress, decompress, HuffReader, HuffWriter};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::{Read, Write};
+
+fn round_trip(data: &[u8]) {
+    let compressed = compress(data, Vec::new()).unwrap();
+    assert_eq!(decompress(&compressed[..]).unwrap(), data, "{:?}", data);
+}
+
+#[test]
+fn random_data_round_trips() {
+    let mut rng = StdRng::seed_from_u64(1);
+    for len in [0, 1, 2, 3, 7, 8, 9, 100, 1000, 10000] {
+        //from one byte value to all of them
+        for symbols in [1, 2, 3, 17, 256] {
+            let data: Vec<u8> = (0..len).map(|_| rng.gen_range(0..symbols) as u8).collect();
+            round_trip(&data);
+        }
+        //skewed, some bytes much more often than others
+        let data: Vec<u8> = (0..len).map(|_| (rng.gen::<f64>().powi(4) * 256.0) as u8).collect();
+        round_trip(&data);
+    }
+}
+
+#[test]
+fn deep_trees_round_trip() {
+    //fibonacci counts give the deepest tree for their size
+    let (mut a, mut b) = (1, 1);
+    let mut data = Vec::new();
+    for symbol in 0..25u8 {
+        data.extend(std::iter::repeat_n(symbol, a));
+        (a, b) = (b, a + b);
+    }
+    round_trip(&data);
+}
+
+#[test]
+fn streams_round_trip() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..50 {
+        let len = rng.gen_range(0..5000);
+        let symbols = rng.gen_range(1..=256);
+        let data: Vec<u8> = (0..len).map(|_| rng.gen_range(0..symbols) as u8).collect();
+        //written and read in random chunks, into blocks of random sizes
+        let mut w = HuffWriter::with_block_size(Vec::new(), rng.gen_range(1..2000));
+        let mut rest = &data[..];
+        while !rest.is_empty() {
+            let n = w.write(&rest[..rng.gen_range(1..=rest.len())]).unwrap();
+            rest = &rest[n..];
+            if rng.gen_bool(0.1) {
+                w.flush().unwrap();
+            }
+        }
+        let compressed = w.finish().unwrap();
+        let mut r = HuffReader::new(&compressed[..]);
+        let mut res = Vec::new();
+        loop {
+            let mut buf = vec![0; rng.gen_range(1..3000)];
+            match r.read(&mut buf).unwrap() {
+                0 => break,
+                n => res.extend_from_slice(&buf[..n]),
+            }
+        }
+        assert_eq!(res, data);
+    }
+}
+
+#[test]
+fn corrupt_data_does_not_panic() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let data: Vec<u8> = (0..500).map(|_| rng.gen_range(0..40)).collect();
+    let compressed = compress(&data, Vec::new()).unwrap();
+    for _ in 0..500 {
+        let mut bad = compressed.clone();
+        let i = rng.gen_range(0..bad.len());
+        bad[i] ^= 1 << rng.gen_range(0..8);
+        //either an error or some bytes, never a panic
+        let _ = decompress(&bad[..]);
+        bad.truncate(rng.gen_range(0..bad.len()));
+        let _ = decompress(&bad[..]);
+    }
+}